@@ -0,0 +1,237 @@
+//! Achievements, unlocked once and kept in the [`SaveData`].
+//!
+//! Each achievement is registered with [`register_achievement`] next to the
+//! systems and observers that detect it, which trigger [`Unlock`].
+
+use crate::{
+    camera::CameraBounds,
+    damage::Died,
+    level::{Key, LevelComplete, LevelGeometry},
+    player::Player,
+    registry::{Achievement, Registry, register_achievement},
+    save::SaveData,
+    weapon::{Explosion, Shotgun, WeaponFired},
+};
+use avian2d::prelude::Gravity;
+use bevy::prelude::*;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Pacifist>()
+        .init_resource::<ShotgunBlast>()
+        .init_resource::<RocketJump>()
+        .add_systems(
+            FixedUpdate,
+            (reset_pacifist, shotgun_blast_window, rocket_jump),
+        )
+        .add_systems(Update, toasts)
+        .add_observer(unlock)
+        .add_observer(pacifist_fired)
+        .add_observer(pacifist_complete)
+        .add_observer(shotgun_blast_fired)
+        .add_observer(shotgun_blast_key)
+        .add_observer(rocket_jump_explosion);
+
+    register_achievement(app, PACIFIST);
+    register_achievement(app, KEY_SWEEP);
+    register_achievement(app, OVER_THE_TOP);
+}
+
+/// Triggered to unlock the [`Achievement`] with this id, if it is not already.
+#[derive(Event)]
+pub struct Unlock(pub &'static str);
+
+/// Seconds an unlock toast stays on screen.
+const TOAST_DURATION: f32 = 4.0;
+
+#[derive(Component)]
+struct Toast(Timer);
+
+fn unlock(
+    unlock: On<Unlock>,
+    mut commands: Commands,
+    registry: Res<Registry>,
+    mut save: ResMut<SaveData>,
+) {
+    let Some(achievement) = registry
+        .achievements
+        .iter()
+        .find(|achievement| achievement.id == unlock.0)
+    else {
+        warn!("unknown achievement {}", unlock.0);
+        return;
+    };
+    if save.achievements.contains(achievement.id) {
+        return;
+    }
+    info!("achievement unlocked: {}", achievement.name);
+    save.achievements.insert(achievement.id.to_string());
+
+    commands.spawn((
+        Toast(Timer::from_seconds(TOAST_DURATION, TimerMode::Once)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(16.0),
+            right: Val::Px(16.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            flex_direction: FlexDirection::Column,
+            ..Default::default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.7)),
+        children![
+            Text::new(format!("Achievement unlocked: {}", achievement.name)),
+            (
+                Text::new(achievement.description),
+                TextFont::from_font_size(14.0),
+            ),
+        ],
+    ));
+}
+
+fn toasts(mut commands: Commands, time: Res<Time>, mut toasts: Query<(Entity, &mut Toast)>) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        if toast.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+const PACIFIST: Achievement = Achievement {
+    id: "pacifist",
+    name: "Pacifist",
+    description: "Finish a level without firing.",
+};
+
+/// Whether a weapon has fired since the level loaded.
+#[derive(Default, Resource)]
+struct Pacifist {
+    fired: bool,
+}
+
+fn reset_pacifist(mut pacifist: ResMut<Pacifist>, new_level: Query<(), Added<LevelGeometry>>) {
+    if !new_level.is_empty() {
+        pacifist.fired = false;
+    }
+}
+
+fn pacifist_fired(_: On<WeaponFired>, mut pacifist: ResMut<Pacifist>) {
+    pacifist.fired = true;
+}
+
+fn pacifist_complete(_: On<LevelComplete>, mut commands: Commands, pacifist: Res<Pacifist>) {
+    if !pacifist.fired {
+        commands.trigger(Unlock(PACIFIST.id));
+    }
+}
+
+const KEY_SWEEP: Achievement = Achievement {
+    id: "key_sweep",
+    name: "Clean Sweep",
+    description: "Destroy 3 keys with one shotgun blast.",
+};
+
+/// Seconds after a shotgun fires in which destroyed keys count toward its
+/// blast, the lifetime of its pellets.
+const SHOTGUN_BLAST_WINDOW: f32 = 0.8;
+
+#[derive(Default, Resource)]
+struct ShotgunBlast {
+    remaining: f32,
+    keys: u32,
+}
+
+fn shotgun_blast_window(time: Res<Time>, mut blast: ResMut<ShotgunBlast>) {
+    blast.remaining = (blast.remaining - time.delta_secs()).max(0.0);
+}
+
+fn shotgun_blast_fired(
+    fired: On<WeaponFired>,
+    shotguns: Query<(), With<Shotgun>>,
+    mut blast: ResMut<ShotgunBlast>,
+) {
+    if shotguns.contains(fired.entity) {
+        blast.remaining = SHOTGUN_BLAST_WINDOW;
+        blast.keys = 0;
+    }
+}
+
+fn shotgun_blast_key(
+    died: On<Died>,
+    mut commands: Commands,
+    keys: Query<(), With<Key>>,
+    mut blast: ResMut<ShotgunBlast>,
+) {
+    if !keys.contains(died.entity) || blast.remaining <= 0.0 {
+        return;
+    }
+    blast.keys += 1;
+    if blast.keys == 3 {
+        commands.trigger(Unlock(KEY_SWEEP.id));
+    }
+}
+
+const OVER_THE_TOP: Achievement = Achievement {
+    id: "over_the_top",
+    name: "Over the Top",
+    description: "Rocket jump over the top of a level.",
+};
+
+/// Distance from an [`Explosion`] within which a player is launched by it.
+const ROCKET_JUMP_RADIUS: f32 = 200.0;
+
+/// Seconds after an explosion that a player counts as rocket jumping.
+const ROCKET_JUMP_DURATION: f32 = 2.0;
+
+/// Seconds left of the current rocket jump for each launched player.
+#[derive(Default, Resource)]
+struct RocketJump(Vec<(Entity, f32)>);
+
+fn rocket_jump_explosion(
+    explosion: On<Explosion>,
+    players: Query<(Entity, &GlobalTransform), With<Player>>,
+    mut jumps: ResMut<RocketJump>,
+) {
+    for (player, transform) in players.iter() {
+        if transform.translation().xy().distance(explosion.position) > ROCKET_JUMP_RADIUS {
+            continue;
+        }
+        jumps.0.retain(|(entity, _)| *entity != player);
+        jumps.0.push((player, ROCKET_JUMP_DURATION));
+    }
+}
+
+fn rocket_jump(
+    mut commands: Commands,
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    // Only tracked by the camera, so absent when headless.
+    bounds: Option<Res<CameraBounds>>,
+    players: Query<&GlobalTransform, With<Player>>,
+    mut jumps: ResMut<RocketJump>,
+) {
+    let Some(bounds) = bounds.filter(|bounds| !bounds.0.is_empty()) else {
+        jumps.0.clear();
+        return;
+    };
+    if jumps.0.is_empty() {
+        return;
+    }
+    let dt = time.delta_secs();
+    let mut unlocked = false;
+    jumps.0.retain_mut(|(player, remaining)| {
+        *remaining -= dt;
+        let Ok(transform) = players.get(*player) else {
+            return false;
+        };
+        let y = transform.translation().y;
+        // The top is wherever gravity points away from.
+        unlocked |= if gravity.0.y > 0.0 {
+            y < bounds.0.min.y
+        } else {
+            y > bounds.0.max.y
+        };
+        *remaining > 0.0
+    });
+    if unlocked {
+        commands.trigger(Unlock(OVER_THE_TOP.id));
+    }
+}
@@ -0,0 +1,77 @@
+//! Locates the `assets/` folder at runtime and stages it for distribution.
+
+use bevy::prelude::*;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// The `assets/` folder read by the [`AssetPlugin`] and by direct file access.
+///
+/// Looked up next to the executable, then in the working directory, then in
+/// the crate root the binary was built from.
+pub fn root() -> &'static Path {
+    static ROOT: OnceLock<PathBuf> = OnceLock::new();
+    ROOT.get_or_init(|| {
+        let exe = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join("assets")));
+        let cwd = std::env::current_dir().ok().map(|dir| dir.join("assets"));
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        exe.into_iter()
+            .chain(cwd)
+            .find(|dir| dir.is_dir())
+            .unwrap_or(manifest)
+    })
+}
+
+/// `relative` inside the [`root`] asset folder.
+pub fn path(relative: impl AsRef<Path>) -> PathBuf {
+    root().join(relative)
+}
+
+/// An [`AssetPlugin`] that reads from [`root`].
+pub fn asset_plugin() -> AssetPlugin {
+    AssetPlugin {
+        file_path: root().to_string_lossy().into_owned(),
+        ..Default::default()
+    }
+}
+
+/// Copies the running executable and the asset folder into `out`.
+///
+/// Fails when built with the `dynamic_linking` feature, as the executable
+/// would not start without the dylibs in `target/`.
+pub fn package(out: &Path) -> io::Result<()> {
+    if cfg!(feature = "dynamic_linking") {
+        return Err(io::Error::other(
+            "built with `dynamic_linking`, rebuild with `--no-default-features --features debug`",
+        ));
+    }
+    fs::create_dir_all(out)?;
+    let exe = std::env::current_exe()?;
+    if let Some(name) = exe.file_name() {
+        fs::copy(&exe, out.join(name))?;
+    }
+    // `out` may be inside the assets, which must not copy into itself.
+    let out = fs::canonicalize(out)?;
+    copy_dir(root(), &out.join("assets"), &out)
+}
+
+/// Copies `from` into `to` recursively, leaving out `skip`.
+fn copy_dir(from: &Path, to: &Path, skip: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if fs::canonicalize(entry.path())? != skip {
+                copy_dir(&entry.path(), &target, skip)?;
+            }
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,394 @@
+use crate::settings::Settings;
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    audio::{SpatialScale, Volume},
+    platform::collections::HashMap,
+    prelude::*,
+    window::WindowFocused,
+};
+use rand::Rng;
+use serde::Deserialize;
+
+pub fn plugin(app: &mut App) {
+    app.add_message::<PlaySfx>()
+        .init_asset::<SfxSets>()
+        .init_asset_loader::<SfxSetsLoader>()
+        .init_resource::<Muted>()
+        .add_systems(Startup, load_sfx)
+        .add_systems(
+            Update,
+            (
+                (reload_sfx, ui_click, play_sfx).chain(),
+                bus_volumes,
+                (mute_on_focus_loss, mute_new_sinks).chain(),
+            ),
+        );
+}
+
+/// Volume channel that a sound plays through.
+///
+/// The final volume is the channel volume scaled by the master volume in
+/// [`Settings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Ui,
+}
+
+impl AudioBus {
+    pub fn volume(self, settings: &Settings) -> f32 {
+        let bus = match self {
+            Self::Music => settings.music_volume,
+            Self::Sfx => settings.sfx_volume,
+            Self::Ui => settings.ui_volume,
+        };
+        settings.master_volume * bus
+    }
+}
+
+/// Identifies a sound effect in [`SfxHandles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Sfx {
+    Shotgun,
+    AssaultRifle,
+    Rocket,
+    Jump,
+    Land,
+    Door,
+    KeyDestroyed,
+    Death,
+    Click,
+    Explosion,
+    /// A bullet hitting something.
+    Impact,
+}
+
+impl Sfx {
+    pub const ALL: [Self; 11] = [
+        Self::Shotgun,
+        Self::AssaultRifle,
+        Self::Rocket,
+        Self::Jump,
+        Self::Land,
+        Self::Door,
+        Self::KeyDestroyed,
+        Self::Death,
+        Self::Click,
+        Self::Explosion,
+        Self::Impact,
+    ];
+
+    fn bus(self) -> AudioBus {
+        match self {
+            Self::Click => AudioBus::Ui,
+            _ => AudioBus::Sfx,
+        }
+    }
+
+    /// Played when the sound has no set in [`SFX_PATH`].
+    fn path(self) -> &'static str {
+        match self {
+            Self::Shotgun => "audio/shotgun.wav",
+            Self::AssaultRifle => "audio/assault_rifle.wav",
+            Self::Rocket => "audio/rocket.wav",
+            Self::Jump => "audio/jump.wav",
+            Self::Land => "audio/land.wav",
+            Self::Door => "audio/door.wav",
+            Self::KeyDestroyed => "audio/key.wav",
+            Self::Death => "audio/death.wav",
+            Self::Click => "audio/click.wav",
+            Self::Explosion => "audio/explosion.wav",
+            Self::Impact => "audio/land.wav",
+        }
+    }
+}
+
+/// Converts world units into spatial audio distance.
+///
+/// Sounds fall off quickly past a few units, so this decides how far off-screen
+/// a positional sound is still audible.
+const SPATIAL_SCALE: f32 = 1.0 / 300.0;
+
+/// Distance between the ears of the [`SpatialListener`] in world units.
+pub const LISTENER_GAP: f32 = 600.0;
+
+/// Plays a sound effect once.
+///
+/// Sounds with a `position` are panned and attenuated relative to the
+/// [`SpatialListener`].
+#[derive(Message)]
+pub struct PlaySfx {
+    pub sfx: Sfx,
+    pub position: Option<Vec2>,
+}
+
+impl PlaySfx {
+    pub fn new(sfx: Sfx) -> Self {
+        Self {
+            sfx,
+            position: None,
+        }
+    }
+
+    pub fn at(sfx: Sfx, position: Vec2) -> Self {
+        Self {
+            sfx,
+            position: Some(position),
+        }
+    }
+}
+
+const SFX_PATH: &str = "audio/sfx.ron";
+
+/// Variations of a sound effect, one picked at random each time it plays.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SfxSet {
+    pub paths: Vec<String>,
+    /// Scales the [`AudioBus`] volume.
+    pub volume: f32,
+    /// Playback speed is randomized within `1.0 ± pitch_variation`.
+    pub pitch_variation: f32,
+}
+
+impl Default for SfxSet {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            volume: 1.0,
+            pitch_variation: 0.0,
+        }
+    }
+}
+
+/// Sound sets loaded from `assets/audio/sfx.ron`.
+///
+/// Sounds missing from the file play [`Sfx::path`] unchanged.
+#[derive(Debug, Clone, Default, Asset, TypePath, Deserialize)]
+pub struct SfxSets(pub HashMap<Sfx, SfxSet>);
+
+#[derive(Default)]
+struct SfxSetsLoader;
+
+impl AssetLoader for SfxSetsLoader {
+    type Asset = SfxSets;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &(),
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sfx.ron"]
+    }
+}
+
+struct LoadedSfx {
+    handles: Vec<Handle<AudioSource>>,
+    volume: f32,
+    pitch_variation: f32,
+}
+
+#[derive(Resource)]
+pub struct SfxHandles {
+    sets: Handle<SfxSets>,
+    sfx: HashMap<Sfx, LoadedSfx>,
+}
+
+impl SfxHandles {
+    fn load(&mut self, server: &AssetServer, sets: &SfxSets) {
+        self.sfx = Sfx::ALL
+            .into_iter()
+            .map(|sfx| {
+                let set = sets.0.get(&sfx).cloned().unwrap_or_default();
+                let handles = if set.paths.is_empty() {
+                    vec![server.load(sfx.path())]
+                } else {
+                    set.paths.iter().map(|path| server.load(path)).collect()
+                };
+                let loaded = LoadedSfx {
+                    handles,
+                    volume: set.volume,
+                    pitch_variation: set.pitch_variation,
+                };
+                (sfx, loaded)
+            })
+            .collect();
+    }
+}
+
+fn load_sfx(mut commands: Commands, server: Res<AssetServer>) {
+    let mut handles = SfxHandles {
+        sets: server.load(SFX_PATH),
+        sfx: HashMap::default(),
+    };
+    // Plays the defaults until the sets load.
+    handles.load(&server, &SfxSets::default());
+    commands.insert_resource(handles);
+}
+
+fn reload_sfx(
+    mut events: MessageReader<AssetEvent<SfxSets>>,
+    server: Res<AssetServer>,
+    mut handles: ResMut<SfxHandles>,
+    sets: Res<Assets<SfxSets>>,
+) {
+    for event in events.read() {
+        if (event.is_loaded_with_dependencies(&handles.sets) || event.is_modified(&handles.sets))
+            && let Some(sets) = sets.get(&handles.sets)
+        {
+            info!("loaded {SFX_PATH}");
+            handles.load(&server, sets);
+        }
+    }
+}
+
+/// Volume of an [`SfxSet`], kept on the sound while it plays.
+#[derive(Component)]
+struct SfxVolume(f32);
+
+fn play_sfx(
+    mut commands: Commands,
+    mut reader: MessageReader<PlaySfx>,
+    handles: Res<SfxHandles>,
+    settings: Res<Settings>,
+) {
+    // Presentation only, so the global RNG is left alone to keep runs
+    // deterministic.
+    let mut rng = rand::rng();
+    for PlaySfx { sfx, position } in reader.read() {
+        let Some(loaded) = handles.sfx.get(sfx) else {
+            continue;
+        };
+        if loaded.handles.is_empty() {
+            continue;
+        }
+        let handle = &loaded.handles[rng.random_range(0..loaded.handles.len())];
+        let speed = if loaded.pitch_variation > 0.0 {
+            1.0 + rng.random_range(-loaded.pitch_variation..=loaded.pitch_variation)
+        } else {
+            1.0
+        };
+        let bus = sfx.bus();
+        let playback = PlaybackSettings::DESPAWN
+            .with_volume(Volume::Linear(bus.volume(&settings) * loaded.volume))
+            .with_speed(speed);
+        let mut entity = commands.spawn((
+            Name::new(format!("{sfx:?}")),
+            bus,
+            SfxVolume(loaded.volume),
+            AudioPlayer(handle.clone()),
+            playback,
+        ));
+        if let Some(position) = position {
+            entity.insert((
+                Transform::from_translation(position.extend(0.0)),
+                playback
+                    .with_spatial(true)
+                    .with_spatial_scale(SpatialScale::new_2d(SPATIAL_SCALE)),
+            ));
+        }
+    }
+}
+
+/// Applies [`Settings`] volume changes to sounds that are already playing.
+///
+/// [`AudioBus::Music`] volume is managed by the music crossfade.
+fn bus_volumes(
+    settings: Res<Settings>,
+    mut sinks: Query<(
+        &AudioBus,
+        Option<&SfxVolume>,
+        Option<&mut AudioSink>,
+        Option<&mut SpatialAudioSink>,
+    )>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (bus, sfx_volume, sink, spatial_sink) in sinks.iter_mut() {
+        if *bus == AudioBus::Music {
+            continue;
+        }
+        let scale = sfx_volume.map_or(1.0, |volume| volume.0);
+        let volume = Volume::Linear(bus.volume(&settings) * scale);
+        if let Some(mut sink) = sink {
+            sink.set_volume(volume);
+        }
+        if let Some(mut sink) = spatial_sink {
+            sink.set_volume(volume);
+        }
+    }
+}
+
+/// Whether all audio is silenced because the window lost focus.
+#[derive(Default, Resource)]
+pub struct Muted(pub bool);
+
+fn mute_on_focus_loss(
+    mut reader: MessageReader<WindowFocused>,
+    settings: Res<Settings>,
+    mut muted: ResMut<Muted>,
+    mut sinks: Query<&mut AudioSink>,
+    mut spatial_sinks: Query<&mut SpatialAudioSink>,
+) {
+    let Some(focus) = reader.read().last() else {
+        return;
+    };
+    let mute = !focus.focused && settings.mute_on_focus_loss;
+    if mute == muted.0 {
+        return;
+    }
+    muted.0 = mute;
+
+    for mut sink in sinks.iter_mut() {
+        if mute {
+            sink.mute();
+        } else {
+            sink.unmute();
+        }
+    }
+    for mut sink in spatial_sinks.iter_mut() {
+        if mute {
+            sink.mute();
+        } else {
+            sink.unmute();
+        }
+    }
+}
+
+fn mute_new_sinks(
+    muted: Res<Muted>,
+    mut sinks: Query<&mut AudioSink, Added<AudioSink>>,
+    mut spatial_sinks: Query<&mut SpatialAudioSink, Added<SpatialAudioSink>>,
+) {
+    if !muted.0 {
+        return;
+    }
+    for mut sink in sinks.iter_mut() {
+        sink.mute();
+    }
+    for mut sink in spatial_sinks.iter_mut() {
+        sink.mute();
+    }
+}
+
+fn ui_click(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut writer: MessageWriter<PlaySfx>,
+) {
+    for interaction in buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            writer.write(PlaySfx::new(Sfx::Click));
+        }
+    }
+}
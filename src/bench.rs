@@ -0,0 +1,171 @@
+use crate::{
+    coop::Partner,
+    effects::GLOW,
+    level::{Wall, rectangle},
+    player::Player,
+    weapon::Bullet,
+};
+use avian2d::prelude::{Collider, GravityScale, LinearVelocity, PhysicsSystems};
+use bevy::{ecs::entity::Entities, prelude::*};
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PhysicsStepTime>()
+        .add_systems(
+            FixedPostUpdate,
+            (
+                start_physics_step.in_set(PhysicsSystems::First),
+                end_physics_step.in_set(PhysicsSystems::Last),
+            ),
+        )
+        .add_systems(
+            Update,
+            (spawn_bench.run_if(resource_added::<Bench>), measure_bench)
+                .chain()
+                .run_if(resource_exists::<Bench>),
+        );
+}
+
+/// Frames measured in a [`Bench`] run.
+const FRAMES: u32 = 120;
+
+#[derive(Debug, Clone, Copy)]
+pub enum BenchKind {
+    /// Dynamic bullets flying in random directions.
+    Bullets,
+    /// Static walls in a grid.
+    Walls,
+}
+
+/// Spawns `count` entities and reports timings to the terminal after [`FRAMES`].
+///
+/// Spawned entities are despawned when the run completes.
+#[derive(Resource)]
+pub struct Bench {
+    kind: BenchKind,
+    count: usize,
+    frames: u32,
+    frame_time: Duration,
+    physics_time: Duration,
+    physics_steps: u32,
+}
+
+impl Bench {
+    pub fn new(kind: BenchKind, count: usize) -> Self {
+        Self {
+            kind,
+            count,
+            frames: 0,
+            frame_time: Duration::ZERO,
+            physics_time: Duration::ZERO,
+            physics_steps: 0,
+        }
+    }
+}
+
+#[derive(Component)]
+struct BenchEntity;
+
+/// Duration of the physics steps since the last frame.
+#[derive(Default, Resource)]
+struct PhysicsStepTime {
+    start: Option<Instant>,
+    elapsed: Duration,
+    steps: u32,
+}
+
+fn start_physics_step(mut step: ResMut<PhysicsStepTime>) {
+    step.start = Some(Instant::now());
+}
+
+fn end_physics_step(mut step: ResMut<PhysicsStepTime>) {
+    if let Some(start) = step.start.take() {
+        step.elapsed += start.elapsed();
+        step.steps += 1;
+    }
+}
+
+fn spawn_bench(
+    mut commands: Commands,
+    bench: Res<Bench>,
+    player: Single<&GlobalTransform, (With<Player>, Without<Partner>)>,
+) {
+    let origin = player.translation().xy();
+    let mut rng = rand::rng();
+    info!("bench: spawning {} {:?}", bench.count, bench.kind);
+
+    match bench.kind {
+        BenchKind::Bullets => {
+            for _ in 0..bench.count {
+                let direction = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU));
+                commands.spawn((
+                    BenchEntity,
+                    Bullet,
+                    LinearVelocity(direction * rng.random_range(200.0..1_000.0)),
+                    Transform::from_translation(origin.extend(0.0)),
+                    Collider::circle(5.0),
+                    Sprite::from_color(GLOW, Vec2::splat(10.0)),
+                    GravityScale(0.0),
+                ));
+            }
+        }
+        BenchKind::Walls => {
+            let columns = (bench.count as f32).sqrt().ceil() as usize;
+            let spacing = 30.0;
+            let start = origin + Vec2::new(-(columns as f32) * spacing * 0.5, 300.0);
+            for i in 0..bench.count {
+                let offset = Vec2::new((i % columns) as f32, (i / columns) as f32) * spacing;
+                commands.spawn((
+                    BenchEntity,
+                    Wall,
+                    Transform::from_translation((start + offset).extend(0.0)),
+                    rectangle(20.0, 20.0),
+                ));
+            }
+        }
+    }
+}
+
+fn measure_bench(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut bench: ResMut<Bench>,
+    mut step: ResMut<PhysicsStepTime>,
+    entities: &Entities,
+    spawned: Query<Entity, With<BenchEntity>>,
+) {
+    // Skip the frame that spawned the entities.
+    if bench.is_added() {
+        *step = PhysicsStepTime::default();
+        return;
+    }
+
+    bench.frames += 1;
+    bench.frame_time += time.delta();
+    bench.physics_time += step.elapsed;
+    bench.physics_steps += step.steps;
+    *step = PhysicsStepTime::default();
+
+    if bench.frames < FRAMES {
+        return;
+    }
+
+    let frame_ms = bench.frame_time.as_secs_f64() * 1_000.0 / bench.frames as f64;
+    let physics_ms = if bench.physics_steps == 0 {
+        0.0
+    } else {
+        bench.physics_time.as_secs_f64() * 1_000.0 / bench.physics_steps as f64
+    };
+    info!(
+        "bench {} {:?}: frame {frame_ms:.2}ms, physics step {physics_ms:.2}ms, {} entities",
+        bench.count,
+        bench.kind,
+        entities.len(),
+    );
+
+    for entity in spawned.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<Bench>();
+}
@@ -0,0 +1,174 @@
+//! Bullet time, which slows the game down while [`SlowMotion`] is held.
+//!
+//! [`Time<Virtual>`] and the fixed timestep are scaled together, so physics,
+//! tweens and timers all slow down while the fixed schedules keep running at
+//! the same rate in real time. Input is evaluated every fixed step, so aiming
+//! stays as responsive as at full speed.
+
+use crate::{
+    menu::GameState,
+    physics::PhysicsConfig,
+    player::{Killed, Player, PlayerSet, SlowMotion},
+};
+use bevy::{color::palettes::css::SKY_BLUE, prelude::*};
+use bevy_enhanced_input::prelude::{Complete, Start};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<BulletTime>()
+        .add_systems(FixedUpdate, bullet_time.in_set(PlayerSet))
+        .add_systems(Update, bullet_time_bar)
+        .add_systems(OnExit(GameState::Playing), end_bullet_time)
+        .add_observer(start_slow_motion)
+        .add_observer(end_slow_motion)
+        .add_observer(killed);
+}
+
+/// Speed of the game while bullet time is active.
+pub const BULLET_TIME_SCALE: f32 = 0.3;
+
+/// Real seconds of bullet time in a full meter.
+const BULLET_TIME_METER: f32 = 3.0;
+
+/// Real seconds to refill an empty meter while bullet time is not active.
+const BULLET_TIME_RECHARGE: f32 = 8.0;
+
+/// Bullet time shared by all players, any of them can slow the game down.
+#[derive(Debug, Clone, Resource)]
+pub struct BulletTime {
+    /// Real seconds of bullet time left.
+    pub meter: f32,
+    pub active: bool,
+}
+
+impl Default for BulletTime {
+    fn default() -> Self {
+        Self {
+            meter: BULLET_TIME_METER,
+            active: false,
+        }
+    }
+}
+
+impl BulletTime {
+    pub fn scale(&self) -> f32 {
+        if self.active { BULLET_TIME_SCALE } else { 1.0 }
+    }
+}
+
+fn start_slow_motion(
+    slow_motion: On<Start<SlowMotion>>,
+    players: Query<(), With<Player>>,
+    mut bullet_time: ResMut<BulletTime>,
+) {
+    if players.contains(slow_motion.context) && bullet_time.meter > 0.0 {
+        bullet_time.active = true;
+    }
+}
+
+fn end_slow_motion(
+    slow_motion: On<Complete<SlowMotion>>,
+    players: Query<(), With<Player>>,
+    mut bullet_time: ResMut<BulletTime>,
+) {
+    if players.contains(slow_motion.context) {
+        bullet_time.active = false;
+    }
+}
+
+/// Bullet time is not held through a death, the [`Complete`] of a despawned
+/// player never fires.
+fn killed(_: On<Killed>, mut commands: Commands) {
+    commands.run_system_cached(end_bullet_time);
+}
+
+/// Stops bullet time and puts the clocks back to full speed.
+pub fn end_bullet_time(
+    mut bullet_time: ResMut<BulletTime>,
+    config: Res<PhysicsConfig>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut fixed: ResMut<Time<Fixed>>,
+) {
+    bullet_time.active = false;
+    virtual_time.set_relative_speed(1.0);
+    fixed.set_timestep_seconds(1.0 / config.hz.max(1.0));
+}
+
+/// Refills the meter and stops bullet time, when the level resets.
+pub fn reset_bullet_time(mut commands: Commands, mut bullet_time: ResMut<BulletTime>) {
+    *bullet_time = BulletTime::default();
+    commands.run_system_cached(end_bullet_time);
+}
+
+/// Drains or refills the meter by the real duration of a step, and scales
+/// the clocks to match.
+///
+/// Runs in the fixed schedule so that a replay slows down on the same steps.
+fn bullet_time(
+    mut bullet_time: ResMut<BulletTime>,
+    config: Res<PhysicsConfig>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut fixed: ResMut<Time<Fixed>>,
+) {
+    let step = 1.0 / config.hz.max(1.0);
+    if bullet_time.active {
+        bullet_time.meter -= step as f32;
+        if bullet_time.meter <= 0.0 {
+            bullet_time.meter = 0.0;
+            bullet_time.active = false;
+        }
+    } else if bullet_time.meter < BULLET_TIME_METER {
+        bullet_time.meter = (bullet_time.meter
+            + step as f32 * BULLET_TIME_METER / BULLET_TIME_RECHARGE)
+            .min(BULLET_TIME_METER);
+    }
+
+    let scale = bullet_time.scale();
+    // `apply_physics_config` resets the timestep when the config changes.
+    if virtual_time.relative_speed() != scale || config.is_changed() {
+        virtual_time.set_relative_speed(scale);
+        fixed.set_timestep_seconds(step * scale as f64);
+    }
+}
+
+/// Shows the [`BulletTime`] meter above the charge bar while it is in use or
+/// refilling.
+#[derive(Component)]
+struct BulletTimeBar;
+
+const BULLET_TIME_BAR_WIDTH: f32 = 120.0;
+
+fn bullet_time_bar(
+    mut commands: Commands,
+    bar: Option<Single<(&mut Node, &mut BackgroundColor), With<BulletTimeBar>>>,
+    bullet_time: Res<BulletTime>,
+) {
+    let Some(bar) = bar else {
+        commands.spawn((
+            BulletTimeBar,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(82.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-BULLET_TIME_BAR_WIDTH / 2.0)),
+                width: Val::Px(0.0),
+                height: Val::Px(4.0),
+                ..Default::default()
+            },
+            BackgroundColor(SKY_BLUE.into()),
+            Pickable::IGNORE,
+        ));
+        return;
+    };
+    let (mut node, mut color) = bar.into_inner();
+    let fraction = bullet_time.meter / BULLET_TIME_METER;
+    node.width = if fraction < 1.0 || bullet_time.active {
+        Val::Px(BULLET_TIME_BAR_WIDTH * fraction)
+    } else {
+        Val::Px(0.0)
+    };
+    color.0 = if bullet_time.active {
+        Color::WHITE
+    } else {
+        SKY_BLUE.into()
+    };
+}
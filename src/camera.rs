@@ -0,0 +1,499 @@
+use crate::{
+    audio,
+    coop::{Downed, Partner},
+    enemy::Enemy,
+    level::{LevelBounds, LevelGeometry, LevelLoading, Wall},
+    player::{AimVector, Killed, Player},
+    settings::Settings,
+    versus::Versus,
+    weapon::{Explosion, Shotgun, WeaponFired},
+};
+use avian2d::prelude::{ColliderAabb, Gravity, PhysicsSystems};
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use bevy_tween::prelude::EaseKind;
+use std::f32::consts::PI;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<CameraBounds>()
+        .register_type::<FollowCamera>()
+        .register_type::<ScreenShake>()
+        .add_systems(Startup, spawn_camera)
+        .add_systems(
+            Update,
+            (
+                remove_shake,
+                (
+                    (start_roll, roll).chain(),
+                    (
+                        spectate_while_downed.run_if(resource_exists::<Versus>),
+                        frame_players.run_if(not(resource_exists::<Spectating>)),
+                        spectate.run_if(resource_exists::<Spectating>),
+                    )
+                        .chain(),
+                ),
+                apply_shake,
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            update_camera_bounds.after(PhysicsSystems::Last),
+        )
+        .add_observer(add_trauma)
+        .add_observer(shake_on_shotgun)
+        .add_observer(shake_on_explosion)
+        .add_observer(shake_on_death);
+}
+
+#[derive(Component)]
+#[require(
+    Camera2d,
+    SpatialListener::new(audio::LISTENER_GAP),
+    FollowCamera,
+    ScreenShake
+)]
+pub struct MainCamera;
+
+/// How the camera tracks a lone player, see [`frame_players`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct FollowCamera {
+    /// Size of the area around the camera's focus in which the player moves
+    /// without moving the camera.
+    pub deadzone: Vec2,
+    /// Distance ahead of the player, along their [`AimVector`], that the
+    /// camera looks.
+    pub lookahead: f32,
+    /// Rate at which the camera eases toward its focus.
+    pub smoothing: f32,
+    #[reflect(ignore)]
+    focus: Vec2,
+}
+
+impl Default for FollowCamera {
+    fn default() -> Self {
+        Self {
+            deadzone: Vec2::new(120.0, 80.0),
+            lookahead: 100.0,
+            smoothing: 4.0,
+            focus: Vec2::ZERO,
+        }
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(MainCamera);
+}
+
+/// Seconds the camera takes to roll after gravity flips.
+const ROLL_DURATION: f32 = 0.3;
+
+/// Rotates the camera so that gravity always points down on screen.
+///
+/// UI is unaffected because it is not rendered through the camera transform.
+#[derive(Component)]
+struct CameraRoll {
+    from: f32,
+    to: f32,
+    elapsed: f32,
+}
+
+fn start_roll(
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    camera: Single<(Entity, &Transform), With<MainCamera>>,
+) {
+    if !gravity.is_changed() {
+        return;
+    }
+    let (entity, transform) = camera.into_inner();
+    let to = if gravity.0.y > 0.0 { PI } else { 0.0 };
+    let from = transform.rotation.to_euler(EulerRot::XYZ).2;
+    if (from - to).abs() > f32::EPSILON {
+        commands.trigger(Shake(0.4));
+        commands.entity(entity).insert(CameraRoll {
+            from,
+            to,
+            elapsed: 0.0,
+        });
+    }
+}
+
+fn roll(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera: Single<(Entity, &mut Transform, &mut CameraRoll)>,
+) {
+    let (entity, mut transform, mut roll) = camera.into_inner();
+    roll.elapsed += time.delta_secs();
+    let t = EaseKind::CubicInOut.sample((roll.elapsed / ROLL_DURATION).min(1.0));
+    transform.rotation = Quat::from_rotation_z(roll.from.lerp(roll.to, t));
+    if roll.elapsed >= ROLL_DURATION {
+        commands.entity(entity).remove::<CameraRoll>();
+    }
+}
+
+/// Trauma based camera shake.
+///
+/// [`Shake`] adds trauma, which decays over time. The camera is offset and
+/// rotated by smooth noise scaled by the square of the trauma, so small hits
+/// barely register and big ones stack up. Disabled by
+/// [`Settings::screen_shake`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ScreenShake {
+    /// Largest offset in pixels, at full trauma.
+    pub amplitude: f32,
+    /// Largest rotation in radians, at full trauma.
+    pub angle: f32,
+    /// Rate at which the noise changes direction.
+    pub frequency: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    #[reflect(ignore)]
+    trauma: f32,
+    /// Offset and rotation applied by [`apply_shake`] this frame.
+    #[reflect(ignore)]
+    applied: (Vec2, f32),
+}
+
+impl Default for ScreenShake {
+    fn default() -> Self {
+        Self {
+            amplitude: 24.0,
+            angle: 0.05,
+            frequency: 18.0,
+            decay: 1.5,
+            trauma: 0.0,
+            applied: (Vec2::ZERO, 0.0),
+        }
+    }
+}
+
+/// Adds trauma in `[0, 1]` to the [`ScreenShake`].
+#[derive(Event)]
+pub struct Shake(pub f32);
+
+fn add_trauma(
+    shake: On<Shake>,
+    settings: Res<Settings>,
+    mut camera: Single<&mut ScreenShake, With<MainCamera>>,
+) {
+    if settings.screen_shake {
+        camera.trauma = (camera.trauma + shake.0).min(1.0);
+    }
+}
+
+fn shake_on_shotgun(
+    fired: On<WeaponFired>,
+    mut commands: Commands,
+    shotguns: Query<(), With<Shotgun>>,
+) {
+    if shotguns.contains(fired.entity) {
+        commands.trigger(Shake(0.3));
+    }
+}
+
+fn shake_on_explosion(_: On<Explosion>, mut commands: Commands) {
+    commands.trigger(Shake(0.6));
+}
+
+fn shake_on_death(killed: On<Killed>, mut commands: Commands, players: Query<(), With<Player>>) {
+    if players.contains(killed.entity) {
+        commands.trigger(Shake(0.5));
+    }
+}
+
+/// Undoes last frame's shake, so that the camera systems work from the
+/// unshaken transform.
+fn remove_shake(camera: Single<(&mut Transform, &mut ScreenShake), With<MainCamera>>) {
+    let (mut transform, mut shake) = camera.into_inner();
+    let (offset, angle) = std::mem::take(&mut shake.applied);
+    transform.translation -= offset.extend(0.0);
+    transform.rotate_z(-angle);
+}
+
+fn apply_shake(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    camera: Single<(&mut Transform, &mut ScreenShake), With<MainCamera>>,
+) {
+    let (mut transform, mut shake) = camera.into_inner();
+    if !settings.screen_shake {
+        shake.trauma = 0.0;
+    }
+    shake.trauma = (shake.trauma - shake.decay * time.delta_secs()).max(0.0);
+    if shake.trauma <= 0.0 {
+        return;
+    }
+
+    let strength = shake.trauma * shake.trauma;
+    let t = time.elapsed_secs() * shake.frequency;
+    // Sums of incommensurate sines, smooth and never quite repeating.
+    let noise = |seed: f32| ((t + seed).sin() + (t * 2.3 + seed * 1.7).sin() * 0.5) / 1.5;
+    let offset = Vec2::new(noise(0.0), noise(31.0)) * shake.amplitude * strength;
+    let angle = noise(67.0) * shake.angle * strength;
+    transform.translation += offset.extend(0.0);
+    transform.rotate_z(angle);
+    shake.applied = (offset, angle);
+}
+
+/// Area the camera may show, the level's [`LevelBounds`] or else the bounds
+/// of its [`Wall`]s.
+///
+/// [`Rect::EMPTY`] until a level loads.
+#[derive(Resource)]
+pub struct CameraBounds(pub Rect);
+
+impl Default for CameraBounds {
+    fn default() -> Self {
+        Self(Rect::EMPTY)
+    }
+}
+
+fn update_camera_bounds(
+    loading: Res<LevelLoading>,
+    mut stale: Local<bool>,
+    walls: Query<&ColliderAabb, With<Wall>>,
+    level_bounds: Query<&LevelBounds, With<LevelGeometry>>,
+    mut bounds: ResMut<CameraBounds>,
+) {
+    if loading.0 {
+        *stale = true;
+        return;
+    }
+    if !*stale {
+        return;
+    }
+    *stale = false;
+    if let Some(level_bounds) = level_bounds.iter().next() {
+        bounds.0 = level_bounds.0;
+        return;
+    }
+    bounds.0 = walls.iter().fold(Rect::EMPTY, |rect, aabb| {
+        rect.union(Rect::from_corners(aabb.min, aabb.max))
+    });
+}
+
+/// Largest zoom out while framing co-op players.
+const MAX_ZOOM: f32 = 2.0;
+
+/// Fraction of the view kept clear around framed players.
+const FRAME_MARGIN: f32 = 0.25;
+
+/// Rate at which the camera eases toward its framing.
+const FRAME_RATE: f32 = 6.0;
+
+/// Seconds a co-op player may spend outside the view before they are killed.
+const TETHER_GRACE: f32 = 1.0;
+
+/// Keeps every standing player in view while a [`Partner`] has joined.
+///
+/// Targets the middle of the players and zooms out with their separation, up
+/// to [`MAX_ZOOM`], without showing past the [`CameraBounds`]. A player left
+/// outside the view for [`TETHER_GRACE`] seconds is [`Killed`], respawning on
+/// their partner.
+///
+/// Without a partner the camera follows the first player, see [`FollowCamera`].
+fn frame_players(
+    mut commands: Commands,
+    time: Res<Time>,
+    bounds: Res<CameraBounds>,
+    partners: Query<(), With<Partner>>,
+    players: Query<(Entity, &GlobalTransform), (With<Player>, Without<Downed>)>,
+    first: Query<(&GlobalTransform, &AimVector), (With<Player>, Without<Partner>)>,
+    camera: Single<(&Camera, &mut Transform, &mut Projection, &mut FollowCamera), With<MainCamera>>,
+    mut outside: Local<f32>,
+) {
+    let coop = !partners.is_empty() && !players.is_empty();
+    let (camera, mut transform, mut projection, mut follow) = camera.into_inner();
+    if !coop {
+        *outside = 0.0;
+    }
+    let Projection::Orthographic(ortho) = &mut *projection else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let (target, zoom) = if coop {
+        let framed = players.iter().fold(Rect::EMPTY, |rect, (_, transform)| {
+            rect.union_point(transform.translation().xy())
+        });
+        let zoom = (framed.size() / (viewport * (1.0 - FRAME_MARGIN))).max_element();
+        let zoom = zoom.clamp(1.0, MAX_ZOOM);
+        (
+            clamp_to_bounds(framed.center(), viewport * zoom, bounds.0),
+            zoom,
+        )
+    } else if let Ok((player, aim)) = first.single() {
+        let desired = player.translation().xy() + aim.0 * follow.lookahead;
+        let offset = desired - follow.focus;
+        let half = follow.deadzone * 0.5;
+        follow.focus += offset - offset.clamp(-half, half);
+        (clamp_to_bounds(follow.focus, viewport, bounds.0), 1.0)
+    } else {
+        (transform.translation.xy(), 1.0)
+    };
+
+    let rate = if coop { FRAME_RATE } else { follow.smoothing };
+    let t = 1.0 - (-rate * time.delta_secs()).exp();
+    let mut position = transform.translation.xy().lerp(target, t);
+    let mut scale = ortho.scale.lerp(zoom, t);
+    // Settle exactly, so that the camera stops changing once framed.
+    if position.distance_squared(target) < 0.01 && (scale - zoom).abs() < 0.001 {
+        position = target;
+        scale = zoom;
+    }
+    transform.translation = position.extend(transform.translation.z);
+    ortho.scale = scale;
+
+    if !coop {
+        return;
+    }
+    let view = Rect::from_center_size(position, viewport * ortho.scale);
+    let stray = players
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation().xy()))
+        .filter(|(_, player)| !view.contains(*player))
+        .max_by(|(_, a), (_, b)| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        });
+    match stray {
+        Some((entity, _)) => {
+            *outside += time.delta_secs();
+            if *outside >= TETHER_GRACE {
+                *outside = 0.0;
+                commands.trigger(Killed { entity });
+            }
+        }
+        None => *outside = 0.0,
+    }
+}
+
+/// Detaches the camera from the players.
+///
+/// WASD flies the camera and scrolling zooms. Tab cycles the focus between
+/// players and enemies, which the camera follows until it flies again.
+#[derive(Default, Resource)]
+pub struct Spectating {
+    pub focus: Option<Entity>,
+    /// Entered by [`spectate_while_downed`] rather than the terminal.
+    pub auto: bool,
+}
+
+/// Speed of the spectator camera at a zoom of 1.
+const SPECTATE_SPEED: f32 = 600.0;
+
+/// Zoom range of the spectator camera.
+const SPECTATE_ZOOM: (f32, f32) = (0.25, 4.0);
+
+/// Zoom change per scrolled line.
+const SPECTATE_ZOOM_STEP: f32 = 0.1;
+
+/// Spectates the standing players while the first player is [`Downed`] in
+/// [`Versus`] mode.
+fn spectate_while_downed(
+    mut commands: Commands,
+    spectating: Option<Res<Spectating>>,
+    first: Single<Has<Downed>, (With<Player>, Without<Partner>)>,
+    standing: Query<Entity, (With<Player>, Without<Downed>)>,
+) {
+    match (*first, spectating) {
+        (true, None) => commands.insert_resource(Spectating {
+            focus: standing.iter().next(),
+            auto: true,
+        }),
+        (false, Some(spectating)) if spectating.auto => {
+            commands.remove_resource::<Spectating>();
+        }
+        _ => {}
+    }
+}
+
+fn spectate(
+    time: Res<Time>,
+    input: Res<ButtonInput<KeyCode>>,
+    mut wheel: MessageReader<MouseWheel>,
+    mut spectating: ResMut<Spectating>,
+    targets: Query<(Entity, &GlobalTransform), Or<(With<Player>, With<Enemy>)>>,
+    camera: Single<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let (mut transform, mut projection) = camera.into_inner();
+    let Projection::Orthographic(ortho) = &mut *projection else {
+        return;
+    };
+
+    for event in wheel.read() {
+        ortho.scale = (ortho.scale * (1.0 - event.y.signum() * SPECTATE_ZOOM_STEP))
+            .clamp(SPECTATE_ZOOM.0, SPECTATE_ZOOM.1);
+    }
+
+    if input.just_pressed(KeyCode::Tab) {
+        let targets = targets.iter().map(|(entity, _)| entity).collect::<Vec<_>>();
+        let next = spectating
+            .focus
+            .and_then(|focus| targets.iter().position(|entity| *entity == focus))
+            .map_or(0, |index| index + 1);
+        spectating.focus = targets.get(next % targets.len().max(1)).copied();
+    }
+
+    let mut direction = Vec2::ZERO;
+    for (key, axis) in [
+        (KeyCode::KeyW, Vec2::Y),
+        (KeyCode::KeyA, Vec2::NEG_X),
+        (KeyCode::KeyS, Vec2::NEG_Y),
+        (KeyCode::KeyD, Vec2::X),
+    ] {
+        if input.pressed(key) {
+            direction += axis;
+        }
+    }
+    if direction != Vec2::ZERO {
+        spectating.focus = None;
+        // Fly relative to the screen, which rolls with gravity.
+        let direction = (transform.rotation * direction.normalize().extend(0.0)).xy();
+        transform.translation +=
+            (direction * SPECTATE_SPEED * ortho.scale * time.delta_secs()).extend(0.0);
+        return;
+    }
+
+    let Some(focus) = spectating.focus else {
+        return;
+    };
+    let Ok((_, target)) = targets.get(focus) else {
+        spectating.focus = None;
+        return;
+    };
+    let t = 1.0 - (-FRAME_RATE * time.delta_secs()).exp();
+    let position = transform
+        .translation
+        .xy()
+        .lerp(target.translation().xy(), t);
+    transform.translation = position.extend(transform.translation.z);
+}
+
+/// Moves `center` so that a view of `size` stays inside `bounds`, centering
+/// on any axis where the bounds are smaller than the view.
+fn clamp_to_bounds(center: Vec2, size: Vec2, bounds: Rect) -> Vec2 {
+    if bounds.is_empty() {
+        return center;
+    }
+    let half = size * 0.5;
+    let min = bounds.min + half;
+    let max = bounds.max - half;
+    Vec2::new(
+        if min.x > max.x {
+            bounds.center().x
+        } else {
+            center.x.clamp(min.x, max.x)
+        },
+        if min.y > max.y {
+            bounds.center().y
+        } else {
+            center.y.clamp(min.y, max.y)
+        },
+    )
+}
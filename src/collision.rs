@@ -0,0 +1,150 @@
+//! Collision layers and the matrix of which layers interact.
+//!
+//! Avian only reports a contact when each body's filters contain the other's
+//! layer, so [`Layer::filters`] is the single place to change what hits what.
+//! Print the resulting matrix with the `layers` terminal command.
+
+use avian2d::prelude::{CollisionLayers, LayerMask, PhysicsLayer};
+use bevy::prelude::*;
+use std::fmt::Write;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PhysicsLayer, Component)]
+pub enum Layer {
+    #[default]
+    Default,
+    Player,
+    Bullet,
+    Wall,
+    KillBox,
+    Door,
+    Key,
+    Pickups,
+    Trigger,
+    Enemy,
+    EnemyBullet,
+    /// [`PhysicsVolume`](crate::level::PhysicsVolume)s and
+    /// [`WindZone`](crate::level::WindZone)s, which change the physics of the
+    /// players and bullets inside.
+    Volume,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 12] = [
+        Layer::Default,
+        Layer::Player,
+        Layer::Bullet,
+        Layer::Wall,
+        Layer::KillBox,
+        Layer::Door,
+        Layer::Key,
+        Layer::Pickups,
+        Layer::Trigger,
+        Layer::Enemy,
+        Layer::EnemyBullet,
+        Layer::Volume,
+    ];
+
+    /// Layers this layer accepts contacts from.
+    pub fn filters(self) -> &'static [Layer] {
+        match self {
+            Layer::Player => &[
+                Layer::Default,
+                Layer::Wall,
+                Layer::KillBox,
+                Layer::Door,
+                Layer::Trigger,
+                Layer::Enemy,
+                Layer::EnemyBullet,
+                Layer::Volume,
+            ],
+            Layer::Bullet => &[
+                Layer::Default,
+                Layer::Wall,
+                Layer::Door,
+                Layer::Key,
+                Layer::Enemy,
+                Layer::EnemyBullet,
+                Layer::Volume,
+            ],
+            Layer::Enemy => &[
+                Layer::Player,
+                Layer::Bullet,
+                Layer::Wall,
+                Layer::KillBox,
+                Layer::Enemy,
+            ],
+            Layer::EnemyBullet => &[
+                Layer::Default,
+                Layer::Player,
+                Layer::Bullet,
+                Layer::Wall,
+                Layer::Door,
+            ],
+            Layer::Default
+            | Layer::Wall
+            | Layer::KillBox
+            | Layer::Door
+            | Layer::Key
+            | Layer::Pickups
+            | Layer::Trigger
+            | Layer::Volume => &Layer::ALL,
+        }
+    }
+
+    pub fn mask(layers: &[Layer]) -> LayerMask {
+        LayerMask(layers.iter().fold(0, |bits, layer| bits | layer.to_bits()))
+    }
+
+    pub fn collision_layers(self) -> CollisionLayers {
+        CollisionLayers::new(self, Self::mask(self.filters()))
+    }
+
+    /// Whether bodies on `self` and `other` generate contacts.
+    pub fn interacts(self, other: Layer) -> bool {
+        self.filters().contains(&other) && other.filters().contains(&self)
+    }
+
+    /// [`Layer::collision_layers`] with the [`VERSUS`] interactions added.
+    pub fn versus_collision_layers(self) -> CollisionLayers {
+        let extra = VERSUS.iter().filter_map(|(a, b)| {
+            if *a == self {
+                Some(*b)
+            } else if *b == self {
+                Some(*a)
+            } else {
+                None
+            }
+        });
+        let filters = extra.fold(Self::mask(self.filters()), |mask, layer| {
+            LayerMask(mask.0 | layer.to_bits())
+        });
+        CollisionLayers::new(self, filters)
+    }
+}
+
+/// Pairs that also interact in versus mode, where players shoot each other.
+///
+/// See [`crate::versus`].
+pub const VERSUS: &[(Layer, Layer)] = &[(Layer::Player, Layer::Bullet)];
+
+/// The interaction matrix as a table, `x` where two layers interact.
+pub fn matrix() -> String {
+    let width = Layer::ALL
+        .iter()
+        .map(|layer| format!("{layer:?}").len())
+        .max()
+        .unwrap_or_default();
+
+    let mut table = format!("{:width$}", "");
+    for layer in Layer::ALL {
+        _ = write!(table, " {:3.3}", format!("{layer:?}"));
+    }
+    for row in Layer::ALL {
+        _ = write!(table, "\n{:width$}", format!("{row:?}"));
+        for column in Layer::ALL {
+            let cell = if row.interacts(column) { "x" } else { "." };
+            _ = write!(table, " {cell:^3}");
+        }
+    }
+    table
+}
@@ -0,0 +1,158 @@
+//! Local co-op, a second [`Player`] driven by its own gamepad.
+//!
+//! Pressing start on a gamepad joins a [`Partner`] and select leaves. The
+//! partner spawns next to the first player whenever a level loads, with a copy
+//! of their selected weapon, and takes the gamepad away from them.
+//!
+//! A [`Killed`](crate::player::Killed) player is [`Downed`] and respawns on
+//! their partner, the level only resets once nobody is left standing.
+//!
+//! A [`Coop::remote`] partner is driven by the other peer of a netplay session
+//! instead of a gamepad, see `crate::netplay`.
+//!
+//! Replays only record a single player.
+
+use crate::{
+    level::Serialize,
+    player::{Player, PlayerSet},
+    versus::Versus,
+    weapon::Weapon,
+};
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Coop>()
+        .add_systems(Update, join_and_leave)
+        .add_systems(
+            FixedUpdate,
+            (
+                spawn_partner,
+                respawn_downed.run_if(not(resource_exists::<Versus>)),
+            )
+                .chain()
+                .in_set(PlayerSet),
+        );
+}
+
+/// The gamepad driving the [`Partner`], if one has joined.
+#[derive(Default, Resource)]
+pub struct Coop {
+    pub gamepad: Option<Entity>,
+    /// Keeps a partner without a gamepad joined, gamepads can not join or
+    /// leave.
+    pub remote: bool,
+}
+
+/// The second player, bound exclusively to `gamepad` unless they are remote.
+#[derive(Component)]
+#[require(Player, Name::new("Partner"))]
+pub struct Partner {
+    pub gamepad: Option<Entity>,
+}
+
+/// Horizontal distance from the first player at which the [`Partner`] spawns.
+const PARTNER_OFFSET: f32 = 40.0;
+
+/// Seconds a [`Downed`] player waits before respawning on their partner.
+pub const RESPAWN_DELAY: f32 = 1.5;
+
+/// A killed player waiting to respawn, hidden and without collision.
+#[derive(Clone, Copy, Component)]
+#[require(RigidBodyDisabled, ColliderDisabled)]
+pub struct Downed(pub f32);
+
+fn join_and_leave(mut coop: ResMut<Coop>, gamepads: Query<(Entity, &Gamepad)>) {
+    if coop.remote {
+        return;
+    }
+    match coop.gamepad {
+        None => {
+            if let Some((entity, _)) = gamepads
+                .iter()
+                .find(|(_, gamepad)| gamepad.just_pressed(GamepadButton::Start))
+            {
+                info!("partner joined on {entity}");
+                coop.gamepad = Some(entity);
+            }
+        }
+        Some(entity) => {
+            if gamepads
+                .get(entity)
+                .ok()
+                .is_none_or(|(_, gamepad)| gamepad.just_pressed(GamepadButton::Select))
+            {
+                info!("partner left");
+                coop.gamepad = None;
+            }
+        }
+    }
+}
+
+fn spawn_partner(
+    mut commands: Commands,
+    coop: Res<Coop>,
+    first: Single<(Entity, &Transform, Option<&Children>), (With<Player>, Without<Partner>)>,
+    partners: Query<Entity, With<Partner>>,
+    weapons: Query<Entity, With<Weapon>>,
+) {
+    let (first, transform, children) = first.into_inner();
+    if coop.gamepad.is_none() && !coop.remote {
+        if !partners.is_empty() {
+            for partner in partners.iter() {
+                commands.entity(partner).despawn();
+            }
+            commands.entity(first).insert(GamepadDevice::Any);
+        }
+        return;
+    }
+    if !partners.is_empty() {
+        return;
+    }
+
+    let partner = commands
+        .spawn((
+            Partner {
+                gamepad: coop.gamepad,
+            },
+            coop.gamepad
+                .map_or(GamepadDevice::None, GamepadDevice::Single),
+            Transform::from_translation(transform.translation + Vec3::X * PARTNER_OFFSET),
+        ))
+        // Partners join each level rather than being saved into it.
+        .remove::<Serialize>()
+        .id();
+    commands.entity(first).insert(GamepadDevice::None);
+
+    for weapon in weapons.iter_many(children.into_iter().flatten()) {
+        commands
+            .entity(weapon)
+            .clone_and_spawn()
+            .remove::<Serialize>()
+            .insert(ChildOf(partner));
+    }
+}
+
+fn respawn_downed(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut downed: Query<(Entity, &mut Downed, &mut Transform, &mut LinearVelocity)>,
+    standing: Query<&GlobalTransform, (With<Player>, Without<Downed>)>,
+) {
+    for (entity, mut timer, mut transform, mut velocity) in downed.iter_mut() {
+        timer.0 -= time.delta_secs();
+        if timer.0 > 0.0 {
+            continue;
+        }
+        let Some(partner) = standing.iter().next() else {
+            continue;
+        };
+        transform.translation = partner.translation() + Vec3::Y * PARTNER_OFFSET;
+        velocity.0 = Vec2::ZERO;
+        commands
+            .entity(entity)
+            .remove::<(Downed, RigidBodyDisabled, ColliderDisabled)>()
+            .insert((Visibility::Inherited, ContextActivity::<Player>::ACTIVE));
+    }
+}
@@ -0,0 +1,217 @@
+//! Daily challenge, a gauntlet of levels picked from today's date.
+//!
+//! `--daily` seeds the run with the date and plays [`GAUNTLET_LENGTH`] levels
+//! drawn from `assets/scenes` with that seed, in deterministic mode. Doors lead
+//! to the next level of the gauntlet rather than their own destination.
+//!
+//! Each day allows one attempt, which ends on the first death or after the
+//! last level. The result is kept in the [`SaveData`] and the run's replay in
+//! `replays/daily/<date>.ron`, which plays back with `--replay`.
+
+use crate::{
+    coop::Downed,
+    level::{Level, LevelComplete, level_idents},
+    player::{Killed, Player},
+    replay::Recording,
+    save::SaveData,
+};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use bevy_rand::prelude::WyRand;
+use rand::{SeedableRng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, start_attempt.run_if(resource_exists::<Daily>))
+        .add_systems(Update, results.run_if(resource_exists::<Daily>))
+        .add_observer(next_level)
+        .add_observer(died);
+}
+
+/// Levels in a daily gauntlet.
+pub const GAUNTLET_LENGTH: usize = 3;
+
+/// The daily run in progress.
+#[derive(Resource)]
+pub struct Daily {
+    /// `YYYY-MM-DD`, in UTC.
+    pub date: String,
+    pub seed: u64,
+    pub levels: Vec<String>,
+    /// Index into `levels` of the level being played.
+    pub current: usize,
+    /// Sum of the completed levels' times.
+    pub time: f64,
+    over: bool,
+}
+
+impl Daily {
+    /// Today's challenge, `Err` if the levels can not be listed.
+    pub fn today() -> Result<Self, String> {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| err.to_string())?
+            .as_secs()
+            / 86_400;
+        let (year, month, day) = civil_from_days(days as i64);
+
+        let mut levels = level_idents()?;
+        if levels.is_empty() {
+            return Err("no levels in assets/scenes".to_string());
+        }
+        levels.shuffle(&mut WyRand::seed_from_u64(days));
+        levels.truncate(GAUNTLET_LENGTH);
+
+        Ok(Self {
+            date: format!("{year:04}-{month:02}-{day:02}"),
+            seed: days,
+            levels,
+            current: 0,
+            time: 0.0,
+            over: false,
+        })
+    }
+}
+
+/// The outcome of a day's attempt.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyResult {
+    pub levels_cleared: usize,
+    /// Sum of the cleared levels' times.
+    pub time: f64,
+    pub finished: bool,
+}
+
+/// Converts days since the Unix epoch into a `(year, month, day)` date.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn start_attempt(mut save: ResMut<SaveData>, daily: Res<Daily>, mut exit: MessageWriter<AppExit>) {
+    if save.daily.contains_key(&daily.date) {
+        error!(
+            "the daily challenge for {} was already attempted",
+            daily.date
+        );
+        exit.write(AppExit::error());
+        return;
+    }
+    info!(
+        "daily challenge {}: {}",
+        daily.date,
+        daily.levels.join(", ")
+    );
+    // Recorded up front, so that quitting still uses up the attempt.
+    save.daily
+        .insert(daily.date.clone(), DailyResult::default());
+}
+
+fn next_level(
+    complete: On<LevelComplete>,
+    mut commands: Commands,
+    daily: Option<ResMut<Daily>>,
+    mut level: ResMut<Level>,
+) {
+    let Some(mut daily) = daily.filter(|daily| !daily.over) else {
+        return;
+    };
+    daily.time += complete.time;
+    daily.current += 1;
+    match daily.levels.get(daily.current) {
+        Some(next) => level.0 = next.clone(),
+        None => commands.run_system_cached(end_attempt),
+    }
+}
+
+fn died(
+    killed: On<Killed>,
+    mut commands: Commands,
+    daily: Option<Res<Daily>>,
+    standing: Query<Entity, (With<Player>, Without<Downed>)>,
+) {
+    if daily.is_some_and(|daily| !daily.over)
+        && standing.contains(killed.entity)
+        && standing.iter().all(|entity| entity == killed.entity)
+    {
+        commands.run_system_cached(end_attempt);
+    }
+}
+
+fn end_attempt(
+    mut daily: ResMut<Daily>,
+    mut save: ResMut<SaveData>,
+    recording: Option<Res<Recording>>,
+) {
+    daily.over = true;
+    let result = DailyResult {
+        levels_cleared: daily.current,
+        time: daily.time,
+        finished: daily.current >= daily.levels.len(),
+    };
+    info!("daily challenge {} over: {result:?}", daily.date);
+    save.daily.insert(daily.date.clone(), result);
+
+    let Some(serialized) = recording.and_then(|recording| {
+        ron::to_string(recording.replay())
+            .inspect_err(|err| error!("failed to serialize daily replay: {err}"))
+            .ok()
+    }) else {
+        return;
+    };
+    let path = format!("replays/daily/{}.ron", daily.date);
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(err) =
+                fs::create_dir_all("replays/daily").and_then(|_| fs::write(&path, serialized))
+            {
+                error!("failed to write {path}: {err}");
+            }
+        })
+        .detach();
+}
+
+/// Shown once the attempt is over.
+#[derive(Component)]
+struct DailyResults;
+
+fn results(mut commands: Commands, daily: Res<Daily>, shown: Query<(), With<DailyResults>>) {
+    if !daily.over || !shown.is_empty() {
+        return;
+    }
+    let text = format!(
+        "Daily challenge {}\n\nLevels cleared: {}/{}\nTime: {:.2}s",
+        daily.date,
+        daily.current,
+        daily.levels.len(),
+        daily.time
+    );
+    commands.spawn((
+        DailyResults,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        children![(
+            Text::new(text),
+            TextLayout::new_with_justify(Justify::Center)
+        )],
+    ));
+}
@@ -0,0 +1,119 @@
+use crate::{
+    enemy::Enemy,
+    player::Player,
+    registry::register_serialized,
+    settings::Settings,
+    weapon::{BulletImpact, ImpactSet},
+};
+use avian2d::prelude::{LinearVelocity, RigidBody};
+use bevy::prelude::*;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, bullet_hit.in_set(ImpactSet::Destructibles));
+
+    register_serialized::<Health>(app);
+}
+
+/// Velocity applied to a dynamic body per point of [`Damage`] it takes.
+const KNOCKBACK: f32 = 150.0;
+
+/// Hit points, the entity dies when they run out.
+///
+/// Bullets subtract their [`Damage`] on contact, see [`Died`].
+#[derive(Clone, Copy, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct Health(pub f32);
+
+impl Default for Health {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Health removed by a [`Bullet`](crate::weapon::Bullet) on contact.
+#[derive(Clone, Copy, Component)]
+pub struct Damage(pub f32);
+
+impl Default for Damage {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Triggered on an entity when it is hurt.
+#[derive(EntityEvent)]
+pub struct Damaged {
+    pub entity: Entity,
+    pub amount: f32,
+}
+
+/// Triggered on an entity when its [`Health`] runs out, before it is despawned.
+#[derive(EntityEvent)]
+pub struct Died {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+fn bullet_hit(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    mut targets: Query<
+        (
+            &mut Health,
+            &GlobalTransform,
+            Option<&RigidBody>,
+            Option<&mut LinearVelocity>,
+        ),
+        // Players are hurt through `Hurt`, which respawns rather than despawns.
+        Without<Player>,
+    >,
+    damages: Query<&Damage>,
+    enemies: Query<(), With<Enemy>>,
+    settings: Res<Settings>,
+) {
+    for impact in impacts.read() {
+        let Ok((mut health, transform, body, velocity)) = targets.get_mut(impact.target) else {
+            continue;
+        };
+        if health.0 <= 0.0 {
+            continue;
+        }
+
+        let mut amount = damages.get(impact.bullet).copied().unwrap_or_default().0;
+        if enemies.contains(impact.target) {
+            amount /= settings.difficulty.table().enemy_health;
+        }
+        if body.is_some_and(|body| *body == RigidBody::Dynamic)
+            && let Some(mut velocity) = velocity
+        {
+            velocity.0 += impact.velocity.normalize_or_zero() * KNOCKBACK * amount;
+        }
+        apply_damage(
+            &mut commands,
+            impact.target,
+            &mut health,
+            amount,
+            transform.translation().xy(),
+        );
+    }
+}
+
+/// Removes `amount` from the entity's `health`, triggering [`Damaged`], and
+/// [`Died`] before despawning it once the health runs out.
+pub fn apply_damage(
+    commands: &mut Commands,
+    entity: Entity,
+    health: &mut Health,
+    amount: f32,
+    position: Vec2,
+) {
+    if health.0 <= 0.0 {
+        return;
+    }
+    health.0 -= amount;
+    commands.trigger(Damaged { entity, amount });
+    if health.0 <= 0.0 {
+        commands.trigger(Died { entity, position });
+        commands.entity(entity).try_despawn();
+    }
+}
@@ -0,0 +1,40 @@
+use crate::physics::PhysicsConfig;
+use bevy::{
+    ecs::schedule::{LogLevel, ScheduleBuildSettings, ScheduleLabel},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use std::time::Duration;
+
+/// Makes a run reproducible from its [`Seed`](crate::Seed) and input alone.
+///
+/// Every frame advances exactly one fixed timestep, regardless of wall time,
+/// and ambiguously ordered systems in the fixed schedules are reported.
+/// Gameplay that affects physics must run in the fixed schedules.
+///
+/// Rollback netplay relies on the same determinism, see `crate::netplay` with
+/// the `netplay` feature.
+pub fn plugin(app: &mut App) {
+    for schedule in [
+        FixedPreUpdate.intern(),
+        FixedUpdate.intern(),
+        FixedPostUpdate.intern(),
+    ] {
+        app.edit_schedule(schedule, |schedule| {
+            schedule.set_build_settings(ScheduleBuildSettings {
+                ambiguity_detection: LogLevel::Warn,
+                ..Default::default()
+            });
+        });
+    }
+
+    app.add_systems(PreStartup, frame_duration)
+        .add_systems(Update, frame_duration);
+}
+
+fn frame_duration(config: Res<PhysicsConfig>, mut strategy: ResMut<TimeUpdateStrategy>) {
+    if config.is_changed() {
+        *strategy =
+            TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / config.hz.max(1.0)));
+    }
+}
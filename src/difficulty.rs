@@ -0,0 +1,85 @@
+//! Difficulty levels and the multipliers they apply.
+//!
+//! The [`Difficulty`] is part of the [`Settings`](crate::settings::Settings),
+//! so it persists between sessions. Gameplay reads its [`DifficultyTable`]
+//! rather than matching on the level directly.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// Multipliers applied by a [`Difficulty`].
+#[derive(Debug, Clone, Copy)]
+pub struct DifficultyTable {
+    /// Scales enemy [`Health`](crate::damage::Health) by dividing the damage
+    /// they take.
+    pub enemy_health: f32,
+    /// Scales [`Turret::fire_rate`](crate::enemy::Turret::fire_rate).
+    pub enemy_fire_rate: f32,
+    /// Fraction of a weapon's ammo refilled by an
+    /// [`AmmoPickup`](crate::weapon::AmmoPickup).
+    pub ammo_refill: f32,
+    /// Kill boxes send the player back to their
+    /// [`LevelStart`](crate::level::LevelStart) instead of killing them.
+    pub killbox_respawn: bool,
+    /// Scales the [`ParTimes`](crate::stats::ParTimes) of every level.
+    pub par_time: f32,
+}
+
+impl Difficulty {
+    /// The next harder difficulty, wrapping around to the easiest.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Easy,
+        }
+    }
+
+    pub fn table(self) -> DifficultyTable {
+        match self {
+            Self::Easy => DifficultyTable {
+                enemy_health: 0.5,
+                enemy_fire_rate: 0.6,
+                ammo_refill: 1.0,
+                killbox_respawn: true,
+                par_time: 1.5,
+            },
+            Self::Normal => DifficultyTable {
+                enemy_health: 1.0,
+                enemy_fire_rate: 1.0,
+                ammo_refill: 1.0,
+                killbox_respawn: false,
+                par_time: 1.0,
+            },
+            Self::Hard => DifficultyTable {
+                enemy_health: 1.5,
+                enemy_fire_rate: 1.5,
+                ammo_refill: 0.5,
+                killbox_respawn: false,
+                par_time: 0.75,
+            },
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "normal" => Ok(Self::Normal),
+            "hard" => Ok(Self::Hard),
+            _ => Err(format!("unknown difficulty `{s}`")),
+        }
+    }
+}
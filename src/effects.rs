@@ -0,0 +1,566 @@
+use crate::{
+    coop::Partner,
+    damage::{Damaged, Health},
+    level::{DespawnOnLevelUnload, Grappling, LaserBeam, LevelLoading, WindZone},
+    player::{PLAYER_HEALTH, Player},
+    settings::Settings,
+    transition::{Transition, TransitionPhase, TransitionStyle},
+    weapon::{Beam, GravityWell},
+};
+use avian2d::prelude::ColliderAabb;
+use bevy::{
+    post_process::{bloom::Bloom, effect_stack::ChromaticAberration},
+    prelude::*,
+    ui::{BackgroundGradient, ColorStop, RadialGradient, RadialGradientShape, UiPosition},
+};
+use rand::Rng;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<DamagePulse>()
+        .add_systems(
+            Startup,
+            (
+                spawn_vignette,
+                spawn_loading_fade,
+                spawn_loading_indicator,
+                spawn_transition_cover,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_bloom,
+                draw_ropes,
+                draw_laser_beams,
+                draw_gravity_wells,
+                (spawn_beam_meshes, beam_meshes).chain(),
+                loading_fade,
+                loading_indicator,
+                transition_cover,
+                particles,
+                wind_particles,
+                hurt_flash,
+                (decay_damage_pulse, (vignette, chromatic_aberration)).chain(),
+            ),
+        )
+        .add_observer(damage_pulse)
+        .add_observer(start_hurt_flash)
+        .add_observer(particle_burst);
+}
+
+/// Color for sprites that should glow under [`Bloom`].
+pub const GLOW: Color = Color::linear_rgb(4.0, 4.0, 4.0);
+
+fn toggle_bloom(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    camera: Single<Entity, With<Camera2d>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if settings.bloom {
+        commands.entity(*camera).insert(Bloom::NATURAL);
+    } else {
+        commands.entity(*camera).remove::<Bloom>();
+    }
+}
+
+/// Strength of the hurt effects in `[0, 1]`, decays over time.
+#[derive(Default, Resource)]
+struct DamagePulse(f32);
+
+fn damage_pulse(
+    damaged: On<Damaged>,
+    players: Query<(), With<Player>>,
+    mut pulse: ResMut<DamagePulse>,
+) {
+    if players.contains(damaged.entity) {
+        pulse.0 = 1.0;
+    }
+}
+
+/// Seconds a damaged sprite stays tinted.
+const HURT_FLASH: f32 = 0.1;
+
+/// Tints a damaged sprite until `remaining` runs out, then restores `color`.
+#[derive(Component)]
+struct HurtFlash {
+    color: Color,
+    remaining: f32,
+}
+
+fn start_hurt_flash(
+    damaged: On<Damaged>,
+    mut commands: Commands,
+    mut sprites: Query<(&mut Sprite, Option<&HurtFlash>), Without<Player>>,
+) {
+    let Ok((mut sprite, flash)) = sprites.get_mut(damaged.entity) else {
+        return;
+    };
+    let color = flash.map_or(sprite.color, |flash| flash.color);
+    sprite.color = GLOW;
+    commands.entity(damaged.entity).try_insert(HurtFlash {
+        color,
+        remaining: HURT_FLASH,
+    });
+}
+
+fn hurt_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut sprites: Query<(Entity, &mut HurtFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in sprites.iter_mut() {
+        flash.remaining -= time.delta_secs();
+        if flash.remaining <= 0.0 {
+            sprite.color = flash.color;
+            commands.entity(entity).remove::<HurtFlash>();
+        }
+    }
+}
+
+fn decay_damage_pulse(time: Res<Time>, mut pulse: ResMut<DamagePulse>) {
+    if pulse.0 > 0.0 {
+        pulse.0 = (pulse.0 - time.delta_secs() * 2.0).max(0.0);
+    }
+}
+
+/// Fraction of [`PLAYER_HEALTH`] below which the hurt effects stay on.
+const LOW_HEALTH: f32 = 0.5;
+
+/// `health` is that of the first player, which keeps the effects on while it is
+/// low.
+fn intensity(pulse: &DamagePulse, settings: &Settings, health: Option<f32>) -> f32 {
+    if !settings.damage_effects {
+        return 0.0;
+    }
+    let fraction = health.map_or(1.0, |health| health / PLAYER_HEALTH);
+    if fraction < LOW_HEALTH {
+        pulse.0.max(1.0 - fraction)
+    } else {
+        pulse.0
+    }
+}
+
+#[derive(Component)]
+struct Vignette;
+
+fn spawn_vignette(mut commands: Commands) {
+    commands.spawn((
+        Vignette,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        GlobalZIndex(-1),
+        #[cfg(feature = "debug")]
+        Pickable::IGNORE,
+        vignette_gradient(0.0),
+    ));
+}
+
+/// Seconds the [`LoadingFade`] takes to clear after a level loads.
+const LOADING_FADE: f32 = 0.3;
+
+/// Covers the screen while the level is [`LevelLoading`].
+#[derive(Component)]
+struct LoadingFade;
+
+fn spawn_loading_fade(mut commands: Commands) {
+    commands.spawn((
+        LoadingFade,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        GlobalZIndex(10),
+        #[cfg(feature = "debug")]
+        Pickable::IGNORE,
+        BackgroundColor(Color::BLACK),
+    ));
+}
+
+fn loading_fade(
+    time: Res<Time>,
+    loading: Res<LevelLoading>,
+    transition: Option<Res<Transition>>,
+    mut fade: Single<&mut BackgroundColor, With<LoadingFade>>,
+) {
+    // The transition covers the loading level itself.
+    let alpha = if transition.is_some() {
+        0.0
+    } else if loading.0 {
+        1.0
+    } else {
+        (fade.0.alpha() - time.delta_secs() / LOADING_FADE).max(0.0)
+    };
+    if fade.0.alpha() != alpha {
+        fade.0.set_alpha(alpha);
+    }
+}
+
+/// Real seconds a level takes to load before the [`LoadingIndicator`] shows,
+/// so that quick loads do not flash it.
+const LOADING_INDICATOR_DELAY: f32 = 0.15;
+
+/// Shown in the corner while the level is [`LevelLoading`], over any cover.
+#[derive(Component)]
+struct LoadingIndicator;
+
+fn spawn_loading_indicator(mut commands: Commands) {
+    commands.spawn((
+        LoadingIndicator,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(24.0),
+            bottom: Val::Px(24.0),
+            ..default()
+        },
+        GlobalZIndex(12),
+        #[cfg(feature = "debug")]
+        Pickable::IGNORE,
+        Visibility::Hidden,
+        Text::new("Loading"),
+    ));
+}
+
+fn loading_indicator(
+    time: Res<Time<Real>>,
+    loading: Res<LevelLoading>,
+    mut elapsed: Local<f32>,
+    indicator: Single<(&mut Visibility, &mut Text), With<LoadingIndicator>>,
+) {
+    let (mut visibility, mut text) = indicator.into_inner();
+    if !loading.0 {
+        *elapsed = 0.0;
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    *elapsed += time.delta_secs();
+    if *elapsed < LOADING_INDICATOR_DELAY {
+        return;
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+    let dots = (*elapsed * 3.0) as usize % 4;
+    text.0 = format!("Loading{}", ".".repeat(dots));
+}
+
+/// Covers the screen during a [`Transition`], drawn in the
+/// [`Settings::transition`] style.
+#[derive(Component)]
+struct TransitionCover;
+
+fn spawn_transition_cover(mut commands: Commands) {
+    commands.spawn((
+        TransitionCover,
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        GlobalZIndex(11),
+        #[cfg(feature = "debug")]
+        Pickable::IGNORE,
+        Visibility::Hidden,
+        BackgroundColor(Color::NONE),
+        BackgroundGradient::default(),
+    ));
+}
+
+fn transition_cover(
+    transition: Option<Res<Transition>>,
+    settings: Res<Settings>,
+    cover: Single<
+        (
+            &mut Node,
+            &mut Visibility,
+            &mut BackgroundColor,
+            &mut BackgroundGradient,
+        ),
+        With<TransitionCover>,
+    >,
+) {
+    let (mut node, mut visibility, mut color, mut gradient) = cover.into_inner();
+    let Some(transition) = transition else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+    let coverage = transition.coverage();
+    node.left = percent(0);
+    node.width = percent(100);
+    match settings.transition {
+        TransitionStyle::Fade => {
+            color.0 = Color::BLACK.with_alpha(coverage);
+            *gradient = BackgroundGradient::default();
+        }
+        TransitionStyle::Wipe => {
+            // Enters from the left and leaves to the right.
+            if transition.phase == TransitionPhase::In {
+                node.left = percent(100.0 * (1.0 - coverage));
+            }
+            node.width = percent(100.0 * coverage);
+            color.0 = Color::BLACK;
+            *gradient = BackgroundGradient::default();
+        }
+        TransitionStyle::Iris => {
+            let radius = 100.0 * (1.0 - coverage);
+            color.0 = Color::NONE;
+            *gradient = BackgroundGradient::from(RadialGradient::new(
+                UiPosition::CENTER,
+                RadialGradientShape::FarthestCorner,
+                vec![
+                    ColorStop::percent(Color::NONE, radius),
+                    ColorStop::percent(Color::BLACK, radius),
+                ],
+            ));
+        }
+    }
+}
+
+fn vignette_gradient(intensity: f32) -> BackgroundGradient {
+    BackgroundGradient::from(RadialGradient::new(
+        UiPosition::CENTER,
+        RadialGradientShape::FarthestCorner,
+        vec![
+            ColorStop::percent(Color::NONE, 40.0),
+            ColorStop::percent(Color::srgba(0.8, 0.0, 0.0, 0.6 * intensity), 100.0),
+        ],
+    ))
+}
+
+fn vignette(
+    pulse: Res<DamagePulse>,
+    settings: Res<Settings>,
+    health: Option<Single<Ref<Health>, (With<Player>, Without<Partner>)>>,
+    mut vignette: Single<&mut BackgroundGradient, With<Vignette>>,
+) {
+    let health_changed = health.as_ref().is_some_and(|health| health.is_changed());
+    if pulse.is_changed() || settings.is_changed() || health_changed {
+        **vignette = vignette_gradient(intensity(&pulse, &settings, health.map(|health| health.0)));
+    }
+}
+
+fn chromatic_aberration(
+    mut commands: Commands,
+    pulse: Res<DamagePulse>,
+    settings: Res<Settings>,
+    health: Option<Single<Ref<Health>, (With<Player>, Without<Partner>)>>,
+    camera: Single<Entity, With<Camera2d>>,
+) {
+    let health_changed = health.as_ref().is_some_and(|health| health.is_changed());
+    if !pulse.is_changed() && !settings.is_changed() && !health_changed {
+        return;
+    }
+    let intensity = intensity(&pulse, &settings, health.map(|health| health.0));
+    if intensity > 0.0 {
+        commands.entity(*camera).insert(ChromaticAberration {
+            intensity: 0.03 * intensity,
+            ..default()
+        });
+    } else {
+        commands.entity(*camera).remove::<ChromaticAberration>();
+    }
+}
+
+/// Spawns `count` particles flying out from `position`.
+#[derive(Event)]
+pub struct ParticleBurst {
+    pub position: Vec2,
+    pub color: Color,
+    pub count: usize,
+}
+
+/// Seconds a [`ParticleBurst`] particle lives.
+const PARTICLE_LIFETIME: f32 = 0.5;
+
+#[derive(Component)]
+#[require(DespawnOnLevelUnload)]
+struct Particle {
+    velocity: Vec2,
+    age: f32,
+}
+
+fn particle_burst(burst: On<ParticleBurst>, mut commands: Commands) {
+    for i in 0..burst.count {
+        // Evenly spaced rather than random, so that effects do not consume
+        // gameplay randomness.
+        let angle = i as f32 / burst.count as f32 * std::f32::consts::TAU;
+        let speed = 150.0 + 100.0 * (i % 3) as f32;
+        commands.spawn((
+            Particle {
+                velocity: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+            },
+            Sprite::from_color(burst.color, Vec2::splat(6.0)),
+            Transform::from_translation(burst.position.extend(1.0)),
+        ));
+    }
+}
+
+fn particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        particle.age += dt;
+        if particle.age >= PARTICLE_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (particle.velocity * dt).extend(0.0);
+        sprite
+            .color
+            .set_alpha(1.0 - particle.age / PARTICLE_LIFETIME);
+    }
+}
+
+/// [`WindZone`] particles spawned per second per million square pixels.
+const WIND_PARTICLE_RATE: f32 = 400.0;
+
+/// Speed of [`WindZone`] particles per unit of force.
+const WIND_PARTICLE_SPEED: f32 = 0.3;
+
+/// Streaks drifting along the force of each [`WindZone`], as a hint of the wind.
+fn wind_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    zones: Query<(&WindZone, &ColliderAabb)>,
+) {
+    // Presentation only, so it stays off the seeded gameplay RNG.
+    let mut rng = rand::rng();
+    for (zone, aabb) in zones.iter() {
+        let size = aabb.size();
+        let expected = size.x * size.y / 1_000_000.0 * WIND_PARTICLE_RATE * time.delta_secs();
+        let count = expected as usize + rng.random_bool(expected.fract() as f64) as usize;
+        for _ in 0..count {
+            let position = aabb.min + Vec2::new(rng.random(), rng.random()) * size;
+            commands.spawn((
+                Particle {
+                    velocity: zone.force * WIND_PARTICLE_SPEED,
+                    age: 0.0,
+                },
+                Sprite::from_color(Color::WHITE, Vec2::new(10.0, 2.0)),
+                Transform::from_translation(position.extend(1.0))
+                    .with_rotation(Quat::from_rotation_z(zone.force.to_angle())),
+            ));
+        }
+    }
+}
+
+/// Draws the rope of each [`Grappling`] player.
+fn draw_ropes(
+    mut gizmos: Gizmos,
+    players: Query<(&GlobalTransform, &Grappling)>,
+    points: Query<&GlobalTransform>,
+) {
+    for (transform, grappling) in players.iter() {
+        if let Ok(point) = points.get(grappling.point) {
+            gizmos.line_2d(
+                transform.translation().xy(),
+                point.translation().xy(),
+                Color::WHITE,
+            );
+        }
+    }
+}
+
+/// Draws active [`LaserBeam`]s, and inactive ones faintly as a warning.
+fn draw_laser_beams(mut gizmos: Gizmos, beams: Query<(&GlobalTransform, &LaserBeam)>) {
+    for (transform, beam) in beams.iter() {
+        let start = transform.translation().xy();
+        let end = start + (transform.rotation() * Vec3::X).xy() * beam.length;
+        let color = if beam.active {
+            Color::linear_rgb(4.0, 0.2, 0.2)
+        } else {
+            Color::srgba(1.0, 0.0, 0.0, 0.15)
+        };
+        gizmos.line_2d(start, end, color);
+    }
+}
+
+/// Quad stretched along the [`Beam`] of a [`Laser`](crate::weapon::Laser).
+#[derive(Component)]
+#[require(DespawnOnLevelUnload)]
+struct BeamMesh(Entity);
+
+/// Narrowest width of a [`BeamMesh`], which flickers up to twice as wide.
+const BEAM_WIDTH: f32 = 6.0;
+
+fn spawn_beam_meshes(
+    mut commands: Commands,
+    beams: Query<Entity, Added<Beam>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for laser in beams.iter() {
+        commands.spawn((
+            BeamMesh(laser),
+            Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
+            MeshMaterial2d(materials.add(Color::linear_rgb(1.0, 3.0, 4.0))),
+            Transform::default(),
+            Visibility::Hidden,
+        ));
+    }
+}
+
+fn beam_meshes(
+    mut commands: Commands,
+    mut meshes: Query<(Entity, &BeamMesh, &mut Transform, &mut Visibility)>,
+    beams: Query<(&GlobalTransform, &Beam)>,
+) {
+    let mut rng = rand::rng();
+    for (entity, mesh, mut transform, mut visibility) in meshes.iter_mut() {
+        let Ok((laser, beam)) = beams.get(mesh.0) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        if !beam.active {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+        let start = laser.translation().xy();
+        let center = start + beam.direction * beam.length * 0.5;
+        *transform = Transform::from_translation(center.extend(1.0))
+            .with_rotation(Quat::from_rotation_z(beam.direction.to_angle()))
+            .with_scale(Vec3::new(
+                beam.length,
+                rng.random_range(BEAM_WIDTH..BEAM_WIDTH * 2.0),
+                1.0,
+            ));
+    }
+}
+
+/// Draws each [`GravityWell`] as rings shrinking toward its center.
+fn draw_gravity_wells(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    wells: Query<(&GlobalTransform, &GravityWell)>,
+) {
+    for (transform, well) in wells.iter() {
+        let center = transform.translation().xy();
+        let alpha = well.remaining.min(1.0);
+        gizmos.circle_2d(
+            center,
+            well.radius,
+            Color::srgba(0.6, 0.2, 1.0, 0.3 * alpha),
+        );
+        for i in 0..3 {
+            let t = 1.0 - (time.elapsed_secs() * 0.8 + i as f32 / 3.0).fract();
+            gizmos.circle_2d(
+                center,
+                well.radius * t,
+                Color::srgba(0.6, 0.2, 1.0, alpha * (1.0 - t)),
+            );
+        }
+    }
+}
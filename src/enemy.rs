@@ -0,0 +1,617 @@
+use crate::{
+    audio::{PlaySfx, Sfx},
+    collision::Layer,
+    coop::Downed,
+    damage::{Died, Health},
+    effects::ParticleBurst,
+    level::{DebugPickingColor, NeedsSerializedCollider, Serialize},
+    navigation::{CELL_SIZE, NavGrid},
+    player::{AimVector, Deflecting, Hurt, Player, nearest_player},
+    registry::{register_level_entity, register_serialized},
+    settings::Settings,
+    weapon::{
+        Bullet, BulletImpact, BulletPool, DropsAmmo, Explosion, FiredBy, ImpactSet, Projectile,
+        WeaponDef, despawn_bullet,
+    },
+};
+use avian2d::prelude::*;
+use bevy::{
+    color::palettes::css::{CRIMSON, DARK_ORANGE, MEDIUM_PURPLE},
+    prelude::*,
+};
+use bevy_rand::{global::GlobalRng, prelude::WyRand};
+use std::f32::consts::{PI, TAU};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        ((patrol, chase).chain(), turret, flyer).in_set(EnemySet),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            deflect_enemy_bullet,
+            enemy_bullet_damage,
+            shoot_down_enemy_bullet,
+        )
+            .chain()
+            .in_set(ImpactSet::Enemies),
+    )
+    .add_observer(contact_damage)
+    .add_observer(flyer_contact)
+    .add_observer(enemy_died);
+
+    register_serialized::<Enemy>(app);
+    register_serialized::<Patrol>(app);
+    register_serialized::<PatrolPath>(app);
+    register_serialized::<Chase>(app);
+    register_level_entity::<Grunt>(app);
+    register_level_entity::<Turret>(app);
+    register_level_entity::<Flyer>(app);
+}
+
+/// Enemy AI, after [`WeaponSet`](crate::weapon::WeaponSet) and before
+/// [`LevelSet`](crate::level::LevelSet).
+///
+/// See [`crate::game_plugin`] for the full ordering.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct EnemySet;
+
+/// Distance past its collider that an enemy probes for walls and ledges.
+const EDGE_PROBE: f32 = 4.0;
+
+/// Hurts the player on contact and dies when its [`Health`] runs out.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Health,
+    DropsAmmo,
+    RigidBody::Dynamic,
+    LockedAxes::ROTATION_LOCKED,
+    TransformInterpolation,
+    Friction = Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
+    CollisionEventsEnabled,
+    CollisionLayers = Layer::Enemy.collision_layers(),
+    DebugPickingColor::new(CRIMSON),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct Enemy;
+
+/// Walks until it reaches a wall or a ledge, then turns around.
+#[derive(Clone, Copy, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct Patrol {
+    pub speed: f32,
+    /// `1.0` walks right, `-1.0` walks left.
+    pub direction: f32,
+}
+
+impl Default for Patrol {
+    fn default() -> Self {
+        Self {
+            speed: 100.0,
+            direction: 1.0,
+        }
+    }
+}
+
+/// World space waypoints a [`Patrol`] walks between in order, instead of
+/// turning at walls and ledges.
+#[derive(Default, Clone, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct PatrolPath {
+    pub points: Vec<Vec2>,
+    /// Index of the waypoint being walked to.
+    pub next: usize,
+}
+
+/// Horizontal distance at which a [`PatrolPath`] waypoint counts as reached.
+const WAYPOINT_RADIUS: f32 = 8.0;
+
+/// A basic patrolling enemy.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Enemy,
+    Name::new("Grunt"),
+    Health(3.0),
+    Patrol,
+    Chase,
+    NeedsSerializedCollider(Vec2::new(30.0, 40.0))
+)]
+#[reflect(Default, Component)]
+pub struct Grunt;
+
+fn patrol(
+    spatial: SpatialQuery,
+    gravity: Res<Gravity>,
+    mut enemies: Query<(
+        &mut Patrol,
+        Option<&mut PatrolPath>,
+        &mut LinearVelocity,
+        &ColliderAabb,
+        &Collider,
+    )>,
+) {
+    let down = if gravity.0.y > 0.0 {
+        Dir2::Y
+    } else {
+        Dir2::NEG_Y
+    };
+    let filter = SpatialQueryFilter::from_mask(Layer::Wall);
+
+    for (mut patrol, path, mut velocity, aabb, collider) in enemies.iter_mut() {
+        let center = (aabb.min + aabb.max) * 0.5;
+        let half = (aabb.max - aabb.min) * 0.5;
+
+        if let Some(mut path) = path
+            && !path.points.is_empty()
+        {
+            path.next %= path.points.len();
+            let offset = path.points[path.next].x - center.x;
+            if offset.abs() < WAYPOINT_RADIUS {
+                path.next = (path.next + 1) % path.points.len();
+                if path.points.len() == 1 {
+                    velocity.x = 0.0;
+                    continue;
+                }
+            } else {
+                patrol.direction = offset.signum();
+            }
+            velocity.x = patrol.direction * patrol.speed;
+            continue;
+        }
+
+        let forward = if patrol.direction < 0.0 {
+            Dir2::NEG_X
+        } else {
+            Dir2::X
+        };
+
+        // The whole body is cast so that walls lower than its center block it,
+        // shrunk vertically to clear the ground it stands on.
+        let mut body = collider.clone();
+        body.set_scale(collider.scale() * Vec2::new(1.0, 0.9), 10);
+        let blocked = spatial
+            .cast_shape(
+                &body,
+                center,
+                0.0,
+                forward,
+                &ShapeCastConfig::from_max_distance(EDGE_PROBE),
+                &filter,
+            )
+            .is_some();
+        let grounded = spatial
+            .cast_ray(center, down, half.y + EDGE_PROBE, true, &filter)
+            .is_some();
+        let front = center + forward * (half.x + EDGE_PROBE);
+        let ledge = spatial
+            .cast_ray(front, down, half.y + EDGE_PROBE, true, &filter)
+            .is_none();
+
+        if blocked || (grounded && ledge) {
+            patrol.direction = -patrol.direction;
+        }
+        velocity.x = patrol.direction.signum() * patrol.speed;
+    }
+}
+
+/// Follows a [`NavGrid`] path to the player once they are within
+/// `aggro_radius`, jumping between platforms. Falls back to [`Patrol`]
+/// otherwise.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(Patrol, ChaseState)]
+#[reflect(Default, Component)]
+pub struct Chase {
+    pub aggro_radius: f32,
+    pub jump_speed: f32,
+}
+
+impl Default for Chase {
+    fn default() -> Self {
+        Self {
+            aggro_radius: 350.0,
+            jump_speed: 600.0,
+        }
+    }
+}
+
+/// Remaining waypoints in reverse, so the next one is popped from the end.
+#[derive(Default, Clone, Component)]
+pub(crate) struct ChaseState {
+    path: Vec<Vec2>,
+    replan: f32,
+}
+
+/// Seconds between [`Chase`] path queries.
+const CHASE_REPLAN: f32 = 0.5;
+
+fn chase(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    spatial: SpatialQuery,
+    gravity: Res<Gravity>,
+    players: Query<(Entity, &GlobalTransform), (With<Player>, Without<Downed>)>,
+    mut chasers: Query<(
+        &Chase,
+        &mut ChaseState,
+        &mut Patrol,
+        &mut LinearVelocity,
+        &ColliderAabb,
+    )>,
+) {
+    let dt = time.delta_secs();
+    let filter = SpatialQueryFilter::from_mask(Layer::Wall);
+
+    for (chase, mut state, mut patrol, mut velocity, aabb) in chasers.iter_mut() {
+        let center = (aabb.min + aabb.max) * 0.5;
+        let target = nearest_player(&players, center).map(|(_, target)| target);
+        // The nav grid assumes gravity pulls down.
+        let Some(target) = target
+            .filter(|target| gravity.0.y <= 0.0 && center.distance(*target) <= chase.aggro_radius)
+        else {
+            state.path.clear();
+            state.replan = 0.0;
+            continue;
+        };
+
+        state.replan -= dt;
+        if state.replan <= 0.0 {
+            state.replan = CHASE_REPLAN;
+            state.path = grid.path(center, target).unwrap_or_default();
+            state.path.reverse();
+        }
+
+        // Center of the cell the enemy is standing in.
+        let feet = Vec2::new(center.x, aabb.min.y + CELL_SIZE * 0.5);
+        while state.path.last().is_some_and(|next| {
+            (*next - feet)
+                .abs()
+                .cmplt(Vec2::splat(CELL_SIZE * 0.5))
+                .all()
+        }) {
+            state.path.pop();
+        }
+        let Some(next) = state.path.last().copied() else {
+            continue;
+        };
+
+        let offset = next.x - feet.x;
+        if offset.abs() > CELL_SIZE * 0.25 {
+            patrol.direction = offset.signum();
+        }
+        velocity.x = patrol.direction * patrol.speed;
+
+        let grounded = spatial
+            .cast_ray(
+                center,
+                Dir2::NEG_Y,
+                (aabb.max.y - aabb.min.y) * 0.5 + EDGE_PROBE,
+                true,
+                &filter,
+            )
+            .is_some();
+        if grounded && next.y > feet.y + CELL_SIZE * 0.5 {
+            velocity.y = chase.jump_speed;
+        }
+    }
+}
+
+/// Turns toward the player and fires its [`WeaponDef`] while it has line of
+/// sight.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Enemy,
+    Name::new("Turret"),
+    Health(5.0),
+    RigidBody::Static,
+    DebugPickingColor::new(DARK_ORANGE),
+    NeedsSerializedCollider(Vec2::new(40.0, 20.0)),
+    WeaponDef = Self::def(),
+    TurretState
+)]
+#[reflect(Default, Component)]
+pub struct Turret {
+    /// Shots per second.
+    pub fire_rate: f32,
+    pub range: f32,
+    /// Radians per second.
+    pub turn_rate: f32,
+}
+
+impl Default for Turret {
+    fn default() -> Self {
+        Self {
+            fire_rate: 1.0,
+            range: 600.0,
+            turn_rate: 2.0,
+        }
+    }
+}
+
+impl Turret {
+    fn def() -> WeaponDef {
+        WeaponDef {
+            projectile: Some(Projectile {
+                speed: 500.0,
+                radius: 6.0,
+                gravity_scale: 0.0,
+                despawn_on_hit: true,
+                lifetime: Some(4.0),
+                ..Default::default()
+            }),
+            sfx: Some(Sfx::AssaultRifle),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Component)]
+pub(crate) struct TurretState {
+    angle: f32,
+    cooldown: f32,
+}
+
+/// How far off target, in radians, a [`Turret`] still fires.
+const TURRET_TOLERANCE: f32 = 0.1;
+
+fn turret(
+    mut commands: Commands,
+    time: Res<Time>,
+    spatial: SpatialQuery,
+    players: Query<(Entity, &GlobalTransform), (With<Player>, Without<Downed>)>,
+    mut turrets: Query<(
+        Entity,
+        &Turret,
+        &WeaponDef,
+        &mut TurretState,
+        &mut Transform,
+        &GlobalTransform,
+    )>,
+    mut pool: ResMut<BulletPool>,
+    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+    mut sfx: MessageWriter<PlaySfx>,
+    settings: Res<Settings>,
+) {
+    let dt = time.delta_secs();
+    let filter = SpatialQueryFilter::from_mask([Layer::Wall, Layer::Player]);
+    let fire_rate = settings.difficulty.table().enemy_fire_rate;
+
+    for (entity, turret, def, mut state, mut transform, global) in turrets.iter_mut() {
+        state.cooldown = (state.cooldown - dt).max(0.0);
+        let origin = global.translation().xy();
+        let Some((player, target)) = nearest_player(&players, origin) else {
+            continue;
+        };
+        let Ok(direction) = Dir2::new(target - origin) else {
+            continue;
+        };
+        let in_sight = spatial
+            .cast_ray(origin, direction, turret.range, true, &filter)
+            .is_some_and(|hit| hit.entity == player);
+        if !in_sight {
+            continue;
+        }
+
+        let offset = (direction.to_angle() - state.angle + PI).rem_euclid(TAU) - PI;
+        let turn = turret.turn_rate * dt;
+        state.angle += offset.clamp(-turn, turn);
+        transform.rotation = Quat::from_rotation_z(state.angle);
+
+        if offset.abs() < TURRET_TOLERANCE && state.cooldown == 0.0 {
+            state.cooldown = 1.0 / (turret.fire_rate * fire_rate).max(f32::EPSILON);
+            if let Some(effect) = def.sfx {
+                sfx.write(PlaySfx::at(effect, origin));
+            }
+            def.spawn_projectiles(
+                &mut commands,
+                &mut pool,
+                &mut **rng,
+                origin,
+                Vec2::from_angle(state.angle),
+                |bullet| {
+                    bullet.insert((
+                        EnemyBullet,
+                        FiredBy(entity),
+                        Layer::EnemyBullet.collision_layers(),
+                    ));
+                },
+            );
+        }
+    }
+}
+
+/// Chases the player through the air and explodes on contact.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Enemy,
+    Name::new("Flyer"),
+    Health(2.0),
+    GravityScale(0.0),
+    DebugPickingColor::new(MEDIUM_PURPLE),
+    NeedsSerializedCollider(Vec2::splat(24.0))
+)]
+#[reflect(Default, Component)]
+pub struct Flyer {
+    /// Distance at which the flyer starts chasing the player.
+    pub aggro_radius: f32,
+    pub speed: f32,
+}
+
+impl Default for Flyer {
+    fn default() -> Self {
+        Self {
+            aggro_radius: 400.0,
+            speed: 250.0,
+        }
+    }
+}
+
+/// Change in a [`Flyer`]'s velocity per second.
+const FLYER_ACCELERATION: f32 = 600.0;
+
+/// Length of the rays a [`Flyer`] uses to steer around walls.
+const FLYER_AVOIDANCE: f32 = 40.0;
+
+fn flyer(
+    time: Res<Time>,
+    spatial: SpatialQuery,
+    players: Query<(Entity, &GlobalTransform), (With<Player>, Without<Downed>)>,
+    mut flyers: Query<(&Flyer, &mut LinearVelocity, &GlobalTransform)>,
+) {
+    let dt = time.delta_secs();
+    let filter = SpatialQueryFilter::from_mask([Layer::Wall, Layer::KillBox]);
+
+    for (flyer, mut velocity, transform) in flyers.iter_mut() {
+        let position = transform.translation().xy();
+        let to_player = nearest_player(&players, position)
+            .map_or(Vec2::INFINITY, |(_, target)| target - position);
+        let mut desired = if to_player.length() < flyer.aggro_radius {
+            to_player.normalize_or_zero() * flyer.speed
+        } else {
+            Vec2::ZERO
+        };
+
+        if let Ok(heading) = Dir2::new(desired) {
+            for angle in [0.0, PI / 4.0, -PI / 4.0] {
+                let ray = Rot2::radians(angle) * heading;
+                if let Some(hit) = spatial.cast_ray(position, ray, FLYER_AVOIDANCE, true, &filter) {
+                    let closeness = 1.0 - hit.distance / FLYER_AVOIDANCE;
+                    desired += hit.normal * flyer.speed * closeness;
+                }
+            }
+        }
+
+        velocity.0 = velocity.0.move_towards(desired, FLYER_ACCELERATION * dt);
+    }
+}
+
+fn flyer_contact(
+    start: On<CollisionStart>,
+    mut commands: Commands,
+    players: Query<(), With<Player>>,
+    flyers: Query<&GlobalTransform, With<Flyer>>,
+) {
+    let Ok(transform) = flyers.get(start.collider1) else {
+        return;
+    };
+    if !players.contains(start.collider2) {
+        return;
+    }
+    let position = transform.translation().xy();
+    // Knocks back without hurting anything around it.
+    commands.trigger(Explosion {
+        position,
+        force: 2_500.0,
+        radius: 0.0,
+        damage: 0.0,
+    });
+    commands.trigger(ParticleBurst {
+        position,
+        color: MEDIUM_PURPLE.into(),
+        count: 24,
+    });
+    commands.entity(start.collider1).despawn();
+}
+
+/// A [`Bullet`] fired by an enemy, which hurts the player instead of enemies.
+///
+/// Player bullets shoot it down and a [`Deflecting`] player sends it back as
+/// their own.
+#[derive(Component)]
+pub struct EnemyBullet;
+
+fn enemy_bullet_damage(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    players: Query<(), (With<Player>, Without<Deflecting>)>,
+    bullets: Query<(), With<EnemyBullet>>,
+) {
+    for impact in impacts.read() {
+        if bullets.contains(impact.bullet) && players.contains(impact.target) {
+            commands.trigger(Hurt {
+                entity: impact.target,
+                amount: 1.0,
+                source: impact.position,
+            });
+        }
+    }
+}
+
+fn deflect_enemy_bullet(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    players: Query<&AimVector, (With<Player>, With<Deflecting>)>,
+    mut bullets: Query<&mut LinearVelocity, With<EnemyBullet>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    for impact in impacts.read() {
+        let Ok(aim) = players.get(impact.target) else {
+            continue;
+        };
+        let Ok(mut velocity) = bullets.get_mut(impact.bullet) else {
+            continue;
+        };
+        velocity.0 = aim.0.normalize_or(-velocity.0.normalize_or_zero()) * velocity.0.length();
+        commands
+            .entity(impact.bullet)
+            .remove::<EnemyBullet>()
+            .insert(Layer::Bullet.collision_layers());
+        sfx.write(PlaySfx::new(Sfx::Click));
+    }
+}
+
+fn shoot_down_enemy_bullet(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    enemy_bullets: Query<(), With<EnemyBullet>>,
+    player_bullets: Query<(), (With<Bullet>, Without<EnemyBullet>)>,
+) {
+    for impact in impacts.read() {
+        if enemy_bullets.contains(impact.bullet) && player_bullets.contains(impact.target) {
+            commands.trigger(ParticleBurst {
+                position: impact.position,
+                color: DARK_ORANGE.into(),
+                count: 6,
+            });
+            commands
+                .entity(impact.bullet)
+                .queue_silenced(despawn_bullet);
+        }
+    }
+}
+
+fn contact_damage(
+    start: On<CollisionStart>,
+    mut commands: Commands,
+    players: Query<(), With<Player>>,
+    enemies: Query<&GlobalTransform, (With<Enemy>, Without<Flyer>)>,
+) {
+    if let Ok(transform) = enemies.get(start.collider1)
+        && players.contains(start.collider2)
+    {
+        commands.trigger(Hurt {
+            entity: start.collider2,
+            amount: 1.0,
+            source: transform.translation().xy(),
+        });
+    }
+}
+
+fn enemy_died(
+    died: On<Died>,
+    mut commands: Commands,
+    enemies: Query<Option<&Sprite>, With<Enemy>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let Ok(sprite) = enemies.get(died.entity) else {
+        return;
+    };
+    sfx.write(PlaySfx::at(Sfx::KeyDestroyed, died.position));
+    commands.trigger(ParticleBurst {
+        position: died.position,
+        color: sprite.map_or(CRIMSON.into(), |sprite| sprite.color),
+        count: 16,
+    });
+}
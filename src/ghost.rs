@@ -0,0 +1,107 @@
+use crate::{
+    level::{DespawnOnLevelUnload, Level, LevelGeometry, LevelTime},
+    replay::{self, Recording},
+    settings::Settings,
+};
+use avian2d::prelude::TransformInterpolation;
+use bevy::{
+    color::palettes::css::{ORANGE, SKY_BLUE},
+    prelude::*,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Update, spawn_ghost)
+        .add_systems(FixedUpdate, move_ghost);
+}
+
+/// Re-enacts a run through the current level alongside the player.
+///
+/// There is one for the best completed run and one for the previous attempt
+/// this session.
+#[derive(Component)]
+#[require(DespawnOnLevelUnload, TransformInterpolation)]
+struct Ghost {
+    positions: Vec<Vec2>,
+    step: usize,
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    level: Res<Level>,
+    level_time: Res<LevelTime>,
+    time: Res<Time<Fixed>>,
+    recording: Option<Res<Recording>>,
+    new_level: Query<(), Added<LevelGeometry>>,
+    ghosts: Query<Entity, With<Ghost>>,
+    mut enabled: Local<bool>,
+) {
+    let toggled = settings.ghost != *enabled;
+    *enabled = settings.ghost;
+    if new_level.is_empty() && !toggled {
+        return;
+    }
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !settings.ghost {
+        return;
+    }
+
+    // Catch up with the player when enabled partway through a level.
+    let step = if new_level.is_empty() {
+        (level_time.0 / time.timestep().as_secs_f64()) as usize
+    } else {
+        0
+    };
+    if let Some(run) = replay::best_run(&level.0) {
+        spawn(&mut commands, "Ghost", ORANGE.into(), run.positions, step);
+    }
+    // The last segment is the attempt in progress.
+    if let Some((_, previous)) = recording
+        .as_ref()
+        .and_then(|recording| recording.replay().segments.split_last())
+        && let Some(attempt) = previous.last().filter(|segment| segment.level == level.0)
+    {
+        let positions = attempt.positions.clone();
+        spawn(
+            &mut commands,
+            "Last attempt",
+            SKY_BLUE.into(),
+            positions,
+            step,
+        );
+    }
+}
+
+fn spawn(
+    commands: &mut Commands,
+    name: &'static str,
+    color: Color,
+    positions: Vec<[f32; 2]>,
+    step: usize,
+) {
+    let positions = positions
+        .into_iter()
+        .map(Vec2::from_array)
+        .collect::<Vec<_>>();
+    let Some(position) = positions.get(step) else {
+        return;
+    };
+    commands.spawn((
+        Name::new(name),
+        Transform::from_translation(position.extend(-0.5)),
+        Sprite::from_color(color.with_alpha(0.3), Vec2::new(25.0, 40.0)),
+        Ghost { positions, step },
+    ));
+}
+
+fn move_ghost(mut commands: Commands, mut ghosts: Query<(Entity, &mut Ghost, &mut Transform)>) {
+    for (entity, mut ghost, mut transform) in ghosts.iter_mut() {
+        ghost.step += 1;
+        match ghost.positions.get(ghost.step) {
+            Some(position) => transform.translation = position.extend(transform.translation.z),
+            None => commands.entity(entity).despawn(),
+        }
+    }
+}
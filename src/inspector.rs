@@ -4,36 +4,100 @@
 //! - `drag`: moves the transform under the cursor.
 //! - `<shift>drag`: vertical scale.
 //! - `<cr>drag`: horizontal scale.
+//! - `<cr><shift>drag`: rotates, in steps of [`ROTATION_STEP`] with snapping.
 //! - `<alt>click`: create a new wall.
 //!
+//! The [`SpawnPoint`] is outlined where the player starts, drag it to move
+//! the start of the level.
+//!
+//! With [`Snapping`] enabled, moved, scaled and placed rectangles are aligned
+//! to the grid. Rotated rectangles keep their rotation, but are not resized to
+//! fit the grid.
+//!
 //! ## Selection
-//! - `click`: selects an entity.
-//! - `<cr>v`: clones the selected entity under the cursor.
+//! - `click`: selects an entity, or only that entity of the selection.
+//! - `<shift>click`: adds an entity to or removes it from the selection.
+//! - `<shift>drag`: adds the entities in a box to the selection, when started
+//!   off any entity.
+//! - `drag`: moves the whole selection when dragging a selected entity.
+//! - `<cr>v`: clones the selection under the cursor.
+//! - `right click`: deletes the entity under the cursor, or the selection if
+//!   it is selected.
+//!
+//! ## History
+//! Drags, scales, rotations, placements, pastes and deletions are recorded in the
+//! [`EditHistory`] until the next level load.
+//! - `<cr>z`: undo.
+//! - `<cr><shift>z`: redo.
+//!
+//! ## Paths
+//! Waypoints of the selected [`Patrol`] enemy or [`MovingPlatform`] are drawn
+//! as handles. Moving platforms are placed with the `MovingPlatform` command.
+//! - `drag`: moves the waypoint under the cursor.
+//! - `<alt>click`: appends a waypoint, instead of creating a wall.
+//! - `delete`: removes the waypoint under the cursor.
 //!
 //! ## Terminal
+//! `/` opens the terminal and `escape` closes it. Commands may be prefixed
+//! with `/`. Submitted commands are kept in the
+//! [`TermHistory`], which is saved to `term_history.txt`.
+//! - `up`/`down`: recalls the previous or next command.
+//! - `<cr>u`: clears the line.
+//! - `tab`: completes the command, level entity or level ident, listing the
+//!   candidates when ambiguous.
 //! - `l ident`: loads the level with `ident`.
 //! - `c ident`: copies the current state into a new level with `ident`.
 //! - `ammo <new_ammo>`
+//! - `set <setting> <value>`: changes a field of [`Settings`].
+//! - `difficulty <easy|normal|hard>`: changes the [`Settings::difficulty`].
+//! - `transition <fade|wipe|iris>`: changes the [`Settings::transition`].
+//! - `physics [<field> <value>]`: prints or changes the [`PhysicsConfig`].
+//! - `music <ident|off>`: crossfades to the track `ident` and keeps it as the
+//!   level's [`LevelMusic`].
+//! - `bench <bullets|walls> <count>`: spawns `count` entities and reports timings.
+//! - `entities`: lists registered level entities and weapons, see [`crate::registry`].
+//! - `layers`: prints the collision layer matrix, see [`crate::collision`].
+//! - `spectate`: toggles the free-flying [`Spectating`] camera, freezing the players.
+//! - `grid [<size>]`: toggles [`Snapping`], or enables it with cells of `size`.
+//! - `align <left|right|top|bottom|center|middle>`: lines the selection up on
+//!   an edge or center line of the entity shown in the inspector.
+//! - `poly`: starts a [`PolygonTool`] wall, whose vertices are then clicked,
+//!   and finishes it when entered again.
 //! - `{type_name} ...`: spawns entity with components `type_name` under cursor.
 //! - `relate <src_id> Relationship <dst_id>`
 
 use crate::{
-    level::{self, Door, Key, KeyOf, KillBox, KillboxClock, Level, LevelGeometry, Wall, rectangle},
+    bench::{self, Bench, BenchKind},
+    camera::Spectating,
+    collision,
+    coop::{Downed, Partner},
+    enemy::{Patrol, PatrolPath},
+    level::{
+        self, Door, KeyOf, KillBox, KillboxClock, Level, LevelGeometry, MovingPlatform, PlateOf,
+        SerializedColliderConstructor, SpawnPoint, Wall, rectangle,
+    },
+    menu::GameState,
+    music::{self, LevelMusic, MusicTrack},
+    physics::PhysicsConfig,
     player::Player,
-    weapon::{self, Ammo, MaxAmmo, SelectedWeapon, Weapon, WeaponPickup},
+    registry::Registry,
+    settings::Settings,
+    weapon::{Ammo, MaxAmmo, PrimaryWeapon, Weapon},
 };
-use avian2d::prelude::{LinearVelocity, RigidBody};
+use avian2d::prelude::{ColliderConstructor, LinearVelocity, RigidBody};
 use bevy::{
-    color::palettes::css::MAGENTA,
-    ecs::relationship::Relationship,
+    color::palettes::css::{MAGENTA, ORANGE},
+    ecs::{entity::EntityHashMap, relationship::Relationship},
     log::{
         BoxedLayer,
         tracing::{self, Subscriber},
         tracing_subscriber::Layer,
     },
+    picking::{hover::HoverMap, mesh_picking::MeshPickingPlugin, pointer::PointerId},
     prelude::*,
     reflect::FromType,
     sprite_render::{Wireframe2d, Wireframe2dColor, Wireframe2dPlugin},
+    tasks::IoTaskPool,
     window::PrimaryWindow,
 };
 use bevy_egui::{EguiContext, EguiPrimaryContextPass, PrimaryEguiContext};
@@ -44,18 +108,27 @@ use bevy_simple_text_input::{
 };
 use std::{
     collections::VecDeque,
+    fmt::Write,
+    fs,
+    str::FromStr,
     sync::{Arc, Mutex, atomic::AtomicUsize},
 };
 
 pub fn plugin(app: &mut App) {
     app.add_plugins((
         Wireframe2dPlugin::default(),
+        // Walls that are not rectangles are meshes.
+        MeshPickingPlugin,
         bevy_egui::EguiPlugin::default(),
         bevy_inspector_egui::DefaultInspectorConfigPlugin,
         term_plugin,
         debug_information_plugin,
+        bench::plugin,
     ))
-    .add_message::<SelectionEvent>()
+    .init_resource::<DraggedWaypoint>()
+    .init_resource::<EditHistory>()
+    .init_resource::<Snapping>()
+    .init_resource::<PolygonTool>()
     .add_systems(Startup, spawn_selection)
     .add_systems(
         Update,
@@ -66,10 +139,15 @@ pub fn plugin(app: &mut App) {
                 disable_input.after(toggle_term),
                 enter_exit_inspector,
                 place_thing,
+                box_select,
+                grid_gizmos,
+                draw_polygon,
+                (edit_path, path_gizmos).chain(),
+                spawn_point_gizmos,
                 select_weapon,
                 paste_selection,
+                (clear_history, undo_redo),
                 tags,
-                selection_wireframe,
                 animate_wireframe_color,
             ),
         )
@@ -79,24 +157,27 @@ pub fn plugin(app: &mut App) {
     .register_required_components::<Player, Pickable>()
     .register_required_components::<Player, Selectable>()
     .register_required_components::<Player, DontCopy>()
-    .register_required_components::<Door, Pickable>()
-    .register_required_components::<Door, Selectable>()
     .register_required_components::<Door, DontCopy>()
-    .register_required_components::<Wall, Pickable>()
-    .register_required_components::<Wall, Selectable>()
-    .register_required_components::<KillBox, Pickable>()
-    .register_required_components::<KillBox, Selectable>()
-    .register_required_components::<Key, Pickable>()
-    .register_required_components::<Key, Selectable>()
-    .register_required_components::<WeaponPickup, Pickable>()
-    .register_required_components::<WeaponPickup, Selectable>()
     .add_observer(drag_transform)
     .add_observer(delete_selectable)
     .add_observer(horizontal_expand_selectable)
     .add_observer(vertical_expand_selectable)
+    .add_observer(rotate_selectable)
+    .add_observer(start_transform_edit)
+    .add_observer(end_transform_edit)
     .add_observer(make_selection)
+    .add_observer(click_selection)
+    .add_observer(add_wireframe)
+    .add_observer(remove_wireframe)
     .register_type_data::<ChildOf, ReflectRelationship>()
-    .register_type_data::<KeyOf, ReflectRelationship>();
+    .register_type_data::<KeyOf, ReflectRelationship>()
+    .register_type_data::<PlateOf, ReflectRelationship>();
+}
+
+/// Makes `C` pickable and selectable in the inspector.
+pub fn editable<C: Component>(app: &mut App) {
+    app.register_required_components::<C, Pickable>()
+        .register_required_components::<C, Selectable>();
 }
 
 fn in_inspector(inspector: Option<Single<&Inspector>>) -> bool {
@@ -109,19 +190,23 @@ pub struct DisableInput;
 fn disable_input(
     mut commands: Commands,
     disable_input: Query<&DisableInput>,
-    ctx: Single<(Entity, &ContextActivity<Player>)>,
+    spectating: Option<Res<Spectating>>,
+    players: Query<(Entity, &ContextActivity<Player>), Without<Downed>>,
 ) {
-    let (player, ctx) = ctx.into_inner();
-    if **ctx && !disable_input.is_empty() {
-        commands
-            .entity(player)
-            .insert((RigidBody::Static, ContextActivity::<Player>::INACTIVE));
-    } else if !**ctx && disable_input.is_empty() {
-        commands.entity(player).insert((
-            RigidBody::Dynamic,
-            LinearVelocity::default(),
-            ContextActivity::<Player>::ACTIVE,
-        ));
+    let disabled =
+        !disable_input.is_empty() || spectating.is_some_and(|spectating| !spectating.auto);
+    for (player, ctx) in players.iter() {
+        if **ctx && disabled {
+            commands
+                .entity(player)
+                .insert((RigidBody::Static, ContextActivity::<Player>::INACTIVE));
+        } else if !**ctx && !disabled {
+            commands.entity(player).insert((
+                RigidBody::Dynamic,
+                LinearVelocity::default(),
+                ContextActivity::<Player>::ACTIVE,
+            ));
+        }
     }
 }
 
@@ -131,22 +216,27 @@ struct Inspector;
 fn enter_exit_inspector(
     mut commands: Commands,
     input: Res<ButtonInput<KeyCode>>,
-    mut enabled: Local<bool>,
     inspector: Query<Entity, With<Inspector>>,
     term: Single<&TextInputInactive>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     if input.just_pressed(KeyCode::KeyI) && term.0 {
-        if !*enabled {
-            commands.spawn((Inspector, DisableInput));
+        if inspector.is_empty() {
+            commands.run_system_cached(open_inspector);
         } else {
             for entity in inspector.iter() {
                 commands.entity(entity).despawn();
             }
+            next_state.set(GameState::Playing);
         }
-        *enabled = !*enabled;
     }
 }
 
+pub fn open_inspector(mut commands: Commands, mut next_state: ResMut<NextState<GameState>>) {
+    commands.spawn((Inspector, DisableInput));
+    next_state.set(GameState::Editor);
+}
+
 // ENTITY PICKING
 
 #[derive(Default, Component)]
@@ -155,81 +245,343 @@ struct Selectable;
 #[derive(Default, Component)]
 struct DontCopy;
 
+/// The selected entity shown in the inspector, the last one selected.
+///
+/// The whole selection, including this entity, is marked [`Selected`].
 #[derive(Component)]
 struct Selection(Entity);
 
+/// Member of the selection.
+#[derive(Component)]
+struct Selected;
+
 fn spawn_selection(mut commands: Commands) {
     commands.spawn(Selection(Entity::PLACEHOLDER));
 }
 
 fn make_selection(
     press: On<Pointer<Press>>,
+    mut commands: Commands,
     mut selection: Single<&mut Selection>,
     selectable: Query<(), With<Selectable>>,
-    mut writer: MessageWriter<SelectionEvent>,
+    selected: Query<Entity, With<Selected>>,
+    input: Res<ButtonInput<KeyCode>>,
 ) {
-    if selectable.get(press.entity).is_ok() {
-        writer.write(SelectionEvent {
-            old: selection.0,
-            new: press.entity,
-        });
+    if press.button != PointerButton::Primary || !selectable.contains(press.entity) {
+        return;
+    }
+    // Pressing a member keeps the selection so that it is dragged along, and
+    // `<shift>click`s are handled once released in `click_selection`.
+    if selected.contains(press.entity) {
+        selection.0 = press.entity;
+    } else if !input.pressed(KeyCode::ShiftLeft) {
+        for entity in selected.iter() {
+            commands.entity(entity).remove::<Selected>();
+        }
+        commands.entity(press.entity).insert(Selected);
         selection.0 = press.entity;
     }
 }
 
+fn click_selection(
+    click: On<Pointer<Click>>,
+    mut commands: Commands,
+    mut selection: Single<&mut Selection>,
+    selectable: Query<(), With<Selectable>>,
+    selected: Query<Entity, With<Selected>>,
+    transforms: Query<&Transform>,
+    input: Res<ButtonInput<KeyCode>>,
+    history: Res<EditHistory>,
+) {
+    if click.button != PointerButton::Primary || !selectable.contains(click.entity) {
+        return;
+    }
+    // Drags and scales end with a click too.
+    if history.dragging.iter().any(|(entity, before)| {
+        transforms
+            .get(*entity)
+            .is_ok_and(|transform| transform != before)
+    }) {
+        return;
+    }
+
+    if !input.pressed(KeyCode::ShiftLeft) {
+        for entity in selected.iter().filter(|entity| *entity != click.entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
+    } else if selected.contains(click.entity) {
+        commands.entity(click.entity).remove::<Selected>();
+        if selection.0 == click.entity {
+            selection.0 = selected
+                .iter()
+                .find(|entity| *entity != click.entity)
+                .unwrap_or(Entity::PLACEHOLDER);
+        }
+    } else {
+        commands.entity(click.entity).insert(Selected);
+        selection.0 = click.entity;
+    }
+}
+
+fn box_select(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    hover_map: Res<HoverMap>,
+    selectable: Query<(Entity, &GlobalTransform), With<Selectable>>,
+    mut selection: Single<&mut Selection>,
+    mut corner: Local<Option<Vec2>>,
+    _enable: Single<&Inspector>,
+) {
+    let (camera, camera_transform) = camera.into_inner();
+    let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+
+    // Over an entity, `<shift>drag` scales it instead.
+    let over_selectable = hover_map
+        .get(&PointerId::Mouse)
+        .is_some_and(|hits| hits.keys().any(|entity| selectable.contains(*entity)));
+    if mouse_input.just_pressed(MouseButton::Left)
+        && key_input.pressed(KeyCode::ShiftLeft)
+        && !key_input.pressed(KeyCode::AltLeft)
+        && !over_selectable
+    {
+        *corner = Some(world_position);
+    }
+    let Some(start) = *corner else {
+        return;
+    };
+
+    let rect = Rect::from_corners(start, world_position);
+    if mouse_input.pressed(MouseButton::Left) {
+        gizmos.rect_2d(
+            Isometry2d::from_translation(rect.center()),
+            rect.size(),
+            Color::WHITE,
+        );
+        return;
+    }
+
+    *corner = None;
+    for (entity, transform) in selectable.iter() {
+        if rect.contains(transform.translation().xy()) {
+            commands.entity(entity).insert(Selected);
+            selection.0 = entity;
+        }
+    }
+}
+
 fn paste_selection(
     mut commands: Commands,
     key_input: Res<ButtonInput<KeyCode>>,
     window: Single<&Window, With<PrimaryWindow>>,
     camera: Single<(&Camera, &GlobalTransform)>,
     dont_copy: Query<&DontCopy>,
-    selection: Single<&Selection>,
+    mut selection: Single<&mut Selection>,
+    selected: Query<Entity, With<Selected>>,
     transforms: Query<&Transform>,
+    mut history: ResMut<EditHistory>,
     _enable: Single<&Inspector>,
 ) -> Result {
     if !key_input.pressed(KeyCode::ControlLeft) || !key_input.just_pressed(KeyCode::KeyV) {
         return Ok(());
     }
 
-    if dont_copy.contains(selection.0) {
+    let (camera, camera_transform) = camera.into_inner();
+    let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return Ok(());
+    };
+    let Ok(origin) = transforms.get(selection.0) else {
         return Ok(());
+    };
+
+    // The clones become the selection, keeping their offsets to the entity
+    // shown in the inspector, which lands under the cursor.
+    let offset = (world_position - origin.translation.xy()).extend(0.0);
+    let mut clones = Vec::new();
+    for entity in selected
+        .iter()
+        .filter(|entity| !dont_copy.contains(*entity))
+    {
+        let mut transform = *transforms.get(entity)?;
+        transform.translation += offset;
+        let clone = commands
+            .entity(entity)
+            .clone_and_spawn()
+            .insert((transform, Selected))
+            .id();
+        commands.entity(entity).remove::<Selected>();
+        if entity == selection.0 {
+            selection.0 = clone;
+        }
+        clones.push(Edit::Despawn(clone));
+    }
+    if let Some(undo) = Edit::group(clones) {
+        history.push(undo);
     }
+    Ok(())
+}
+
+// TODO: no wireframes?
+fn add_wireframe(add: On<Add, Selected>, mut commands: Commands) {
+    commands.entity(add.entity).insert((
+        Wireframe2d,
+        Wireframe2dColor {
+            color: Color::WHITE,
+        },
+    ));
+}
 
+fn remove_wireframe(remove: On<Remove, Selected>, mut commands: Commands) {
+    commands.entity(remove.entity).try_remove::<Wireframe2d>();
+}
+
+/// Edge or center line that the selection is lined up on by `align`.
+#[derive(Clone, Copy)]
+enum Align {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// The vertical center line.
+    Center,
+    /// The horizontal center line.
+    Middle,
+}
+
+impl FromStr for Align {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            "center" => Ok(Self::Center),
+            "middle" => Ok(Self::Middle),
+            _ => Err(format!("unknown alignment `{s}`")),
+        }
+    }
+}
+
+/// Vertices of the polygon wall being drawn, `None` when not drawing.
+///
+/// The wall gets the convex hull of the vertices as its collider, so
+/// concave outlines are filled in.
+#[derive(Default, Resource)]
+struct PolygonTool(Option<Vec<Vec2>>);
+
+fn draw_polygon(
+    mut gizmos: Gizmos,
+    mut tool: ResMut<PolygonTool>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    snapping: Res<Snapping>,
+    _enable: Single<&Inspector>,
+) {
+    let Some(points) = &mut tool.0 else {
+        return;
+    };
     let (camera, camera_transform) = camera.into_inner();
-    if let Some(world_position) = window
+    let cursor = window
         .cursor_position()
         .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
-        && let Ok(mut entity) = commands.get_entity(selection.0)
+        .map(|cursor| snapping.translation(cursor, Vec2::ZERO));
+    if let Some(cursor) = cursor
+        && mouse_input.just_pressed(MouseButton::Left)
     {
-        let mut transform = *transforms.get(selection.0)?;
-        transform.translation.x = world_position.x;
-        transform.translation.y = world_position.y;
-        entity.clone_and_spawn().insert(transform);
+        points.push(cursor);
+    }
+
+    let outline = points.iter().copied().chain(cursor);
+    gizmos.linestrip_2d(outline, MAGENTA);
+    for point in points.iter() {
+        gizmos.circle_2d(*point, 4.0, MAGENTA);
     }
-    Ok(())
 }
 
-#[derive(Message)]
-struct SelectionEvent {
-    old: Entity,
-    new: Entity,
+/// Spawns a wall from the vertices clicked with the [`PolygonTool`].
+fn finish_polygon(
+    points: In<Vec<Vec2>>,
+    mut commands: Commands,
+    level_geometry: Single<Entity, With<LevelGeometry>>,
+    mut history: ResMut<EditHistory>,
+) {
+    let center = points.iter().sum::<Vec2>() / points.len() as f32;
+    let points = points.iter().map(|point| *point - center).collect();
+    let placed = commands
+        .spawn((
+            ChildOf(*level_geometry),
+            RigidBody::Static,
+            Transform::from_translation(center.extend(0.0)),
+            SerializedColliderConstructor(ColliderConstructor::ConvexHull { points }),
+            Name::new("Inspector Polygon"),
+            Wall,
+        ))
+        .id();
+    history.push(Edit::Despawn(placed));
 }
 
-// TODO: no wireframes?
-fn selection_wireframe(mut commands: Commands, mut events: MessageReader<SelectionEvent>) {
-    for event in events.read() {
-        if let Ok(mut entity) = commands.get_entity(event.old) {
-            entity.remove::<Wireframe2d>();
-        }
-        if let Ok(mut entity) = commands.get_entity(event.new) {
-            entity.insert((
-                Wireframe2d,
-                Wireframe2dColor {
-                    color: Color::WHITE,
-                },
-            ));
+/// Lines the selection up on the entity shown in the inspector.
+fn align_selection(
+    align: In<Align>,
+    selection: Single<&Selection>,
+    mut transforms: Query<
+        (
+            Entity,
+            &mut Transform,
+            Option<&SerializedColliderConstructor>,
+        ),
+        With<Selected>,
+    >,
+    mut history: ResMut<EditHistory>,
+) {
+    let Ok((_, anchor, collider)) = transforms.get(selection.0) else {
+        error!("nothing is selected");
+        return;
+    };
+    let half_size = anchor.scale.xy() * rectangle_size(collider) / 2.0;
+    let center = anchor.translation.xy();
+    let line = match *align {
+        Align::Left => center.x - half_size.x,
+        Align::Right => center.x + half_size.x,
+        Align::Top => center.y + half_size.y,
+        Align::Bottom => center.y - half_size.y,
+        Align::Center => center.x,
+        Align::Middle => center.y,
+    };
+
+    let mut undo = Vec::new();
+    for (entity, mut transform, collider) in transforms.iter_mut() {
+        let half_size = transform.scale.xy() * rectangle_size(collider) / 2.0;
+        let before = *transform;
+        match *align {
+            Align::Left => transform.translation.x = line + half_size.x,
+            Align::Right => transform.translation.x = line - half_size.x,
+            Align::Top => transform.translation.y = line - half_size.y,
+            Align::Bottom => transform.translation.y = line + half_size.y,
+            Align::Center => transform.translation.x = line,
+            Align::Middle => transform.translation.y = line,
+        }
+        if *transform != before {
+            undo.push(Edit::Transform(entity, before));
         }
     }
+    if let Some(undo) = Edit::group(undo) {
+        history.push(undo);
+    }
 }
 
 fn animate_wireframe_color(time: Res<Time>, mut wireframes: Query<&mut Wireframe2dColor>) {
@@ -330,20 +682,136 @@ fn tags(
     Ok(())
 }
 
+// GRID SNAPPING
+
+/// Grid that edited level geometry is aligned to.
+///
+/// Rectangles snap their edges to the grid lines and their size to whole
+/// cells, other shapes snap their center.
+#[derive(Resource)]
+struct Snapping {
+    enabled: bool,
+    size: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: 25.0,
+        }
+    }
+}
+
+impl Snapping {
+    /// Snaps a translation, given the half size of the shape centered on it.
+    fn translation(&self, translation: Vec2, half_size: Vec2) -> Vec2 {
+        if !self.enabled {
+            return translation;
+        }
+        ((translation - half_size) / self.size).round() * self.size + half_size
+    }
+
+    /// Snaps the transform of a shape with unscaled `size`.
+    fn transform(&self, transform: &mut Transform, size: Vec2) {
+        if !self.enabled {
+            return;
+        }
+        // The edges of rotated shapes do not line up with the grid.
+        let size = if transform.rotation.is_near_identity() {
+            size
+        } else {
+            Vec2::ZERO
+        };
+        if size.cmpgt(Vec2::ZERO).all() {
+            let cells = (transform.scale.xy() * size / self.size)
+                .round()
+                .max(Vec2::ONE);
+            let scale = cells * self.size / size;
+            transform.scale = scale.extend(transform.scale.z);
+        }
+        let half_size = transform.scale.xy() * size / 2.0;
+        let translation = self.translation(transform.translation.xy(), half_size);
+        transform.translation = translation.extend(transform.translation.z);
+    }
+}
+
+/// Unscaled size of a rectangle collider, zero for other shapes.
+fn rectangle_size(collider: Option<&SerializedColliderConstructor>) -> Vec2 {
+    match collider.map(|collider| &collider.0) {
+        Some(ColliderConstructor::Rectangle { x_length, y_length }) => {
+            Vec2::new(*x_length, *y_length)
+        }
+        _ => Vec2::ZERO,
+    }
+}
+
+fn grid_gizmos(
+    mut gizmos: Gizmos,
+    snapping: Res<Snapping>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&GlobalTransform, &Projection), With<Camera>>,
+    _enable: Single<&Inspector>,
+) {
+    if !snapping.enabled {
+        return;
+    }
+    let (transform, projection) = camera.into_inner();
+    let scale = match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    };
+    let cells = (window.size() * scale / snapping.size).ceil().as_uvec2() + UVec2::splat(2);
+    // An even count of cells puts a grid line on the center.
+    let cells = cells + cells % 2;
+    let center = (transform.translation().xy() / snapping.size).round() * snapping.size;
+    gizmos.grid_2d(
+        Isometry2d::from_translation(center),
+        cells,
+        Vec2::splat(snapping.size),
+        Color::WHITE.with_alpha(0.1),
+    );
+}
+
 fn drag_transform(
     pick: On<Pointer<Drag>>,
-    mut transforms: Query<&mut Transform, With<Selectable>>,
+    mut transforms: Query<
+        (&mut Transform, Option<&SerializedColliderConstructor>),
+        With<Selectable>,
+    >,
     input: Res<ButtonInput<KeyCode>>,
+    snapping: Res<Snapping>,
+    history: Res<EditHistory>,
     _enable: Single<&Inspector>,
 ) {
     if input.get_pressed().next().is_some() {
         return;
     }
 
-    if let Ok(mut transform) = transforms.get_mut(pick.entity) {
-        let delta = pick.delta;
-        transform.translation.x += delta.x;
-        transform.translation.y -= delta.y;
+    let Ok((mut transform, collider)) = transforms.get_mut(pick.entity) else {
+        return;
+    };
+    let before = transform.translation;
+    match history.dragging.first() {
+        // Snapped from where the drag started, so that small moves add up.
+        Some((entity, start)) if snapping.enabled && *entity == pick.entity => {
+            transform.translation.x = start.translation.x + pick.distance.x;
+            transform.translation.y = start.translation.y - pick.distance.y;
+            snapping.transform(&mut transform, rectangle_size(collider));
+        }
+        _ => {
+            let delta = pick.delta;
+            transform.translation.x += delta.x;
+            transform.translation.y -= delta.y;
+        }
+    }
+
+    // The rest of the selection follows.
+    let offset = transform.translation - before;
+    for (entity, _) in history.dragging.iter().skip(1) {
+        if let Ok((mut transform, _)) = transforms.get_mut(*entity) {
+            transform.translation += offset;
+        }
     }
 }
 
@@ -354,9 +822,13 @@ fn place_thing(
     window: Single<&Window, With<PrimaryWindow>>,
     camera: Single<(&Camera, &GlobalTransform)>,
     level_geometry: Single<Entity, With<LevelGeometry>>,
+    selection: Single<&Selection>,
+    paths: Query<(), Or<(With<Patrol>, With<MovingPlatform>)>>,
+    snapping: Res<Snapping>,
+    mut history: ResMut<EditHistory>,
     _enable: Single<&Inspector>,
 ) {
-    if !mouse_input.just_pressed(MouseButton::Left) {
+    if !mouse_input.just_pressed(MouseButton::Left) || paths.contains(selection.0) {
         return;
     }
 
@@ -372,39 +844,40 @@ fn place_thing(
         return;
     }
 
-    match (
+    let width = 200.0;
+    let height = 25.0;
+    let transform = Transform::from_translation(
+        snapping
+            .translation(world_position, Vec2::new(width, height) / 2.0)
+            .extend(0.0),
+    );
+    let placed = match (
         key_input.pressed(KeyCode::ControlLeft),
         key_input.pressed(KeyCode::ShiftLeft),
     ) {
-        (false, false) => {
-            let width = 200.0;
-            let height = 25.0;
-            commands.spawn((
+        (false, false) => commands
+            .spawn((
                 ChildOf(*level_geometry),
                 RigidBody::Static,
-                Transform::from_translation(world_position.extend(0.0)),
+                transform,
                 rectangle(width, height),
                 Name::new("Inspector Wall"),
                 Wall,
-            ));
-        }
-        (true, false) => {
-            let width = 200.0;
-            let height = 25.0;
-            commands.spawn((
+            ))
+            .id(),
+        (true, false) => commands
+            .spawn((
                 ChildOf(*level_geometry),
-                Transform::from_translation(world_position.extend(0.0)),
+                transform,
                 rectangle(width, height),
                 Name::new("Inspector Kill Box"),
                 KillBox,
-            ));
-        }
-        (false, true) => {
-            let width = 200.0;
-            let height = 25.0;
-            commands.spawn((
+            ))
+            .id(),
+        (false, true) => commands
+            .spawn((
                 ChildOf(*level_geometry),
-                Transform::from_translation(world_position.extend(0.0)),
+                transform,
                 rectangle(width, height),
                 Name::new("Clocked Kill Box"),
                 KillBox,
@@ -412,14 +885,12 @@ fn place_thing(
                     seconds: 1.0,
                     polarity: true,
                 },
-            ));
-        }
-        (true, true) => {
-            let width = 200.0;
-            let height = 25.0;
-            commands.spawn((
+            ))
+            .id(),
+        (true, true) => commands
+            .spawn((
                 ChildOf(*level_geometry),
-                Transform::from_translation(world_position.extend(0.0)),
+                transform,
                 rectangle(width, height),
                 Name::new("Clocked Kill Box"),
                 KillBox,
@@ -427,54 +898,463 @@ fn place_thing(
                     seconds: 1.0,
                     polarity: false,
                 },
-            ));
-        }
-    }
+            ))
+            .id(),
+    };
+    history.push(Edit::Despawn(placed));
 }
 
 fn delete_selectable(
     pick: On<Pointer<Press>>,
     mut commands: Commands,
     walls: Query<(), With<Selectable>>,
+    selected: Query<Entity, With<Selected>>,
     _enable: Single<&Inspector>,
 ) {
-    if pick.button != PointerButton::Secondary {
+    if pick.button != PointerButton::Secondary || !walls.contains(pick.entity) {
         return;
     }
-    if walls.get(pick.entity).is_ok() {
-        commands.entity(pick.entity).despawn();
+    let entities = if selected.contains(pick.entity) {
+        selected.iter().collect()
+    } else {
+        vec![pick.entity]
+    };
+    commands.run_system_cached_with(delete_recorded, entities);
+}
+
+fn delete_recorded(entities: In<Vec<Entity>>, world: &mut World) {
+    let mut undo = Vec::new();
+    for entity in entities.0 {
+        // Descendants of an entity deleted before are gone already.
+        if world.get_entity(entity).is_ok() {
+            undo.push(Edit::Despawn(entity).apply(world));
+        }
+    }
+    if let Some(undo) = Edit::group(undo) {
+        world.resource_mut::<EditHistory>().push(undo);
     }
 }
 
 fn horizontal_expand_selectable(
     pick: On<Pointer<Drag>>,
-    mut transforms: Query<&mut Transform, With<Selectable>>,
+    mut transforms: Query<
+        (&mut Transform, Option<&SerializedColliderConstructor>),
+        With<Selectable>,
+    >,
     input: Res<ButtonInput<KeyCode>>,
+    snapping: Res<Snapping>,
+    history: Res<EditHistory>,
     _enable: Single<&Inspector>,
 ) {
-    if !input.pressed(KeyCode::ControlLeft) {
+    if !input.pressed(KeyCode::ControlLeft) || input.pressed(KeyCode::ShiftLeft) {
         return;
     }
 
-    if let Ok(mut transform) = transforms.get_mut(pick.entity) {
-        let delta = pick.delta;
-        transform.scale.x += delta.x * 0.05;
+    let Ok((mut transform, collider)) = transforms.get_mut(pick.entity) else {
+        return;
+    };
+    match history.dragging.first() {
+        Some((entity, start)) if snapping.enabled && *entity == pick.entity => {
+            transform.scale.x = start.scale.x + pick.distance.x * 0.05;
+            snapping.transform(&mut transform, rectangle_size(collider));
+        }
+        _ => transform.scale.x += pick.delta.x * 0.05,
     }
 }
 
 fn vertical_expand_selectable(
+    pick: On<Pointer<Drag>>,
+    mut transforms: Query<
+        (&mut Transform, Option<&SerializedColliderConstructor>),
+        With<Selectable>,
+    >,
+    input: Res<ButtonInput<KeyCode>>,
+    snapping: Res<Snapping>,
+    history: Res<EditHistory>,
+    _enable: Single<&Inspector>,
+) {
+    if !input.pressed(KeyCode::ShiftLeft) || input.pressed(KeyCode::ControlLeft) {
+        return;
+    }
+
+    let Ok((mut transform, collider)) = transforms.get_mut(pick.entity) else {
+        return;
+    };
+    match history.dragging.first() {
+        Some((entity, start)) if snapping.enabled && *entity == pick.entity => {
+            transform.scale.y = start.scale.y + pick.distance.y * 0.05;
+            snapping.transform(&mut transform, rectangle_size(collider));
+        }
+        _ => transform.scale.y += pick.delta.y * 0.05,
+    }
+}
+
+/// Angle that rotations snap to with [`Snapping`] enabled.
+const ROTATION_STEP: f32 = std::f32::consts::PI / 12.0;
+
+/// Radians rotated per pixel dragged.
+const ROTATION_SPEED: f32 = 0.01;
+
+fn rotate_selectable(
     pick: On<Pointer<Drag>>,
     mut transforms: Query<&mut Transform, With<Selectable>>,
     input: Res<ButtonInput<KeyCode>>,
+    snapping: Res<Snapping>,
+    history: Res<EditHistory>,
     _enable: Single<&Inspector>,
 ) {
-    if !input.pressed(KeyCode::ShiftLeft) {
+    if !input.pressed(KeyCode::ControlLeft) || !input.pressed(KeyCode::ShiftLeft) {
+        return;
+    }
+
+    let Ok(mut transform) = transforms.get_mut(pick.entity) else {
+        return;
+    };
+    match history.dragging.first() {
+        Some((entity, start)) if snapping.enabled && *entity == pick.entity => {
+            let (_, _, start_angle) = start.rotation.to_euler(EulerRot::XYZ);
+            let angle = start_angle - pick.distance.x * ROTATION_SPEED;
+            let angle = (angle / ROTATION_STEP).round() * ROTATION_STEP;
+            transform.rotation = Quat::from_rotation_z(angle);
+        }
+        _ => transform.rotate_z(-pick.delta.x * ROTATION_SPEED),
+    }
+}
+
+// EDIT HISTORY
+
+/// Most edits kept in the [`EditHistory`].
+const HISTORY_LIMIT: usize = 100;
+
+/// Undo and redo stacks of level edits.
+///
+/// Each stack holds the edits that revert the last change made in the other
+/// direction, so applying one yields its inverse for the opposite stack.
+#[derive(Default, Resource)]
+struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// Transforms when the drag started of the entity being dragged, then of
+    /// the rest of the selection dragged along.
+    dragging: Vec<(Entity, Transform)>,
+}
+
+impl EditHistory {
+    /// Records the edit that undoes a change, clearing the redo stack.
+    fn push(&mut self, undo: Edit) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(undo);
+        self.redo.clear();
+    }
+
+    /// Points edits at respawned entities.
+    fn remap(&mut self, map: &EntityHashMap<Entity>) {
+        for edit in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            edit.remap(map);
+        }
+    }
+}
+
+enum Edit {
+    /// Sets the transform of an entity.
+    Transform(Entity, Transform),
+    /// Despawns an entity and its descendants.
+    Despawn(Entity),
+    /// Respawns the entity with this former id and its descendants from their
+    /// serialized components.
+    Spawn(Entity, DynamicScene),
+    /// Applies several edits at once, like those to a whole selection.
+    Group(Vec<Edit>),
+}
+
+impl Edit {
+    /// Combines edits into one, `None` if there are none.
+    fn group(mut edits: Vec<Edit>) -> Option<Edit> {
+        match edits.len() {
+            0 => None,
+            1 => edits.pop(),
+            _ => Some(Edit::Group(edits)),
+        }
+    }
+
+    fn remap(&mut self, map: &EntityHashMap<Entity>) {
+        match self {
+            Edit::Transform(entity, _) | Edit::Despawn(entity) | Edit::Spawn(entity, _) => {
+                if let Some(mapped) = map.get(entity) {
+                    *entity = *mapped;
+                }
+            }
+            Edit::Group(edits) => {
+                for edit in edits {
+                    edit.remap(map);
+                }
+            }
+        }
+    }
+
+    /// Applies the edit, returning the edit that reverts it.
+    fn apply(self, world: &mut World) -> Edit {
+        match self {
+            Edit::Transform(entity, transform) => match world.get_mut::<Transform>(entity) {
+                Some(mut current) => {
+                    Edit::Transform(entity, std::mem::replace(&mut *current, transform))
+                }
+                None => Edit::Transform(entity, transform),
+            },
+            Edit::Despawn(entity) => {
+                let mut entities = vec![entity];
+                let mut i = 0;
+                while let Some(&parent) = entities.get(i) {
+                    if let Some(children) = world.get::<Children>(parent) {
+                        entities.extend(children.iter());
+                    }
+                    i += 1;
+                }
+                let filter = world.resource::<Registry>().components.clone();
+                let scene = DynamicSceneBuilder::from_world(world)
+                    .with_component_filter(filter)
+                    .extract_entities(entities.into_iter())
+                    .build();
+                world.despawn(entity);
+                Edit::Spawn(entity, scene)
+            }
+            Edit::Spawn(entity, scene) => {
+                // Entities outside of the scene, like the level geometry the
+                // entity is a child of, keep their ids.
+                let mut map = world
+                    .query_filtered::<Entity, Or<(With<Selectable>, With<LevelGeometry>)>>()
+                    .iter(world)
+                    .map(|entity| (entity, entity))
+                    .collect::<EntityHashMap<_>>();
+                if let Err(err) = scene.write_to_world(world, &mut map) {
+                    error!("failed to respawn {entity}: {err}");
+                    return Edit::Spawn(entity, scene);
+                }
+                world.resource_mut::<EditHistory>().remap(&map);
+                Edit::Despawn(map.get(&entity).copied().unwrap_or(entity))
+            }
+            Edit::Group(edits) => {
+                // Reverted in the opposite order.
+                let mut inverse = edits
+                    .into_iter()
+                    .map(|edit| edit.apply(world))
+                    .collect::<Vec<_>>();
+                inverse.reverse();
+                Edit::Group(inverse)
+            }
+        }
+    }
+}
+
+fn start_transform_edit(
+    drag: On<Pointer<DragStart>>,
+    transforms: Query<&Transform, With<Selectable>>,
+    selected: Query<Entity, With<Selected>>,
+    mut history: ResMut<EditHistory>,
+    _enable: Single<&Inspector>,
+) {
+    let Ok(transform) = transforms.get(drag.entity) else {
+        return;
+    };
+    history.dragging = vec![(drag.entity, *transform)];
+    if selected.contains(drag.entity) {
+        let rest = selected
+            .iter()
+            .filter(|entity| *entity != drag.entity)
+            .filter_map(|entity| Some((entity, *transforms.get(entity).ok()?)));
+        history.dragging.extend(rest);
+    }
+}
+
+fn end_transform_edit(
+    drag: On<Pointer<DragEnd>>,
+    transforms: Query<&Transform, With<Selectable>>,
+    mut history: ResMut<EditHistory>,
+) {
+    if history
+        .dragging
+        .first()
+        .is_none_or(|(entity, _)| *entity != drag.entity)
+    {
+        return;
+    }
+    let undo = std::mem::take(&mut history.dragging)
+        .into_iter()
+        .filter(|(entity, before)| {
+            transforms
+                .get(*entity)
+                .is_ok_and(|transform| transform != before)
+        })
+        .map(|(entity, before)| Edit::Transform(entity, before))
+        .collect();
+    if let Some(undo) = Edit::group(undo) {
+        history.push(undo);
+    }
+}
+
+/// Recorded entities are gone once another level loads.
+fn clear_history(mut history: ResMut<EditHistory>, new_level: Query<(), Added<LevelGeometry>>) {
+    if !new_level.is_empty() {
+        *history = EditHistory::default();
+    }
+}
+
+fn undo_redo(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    term: Single<&TextInputInactive>,
+    _enable: Single<&Inspector>,
+) {
+    if term.0 && input.pressed(KeyCode::ControlLeft) && input.just_pressed(KeyCode::KeyZ) {
+        commands.run_system_cached_with(step_history, input.pressed(KeyCode::ShiftLeft));
+    }
+}
+
+fn step_history(redo: In<bool>, world: &mut World) {
+    let mut history = world.resource_mut::<EditHistory>();
+    let edit = if *redo {
+        history.redo.pop()
+    } else {
+        history.undo.pop()
+    };
+    let Some(edit) = edit else {
+        return;
+    };
+    let inverse = edit.apply(world);
+    let mut history = world.resource_mut::<EditHistory>();
+    if *redo {
+        history.undo.push(inverse);
+    } else {
+        history.redo.push(inverse);
+    }
+}
+
+// PATHS
+
+/// Radius of a [`PatrolPath`] or [`MovingPlatform`] waypoint handle.
+const WAYPOINT_HANDLE: f32 = 8.0;
+
+/// Index of the waypoint being dragged in the selected path.
+#[derive(Default, Resource)]
+struct DraggedWaypoint(Option<usize>);
+
+fn edit_path(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    term: Single<&TextInputInactive>,
+    selection: Single<&Selection>,
+    mut patrols: Query<Option<&mut PatrolPath>, With<Patrol>>,
+    mut platforms: Query<&mut MovingPlatform>,
+    mut dragged: ResMut<DraggedWaypoint>,
+    _enable: Single<&Inspector>,
+) {
+    if mouse_input.just_released(MouseButton::Left) {
+        dragged.0 = None;
+    }
+    if !patrols.contains(selection.0) && !platforms.contains(selection.0) {
+        dragged.0 = None;
+        return;
+    }
+
+    let (camera, camera_transform) = camera.into_inner();
+    let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+
+    let (points, next) = if let Ok(platform) = platforms.get_mut(selection.0) {
+        let platform = platform.into_inner();
+        (&mut platform.points, &mut platform.next)
+    } else if let Ok(Some(path)) = patrols.get_mut(selection.0) {
+        let path = path.into_inner();
+        (&mut path.points, &mut path.next)
+    } else {
+        if key_input.pressed(KeyCode::AltLeft) && mouse_input.just_pressed(MouseButton::Left) {
+            commands.entity(selection.0).insert(PatrolPath {
+                points: vec![world_position],
+                next: 0,
+            });
+        }
         return;
+    };
+
+    let hovered = points
+        .iter()
+        .position(|point| point.distance(world_position) < WAYPOINT_HANDLE);
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if key_input.pressed(KeyCode::AltLeft) {
+            points.push(world_position);
+        } else {
+            dragged.0 = hovered;
+        }
+    } else if mouse_input.pressed(MouseButton::Left)
+        && let Some(point) = dragged.0.and_then(|index| points.get_mut(index))
+    {
+        *point = world_position;
     }
 
-    if let Ok(mut transform) = transforms.get_mut(pick.entity) {
-        let delta = pick.delta;
-        transform.scale.y += delta.y * 0.05;
+    if key_input.just_pressed(KeyCode::Delete)
+        && term.0
+        && let Some(index) = hovered
+    {
+        points.remove(index);
+        *next = 0;
+        dragged.0 = None;
+    }
+}
+
+fn spawn_point_gizmos(
+    mut gizmos: Gizmos,
+    spawn_points: Query<&GlobalTransform, With<SpawnPoint>>,
+    _enable: Single<&Inspector>,
+) {
+    for transform in spawn_points.iter() {
+        let position = transform.translation().xy();
+        gizmos.rect_2d(
+            Isometry2d::from_translation(position),
+            Vec2::new(25.0, 40.0),
+            ORANGE,
+        );
+        gizmos.arrow_2d(position + Vec2::Y * 50.0, position + Vec2::Y * 24.0, ORANGE);
+    }
+}
+
+fn path_gizmos(
+    mut gizmos: Gizmos,
+    selection: Single<&Selection>,
+    patrols: Query<(&GlobalTransform, &PatrolPath), With<Patrol>>,
+    platforms: Query<(&GlobalTransform, &MovingPlatform)>,
+    dragged: Res<DraggedWaypoint>,
+    _enable: Single<&Inspector>,
+) {
+    let (transform, points) = if let Ok((transform, path)) = patrols.get(selection.0) {
+        (transform, &path.points)
+    } else if let Ok((transform, platform)) = platforms.get(selection.0) {
+        (transform, &platform.points)
+    } else {
+        return;
+    };
+
+    let start = transform.translation().xy();
+    if let Some(first) = points.first() {
+        gizmos.line_2d(start, *first, Color::WHITE.with_alpha(0.3));
+    }
+    gizmos.linestrip_2d(points.iter().copied(), Color::WHITE);
+    for (i, point) in points.iter().enumerate() {
+        let color = if dragged.0 == Some(i) {
+            Color::from(MAGENTA)
+        } else {
+            Color::WHITE
+        };
+        gizmos.circle_2d(*point, WAYPOINT_HANDLE, color);
     }
 }
 
@@ -500,47 +1380,44 @@ impl<T: Relationship + Component + Reflect> FromType<T> for ReflectRelationship
 fn select_weapon(
     mut commands: Commands,
     input: Res<ButtonInput<KeyCode>>,
-    player: Single<Entity, With<Player>>,
+    player: Single<(Entity, Option<&Children>), (With<Player>, Without<Partner>)>,
+    selected: Query<Entity, With<PrimaryWeapon>>,
     term: Single<&TextInputInactive>,
+    registry: Res<Registry>,
 ) {
     if !term.0 {
         return;
     }
 
+    const DIGITS: [KeyCode; 9] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
     for input in input.get_just_pressed() {
-        match input {
-            KeyCode::Digit1 => {
-                commands
-                    .entity(*player)
-                    .despawn_children()
-                    .with_child((weapon::Shotgun, weapon::SelectedWeapon));
-            }
-            KeyCode::Digit2 => {
-                commands
-                    .entity(*player)
-                    .despawn_children()
-                    .with_child((weapon::AssaultRifle, weapon::SelectedWeapon));
-            }
-            KeyCode::Digit3 => {
-                commands
-                    .entity(*player)
-                    .despawn_children()
-                    .with_child((weapon::GravityGun, weapon::SelectedWeapon));
-            }
-            KeyCode::Digit4 => {
-                commands
-                    .entity(*player)
-                    .despawn_children()
-                    .with_child((weapon::Rocket, weapon::SelectedWeapon));
-            }
-            KeyCode::Digit5 => {
-                commands
-                    .entity(*player)
-                    .despawn_children()
-                    .with_child((weapon::Laser, weapon::SelectedWeapon));
-            }
-            _ => {}
+        let Some(weapon) = DIGITS
+            .iter()
+            .position(|digit| digit == input)
+            .and_then(|index| registry.weapons.get(index))
+        else {
+            continue;
+        };
+        // Added to the inventory, which `CycleWeapon` cycles through.
+        let (player, children) = *player;
+        for entity in selected.iter_many(children.into_iter().flatten()) {
+            commands.entity(entity).remove::<PrimaryWeapon>();
         }
+        commands.entity(player).with_children(|children| {
+            let mut entity = children.spawn(PrimaryWeapon);
+            (weapon.insert)(&mut entity);
+        });
+        return;
     }
 }
 
@@ -554,12 +1431,16 @@ pub fn term_layer(app: &mut App) -> Option<BoxedLayer> {
 
 fn term_plugin(app: &mut App) {
     app.add_plugins(TextInputPlugin)
+        .insert_resource(TermHistory::load())
         .add_systems(Startup, spawn_term)
         .add_systems(
             Update,
             (
                 toggle_term.after(TextInputSystem),
                 parse_commands.after(TextInputSystem),
+                (record_history, navigate_history, complete_command)
+                    .chain()
+                    .after(TextInputSystem),
                 auto_scroll_on_new_items,
                 log_tracing,
             ),
@@ -571,30 +1452,51 @@ fn parse_commands(
     mut commands: Commands,
     mut events: MessageReader<TextInputSubmitMessage>,
     mut level: ResMut<Level>,
-    mut selected_weapon: Option<Single<(&mut MaxAmmo, &mut Ammo), With<SelectedWeapon>>>,
+    mut selected_weapon: Option<Single<(&mut MaxAmmo, &mut Ammo), With<PrimaryWeapon>>>,
+    mut settings: ResMut<Settings>,
+    mut music: ResMut<MusicTrack>,
+    level_geometry: Query<Entity, With<LevelGeometry>>,
+    mut physics: ResMut<PhysicsConfig>,
+    spectating: Option<Res<Spectating>>,
+    mut snapping: ResMut<Snapping>,
+    mut polygon: ResMut<PolygonTool>,
+    registry: Res<Registry>,
 ) {
     let error_str = r#"- `l ident`: loads the level with `ident`.
         - `c ident`: copies the current state into a new level with `ident`.
         - `ammo <new_ammo>`
+        - `set <setting> <value>`
+        - `difficulty <easy|normal|hard>`
+        - `transition <fade|wipe|iris>`
+        - `physics [<field> <value>]`
+        - `music <ident|off>`
+        - `bench <bullets|walls> <count>`
+        - `entities`: lists registered level entities and weapons.
+        - `layers`: prints the collision layer matrix.
+        - `spectate`: toggles the spectator camera.
+        - `grid [<size>]`: toggles grid snapping, or sets the grid size.
+        - `align <left|right|top|bottom|center|middle>`: aligns the selection.
+        - `poly`: starts drawing a polygon wall, or finishes it.
         - `{type_name} ...`: spawns entity with components `type_name` under cursor.
         - `relate <src_id> Relationship <dst_id>`
         "#;
 
     for event in events.read() {
-        if let Some(level_ident) = event.value.strip_prefix("l ") {
+        let input = event.value.strip_prefix('/').unwrap_or(&event.value);
+        if let Some(level_ident) = input.strip_prefix("l ") {
             info!("loading {level_ident}");
             level.0 = level_ident.to_string();
             commands.run_system_cached(level::reset_level);
-        } else if let Some(level_ident) = event.value.strip_prefix("c ") {
+        } else if let Some(level_ident) = input.strip_prefix("c ") {
             info!("saving current state to {level_ident}");
             level.0 = level_ident.to_string();
             commands.run_system_cached(level::serialize_level);
             commands.run_system_cached(level::reset_level);
-        } else if event.value.starts_with("relate ") {
-            let mut args = event.value.split_whitespace();
+        } else if input.starts_with("relate ") {
+            let mut args = input.split_whitespace();
             assert_eq!(args.next(), Some("relate"));
 
-            if event.value.split_whitespace().count() != 4 {
+            if input.split_whitespace().count() != 4 {
                 error!("Usage: relate <src_id> Relationship <dst_id>");
                 return;
             }
@@ -638,7 +1540,7 @@ fn parse_commands(
             } else {
                 error!("Usage: relate <src_id> Relationship <dst_id>");
             }
-        } else if let Some(value) = event.value.strip_prefix("ammo ") {
+        } else if let Some(value) = input.strip_prefix("ammo ") {
             if let Some(selected_weapon) = selected_weapon.as_mut() {
                 let Ok(amount) = value.parse::<usize>() else {
                     error!("{value} is not a usize");
@@ -648,8 +1550,133 @@ fn parse_commands(
                 selected_weapon.0.0 = amount;
                 selected_weapon.1.0 = amount;
             }
+        } else if let Some(ident) = input.strip_prefix("music ") {
+            let level_music = if ident == "off" {
+                info!("stopping music");
+                music.0 = None;
+                LevelMusic(String::new())
+            } else {
+                match music::music_tracks() {
+                    Ok(tracks) if !tracks.iter().any(|track| track == ident) => {
+                        error!("no track {ident}, expected one of: {}", tracks.join(", "));
+                        return;
+                    }
+                    Err(err) => {
+                        error!("{err}");
+                        return;
+                    }
+                    Ok(_) => {}
+                }
+                info!("playing {ident}");
+                music.0 = Some(ident.to_string());
+                LevelMusic(ident.to_string())
+            };
+            // Saved with the level.
+            for entity in level_geometry.iter() {
+                commands.entity(entity).insert(level_music.clone());
+            }
+        } else if let Some(value) = input.strip_prefix("bench ") {
+            let Some((kind, count)) = value.split_once(' ') else {
+                error!("Usage: bench <bullets|walls> <count>");
+                return;
+            };
+            let kind = match kind {
+                "bullets" => BenchKind::Bullets,
+                "walls" => BenchKind::Walls,
+                _ => {
+                    error!("Usage: bench <bullets|walls> <count>");
+                    return;
+                }
+            };
+            let Ok(count) = count.parse::<usize>() else {
+                error!("{count} is not a usize");
+                return;
+            };
+            commands.insert_resource(Bench::new(kind, count));
+        } else if let Some(value) = input.strip_prefix("set ") {
+            let Some((setting, value)) = value.split_once(' ') else {
+                error!("Usage: set <setting> <value>");
+                return;
+            };
+            set_field(settings.as_reflect_mut(), setting, value);
+        } else if input == "entities" {
+            info!("level entities: {}", registry.level_entities.join(", "));
+            info!(
+                "weapons: {}",
+                registry
+                    .weapons
+                    .iter()
+                    .map(|weapon| weapon.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        } else if input == "layers" {
+            info!("collision layers:\n{}", collision::matrix());
+        } else if let Some(value) = input.strip_prefix("difficulty ") {
+            match value.parse() {
+                Ok(difficulty) => {
+                    info!("setting difficulty to {difficulty:?}");
+                    settings.difficulty = difficulty;
+                }
+                Err(err) => error!("{err}"),
+            }
+        } else if let Some(value) = input.strip_prefix("transition ") {
+            match value.parse() {
+                Ok(transition) => {
+                    info!("setting transition to {transition:?}");
+                    settings.transition = transition;
+                }
+                Err(err) => error!("{err}"),
+            }
+        } else if input == "spectate" {
+            if spectating.is_some() {
+                info!("leaving spectator mode");
+                commands.remove_resource::<Spectating>();
+            } else {
+                info!("spectating: WASD to fly, scroll to zoom, tab to cycle focus");
+                commands.init_resource::<Spectating>();
+            }
+        } else if input == "grid" {
+            snapping.enabled = !snapping.enabled;
+            info!(
+                "grid snapping {}",
+                if snapping.enabled { "on" } else { "off" }
+            );
+        } else if let Some(value) = input.strip_prefix("grid ") {
+            match value.parse::<f32>() {
+                Ok(size) if size > 0.0 => {
+                    info!("snapping to a {size} grid");
+                    snapping.enabled = true;
+                    snapping.size = size;
+                }
+                _ => error!("{value} is not a positive grid size"),
+            }
+        } else if input == "poly" {
+            match polygon.0.take() {
+                None => {
+                    info!("click the vertices of the polygon, then `poly` again to finish");
+                    polygon.0 = Some(Vec::new());
+                }
+                Some(points) if points.len() < 3 => {
+                    error!("a polygon needs at least 3 vertices, got {}", points.len());
+                }
+                Some(points) => commands.run_system_cached_with(finish_polygon, points),
+            }
+        } else if let Some(value) = input.strip_prefix("align ") {
+            match value.parse::<Align>() {
+                Ok(align) => commands.run_system_cached_with(align_selection, align),
+                Err(err) => error!("{err}"),
+            }
+        } else if input == "physics" {
+            info!("{:?}", *physics);
+        } else if let Some(value) = input.strip_prefix("physics ") {
+            let Some((field, value)) = value.split_once(' ') else {
+                error!("Usage: physics [<field> <value>]");
+                return;
+            };
+            set_field(physics.as_reflect_mut(), field, value);
         } else {
-            let ty_names = event.value.clone();
+            let ty_names = input.to_string();
             commands.queue(move |world: &mut World| {
                 world.resource_scope(move |world: &mut World, registry: Mut<AppTypeRegistry>| {
                     let window = world
@@ -697,6 +1724,195 @@ fn parse_commands(
     }
 }
 
+/// Where the [`TermHistory`] is saved, in the working directory.
+const HISTORY_PATH: &str = "term_history.txt";
+
+/// Most commands kept in the [`TermHistory`].
+const HISTORY_LENGTH: usize = 200;
+
+/// Submitted terminal commands, oldest first.
+#[derive(Resource)]
+struct TermHistory {
+    commands: Vec<String>,
+    /// Index of the command recalled into the input.
+    recalled: Option<usize>,
+}
+
+impl TermHistory {
+    fn load() -> Self {
+        let commands = fs::read_to_string(HISTORY_PATH)
+            .map(|contents| contents.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            commands,
+            recalled: None,
+        }
+    }
+}
+
+fn record_history(
+    mut events: MessageReader<TextInputSubmitMessage>,
+    mut history: ResMut<TermHistory>,
+) {
+    let mut changed = false;
+    for event in events.read() {
+        history.recalled = None;
+        let command = event.value.trim();
+        if command.is_empty() || history.commands.last().is_some_and(|last| last == command) {
+            continue;
+        }
+        history.commands.push(command.to_string());
+        changed = true;
+    }
+    if !changed {
+        return;
+    }
+    let excess = history.commands.len().saturating_sub(HISTORY_LENGTH);
+    history.commands.drain(..excess);
+
+    let contents = history.commands.join("\n");
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(err) = fs::write(HISTORY_PATH, contents) {
+                error!("failed to write {HISTORY_PATH}: {err}");
+            }
+        })
+        .detach();
+}
+
+fn navigate_history(
+    input: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<TermHistory>,
+    text_input: Single<(&mut TextInputValue, &TextInputInactive), With<TermStdIn>>,
+) {
+    let (mut value, inactive) = text_input.into_inner();
+    if inactive.0 {
+        return;
+    }
+
+    if input.pressed(KeyCode::ControlLeft) && input.just_pressed(KeyCode::KeyU) {
+        value.0.clear();
+        history.recalled = None;
+        return;
+    }
+
+    let recalled = if input.just_pressed(KeyCode::ArrowUp) {
+        match history.recalled {
+            Some(index) => Some(index.saturating_sub(1)),
+            None => history.commands.len().checked_sub(1),
+        }
+    } else if input.just_pressed(KeyCode::ArrowDown) {
+        // Past the newest command is an empty line.
+        history
+            .recalled
+            .map(|index| index + 1)
+            .filter(|index| *index < history.commands.len())
+    } else {
+        return;
+    };
+    if recalled.is_none() && history.recalled.is_none() {
+        return;
+    }
+    history.recalled = recalled;
+    value.0 = recalled
+        .map(|index| history.commands[index].clone())
+        .unwrap_or_default();
+}
+
+/// Commands completed by [`complete_command`], besides level entity names.
+const COMMANDS: &[&str] = &[
+    "l",
+    "c",
+    "relate",
+    "ammo",
+    "set",
+    "difficulty",
+    "transition",
+    "physics",
+    "music",
+    "bench",
+    "entities",
+    "layers",
+    "spectate",
+    "grid",
+    "align",
+    "poly",
+];
+
+fn complete_command(
+    input: Res<ButtonInput<KeyCode>>,
+    registry: Res<Registry>,
+    text_input: Single<(&mut TextInputValue, &TextInputInactive), With<TermStdIn>>,
+) {
+    let (mut value, inactive) = text_input.into_inner();
+    if inactive.0 || !input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    let line = value.0.strip_prefix('/').unwrap_or(&value.0);
+    let (command, word) = match line.rsplit_once(' ') {
+        Some((command, word)) => (Some(command.trim()), word),
+        None => (None, line),
+    };
+    let candidates: Vec<String> = match command {
+        None => COMMANDS
+            .iter()
+            .map(|command| command.to_string())
+            .chain(registry.level_entities.iter().cloned())
+            .collect(),
+        Some("l" | "c") => match level::level_idents() {
+            Ok(levels) => levels,
+            Err(err) => {
+                error!("{err}");
+                return;
+            }
+        },
+        Some("music") => match music::music_tracks() {
+            Ok(tracks) => tracks.into_iter().chain(["off".to_string()]).collect(),
+            Err(err) => {
+                error!("{err}");
+                return;
+            }
+        },
+        Some("difficulty") => ["easy", "normal", "hard"].map(str::to_string).into(),
+        Some("transition") => ["fade", "wipe", "iris"].map(str::to_string).into(),
+        Some("bench") => ["bullets", "walls"].map(str::to_string).into(),
+        Some("align") => ["left", "right", "top", "bottom", "center", "middle"]
+            .map(str::to_string)
+            .into(),
+        // Entities may be spawned with several components.
+        Some(command) if !command.contains(' ') && !COMMANDS.contains(&command) => {
+            registry.level_entities.clone()
+        }
+        Some(_) => return,
+    };
+    let candidates: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+
+    let completion = match candidates.as_slice() {
+        [] => return,
+        [candidate] => format!("{candidate} "),
+        [first, rest @ ..] => {
+            info!("{}", candidates.join("  "));
+            let common = rest.iter().fold(first.as_str(), |common, candidate| {
+                let len = common
+                    .chars()
+                    .zip(candidate.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a.len_utf8())
+                    .sum();
+                &common[..len]
+            });
+            common.to_string()
+        }
+    };
+    let prefix_len = value.0.len() - word.len();
+    value.0.truncate(prefix_len);
+    value.0.push_str(&completion);
+}
+
 #[derive(Component)]
 pub struct Term;
 
@@ -704,16 +1920,22 @@ fn toggle_term(
     mut commands: Commands,
     term: Single<(Entity, &mut Node), With<Term>>,
     text_input: Single<(&mut TextInputValue, &mut TextInputInactive), With<TermStdIn>>,
-    input: Res<ButtonInput<KeyCode>>,
+    mut input: ResMut<ButtonInput<KeyCode>>,
 ) {
     let (mut text_value, mut input_inactive) = text_input.into_inner();
-    let slash = input.just_pressed(KeyCode::Slash);
-    if !input.just_pressed(KeyCode::Escape) && (!slash || !input_inactive.0) {
+    let (entity, mut term) = term.into_inner();
+    let toggle = if term.display == Display::Flex {
+        KeyCode::Escape
+    } else {
+        KeyCode::Slash
+    };
+    if !input.just_pressed(toggle) {
         return;
     }
-    let (entity, mut term) = term.into_inner();
     term.display = match term.display {
         Display::Flex => {
+            // Otherwise the game would be paused by the same press.
+            input.clear_just_pressed(KeyCode::Escape);
             commands.entity(entity).remove::<DisableInput>();
             input_inactive.0 = true;
             text_value.0.clear();
@@ -851,9 +2073,41 @@ impl tracing::field::Visit for CaptureLayerVisitor<'_> {
 
 // DEBUG INFORMATION
 
+/// Parses `value` into the field at `path`, logging the outcome.
+fn set_field(target: &mut dyn Reflect, path: &str, value: &str) {
+    fn parse<T: Reflect + std::str::FromStr + std::fmt::Display>(
+        target: &mut dyn Reflect,
+        path: &str,
+        value: &str,
+    ) -> bool {
+        let (Ok(field), Ok(value)) = (target.path_mut::<T>(path), value.parse::<T>()) else {
+            return false;
+        };
+        info!("setting {path} to {value}");
+        *field = value;
+        true
+    }
+
+    if !(parse::<bool>(target, path, value)
+        || parse::<f32>(target, path, value)
+        || parse::<f64>(target, path, value)
+        || parse::<u32>(target, path, value)
+        || parse::<usize>(target, path, value))
+    {
+        error!("can not set {path} to {value}");
+    }
+}
+
 fn debug_information_plugin(app: &mut App) {
     app.add_systems(Startup, spawn_debug_information)
-        .add_systems(Update, (level_ident, weapon_ammo, weapons));
+        .add_systems(
+            Update,
+            (
+                level_ident.run_if(resource_changed::<Level>),
+                weapon_ammo,
+                weapons,
+            ),
+        );
 }
 
 fn spawn_debug_information(mut commands: Commands) {
@@ -889,9 +2143,8 @@ fn spawn_debug_information(mut commands: Commands) {
 struct LevelIdent;
 
 fn level_ident(mut ident: Single<&mut Text, With<LevelIdent>>, level: Res<Level>) {
-    if level.is_changed() {
-        ident.0 = format!("Level: {}", level.0);
-    }
+    ident.0.clear();
+    _ = write!(ident.0, "Level: {}", level.0);
 }
 
 #[derive(Component)]
@@ -902,8 +2155,8 @@ fn weapon_ammo(
     selected_weapon: Single<
         (&MaxAmmo, &Ammo),
         (
-            With<SelectedWeapon>,
-            Or<(Changed<MaxAmmo>, Changed<Ammo>, Added<SelectedWeapon>)>,
+            With<PrimaryWeapon>,
+            Or<(Changed<MaxAmmo>, Changed<Ammo>, Added<PrimaryWeapon>)>,
         ),
     >,
 ) {
@@ -912,7 +2165,8 @@ fn weapon_ammo(
     if current.0 != max_ammo.0 || current.1 != ammo.0 {
         current.0 = max_ammo.0;
         current.1 = ammo.0;
-        text.0 = format!("Ammo: {}/{}", current.1, current.0);
+        text.0.clear();
+        _ = write!(text.0, "Ammo: {}/{}", current.1, current.0);
     }
 }
 
@@ -921,15 +2175,15 @@ struct Weapons;
 
 fn weapons(
     mut weapons: Single<&mut Text, With<Weapons>>,
-    player: Single<&Children, (Changed<Children>, With<Player>)>,
+    player: Single<&Children, (Changed<Children>, With<Player>, Without<Partner>)>,
     player_weapons: Query<&Name, With<Weapon>>,
 ) {
-    weapons.0 = format!(
-        "Weapons: {}",
-        player_weapons
-            .iter_many(*player)
-            .map(|weapon| weapon.as_str())
-            .collect::<Vec<&str>>()
-            .join(", ")
-    );
+    weapons.0.clear();
+    weapons.0.push_str("Weapons: ");
+    for (i, weapon) in player_weapons.iter_many(*player).enumerate() {
+        if i > 0 {
+            weapons.0.push_str(", ");
+        }
+        weapons.0.push_str(weapon.as_str());
+    }
 }
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy_tween::{component_tween_system, prelude::*, tween::AnimationTarget};
+
+pub fn plugin(app: &mut App) {
+    app.add_tween_systems(component_tween_system::<SquashStretch>())
+        .add_systems(PostUpdate, apply_sprite_scale)
+        .add_observer(juice);
+}
+
+/// Briefly scales an entity's [`Sprite`], then eases it back to its natural size.
+///
+/// Procedural sprite animation goes through [`SpriteScale`] instead of writing
+/// the [`Sprite`] size directly, so it composes with other sprite animation.
+#[derive(EntityEvent)]
+pub struct Juice {
+    pub entity: Entity,
+    pub scale: Vec2,
+    pub duration: f32,
+}
+
+impl Juice {
+    /// Tall and thin.
+    pub fn stretch(entity: Entity, amount: f32) -> Self {
+        Self {
+            entity,
+            scale: Vec2::new(1.0 - amount * 0.5, 1.0 + amount),
+            duration: 0.25,
+        }
+    }
+
+    /// Short and wide.
+    pub fn squash(entity: Entity, amount: f32) -> Self {
+        Self {
+            entity,
+            scale: Vec2::new(1.0 + amount, 1.0 - amount * 0.5),
+            duration: 0.2,
+        }
+    }
+
+    /// A quick uniform punch.
+    pub fn kick(entity: Entity, amount: f32) -> Self {
+        Self {
+            entity,
+            scale: Vec2::splat(1.0 + amount),
+            duration: 0.12,
+        }
+    }
+}
+
+/// Multiplier on the natural size of the entity's [`Sprite`].
+#[derive(Component)]
+pub struct SpriteScale {
+    pub scale: Vec2,
+    natural_size: Option<Vec2>,
+}
+
+impl Default for SpriteScale {
+    fn default() -> Self {
+        Self {
+            scale: Vec2::ONE,
+            natural_size: None,
+        }
+    }
+}
+
+fn juice(juice: On<Juice>, mut commands: Commands) {
+    let target = AnimationTarget.into_target();
+    commands
+        .entity(juice.entity)
+        .insert(AnimationTarget)
+        .insert_if_new(SpriteScale::default())
+        .animation()
+        .insert_tween_here(
+            Duration::from_secs_f32(juice.duration),
+            EaseKind::BackOut,
+            target.with(squash_stretch(juice.scale, Vec2::ONE)),
+        );
+}
+
+fn apply_sprite_scale(mut sprites: Query<(&mut SpriteScale, &mut Sprite), Changed<SpriteScale>>) {
+    for (mut scale, mut sprite) in sprites.iter_mut() {
+        let Some(size) = scale
+            .natural_size
+            .or_else(|| sprite.custom_size)
+            .or_else(|| sprite.rect.map(|rect| rect.size()))
+        else {
+            continue;
+        };
+        scale.bypass_change_detection().natural_size = Some(size);
+        sprite.custom_size = Some(size * scale.scale);
+    }
+}
+
+#[derive(Component)]
+struct SquashStretch {
+    start: Vec2,
+    end: Vec2,
+}
+
+fn squash_stretch(start: Vec2, end: Vec2) -> SquashStretch {
+    SquashStretch { start, end }
+}
+
+impl Interpolator for SquashStretch {
+    type Item = SpriteScale;
+    fn interpolate(
+        &self,
+        item: &mut Self::Item,
+        value: interpolate::CurrentValue,
+        _: interpolate::PreviousValue,
+    ) {
+        item.scale = self.start.lerp(self.end, value);
+    }
+}
@@ -0,0 +1,212 @@
+//! Keyboard, mouse and gamepad bindings of the first player's actions.
+//!
+//! Loaded from and saved to `bindings.ron` in the working directory, and
+//! rebound from the controls menu, see [`crate::menu`]. A [`Partner`] always
+//! uses the default gamepad layout.
+
+use crate::{coop::Partner, player::Player};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(Keybinds::load()).add_systems(
+        Last,
+        (rebind_players, save_keybinds).run_if(resource_changed::<Keybinds>),
+    );
+}
+
+const PATH: &str = "bindings.ron";
+
+/// A button that can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl InputButton {
+    pub fn is_gamepad(self) -> bool {
+        matches!(self, Self::Gamepad(_))
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::Key(key) => format!("{key:?}"),
+            Self::Mouse(button) => format!("Mouse {button:?}"),
+            Self::Gamepad(button) => format!("Pad {button:?}"),
+        }
+    }
+}
+
+impl From<InputButton> for Binding {
+    fn from(button: InputButton) -> Self {
+        match button {
+            InputButton::Key(key) => key.into(),
+            InputButton::Mouse(button) => button.into(),
+            InputButton::Gamepad(button) => button.into(),
+        }
+    }
+}
+
+/// Keys of a direction input, next to the gamepad stick.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Directions {
+    pub up: KeyCode,
+    pub left: KeyCode,
+    pub down: KeyCode,
+    pub right: KeyCode,
+}
+
+impl Directions {
+    fn cardinal(self) -> Cardinal<Binding, Binding, Binding, Binding> {
+        Cardinal {
+            north: self.up.into(),
+            east: self.right.into(),
+            south: self.down.into(),
+            west: self.left.into(),
+        }
+    }
+}
+
+/// Bindings of the actions that can be rebound.
+///
+/// The mouse wheel and gamepad triggers of
+/// [`CycleWeapon`](crate::player::CycleWeapon) are fixed.
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybinds {
+    pub move_keys: Directions,
+    pub aim_keys: Directions,
+    pub jump: Vec<InputButton>,
+    pub attack: Vec<InputButton>,
+    pub secondary_attack: Vec<InputButton>,
+    pub pick_up: Vec<InputButton>,
+    pub deflect: Vec<InputButton>,
+    pub dash: Vec<InputButton>,
+    pub slow_motion: Vec<InputButton>,
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        use InputButton::*;
+        Self {
+            move_keys: Directions {
+                up: KeyCode::KeyW,
+                left: KeyCode::KeyA,
+                down: KeyCode::KeyS,
+                right: KeyCode::KeyD,
+            },
+            aim_keys: Directions {
+                up: KeyCode::ArrowUp,
+                left: KeyCode::ArrowLeft,
+                down: KeyCode::ArrowDown,
+                right: KeyCode::ArrowRight,
+            },
+            jump: vec![
+                Key(KeyCode::Space),
+                Key(KeyCode::ShiftLeft),
+                Gamepad(GamepadButton::South),
+            ],
+            attack: vec![
+                Mouse(MouseButton::Left),
+                Gamepad(GamepadButton::RightTrigger2),
+            ],
+            secondary_attack: vec![
+                Mouse(MouseButton::Right),
+                Gamepad(GamepadButton::LeftTrigger2),
+            ],
+            pick_up: vec![
+                Key(KeyCode::KeyF),
+                Key(KeyCode::Enter),
+                Gamepad(GamepadButton::North),
+            ],
+            deflect: vec![Key(KeyCode::KeyE), Gamepad(GamepadButton::West)],
+            dash: vec![Key(KeyCode::KeyQ), Gamepad(GamepadButton::East)],
+            slow_motion: vec![Key(KeyCode::KeyC), Gamepad(GamepadButton::LeftThumb)],
+        }
+    }
+}
+
+impl Keybinds {
+    fn load() -> Self {
+        match fs::read_to_string(PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("failed to parse {PATH}: {err}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Spawnable bindings of a button action.
+    pub fn buttons(buttons: &[InputButton]) -> SpawnIter<std::vec::IntoIter<Binding>> {
+        let bindings = buttons
+            .iter()
+            .copied()
+            .map(Binding::from)
+            .collect::<Vec<_>>();
+        SpawnIter(bindings.into_iter())
+    }
+
+    pub fn move_bindings(&self) -> impl Bundle {
+        Bindings::spawn((self.move_keys.cardinal(), Axial::left_stick()))
+    }
+
+    pub fn aim_bindings(&self) -> impl Bundle {
+        Bindings::spawn((self.aim_keys.cardinal(), Axial::right_stick()))
+    }
+
+    /// Replaces the binding of the same kind, keyboard and mouse or gamepad,
+    /// as `button`, or adds it if there is none.
+    pub fn rebind(bindings: &mut Vec<InputButton>, button: InputButton) {
+        bindings.retain(|bound| *bound != button);
+        match bindings
+            .iter_mut()
+            .find(|bound| bound.is_gamepad() == button.is_gamepad())
+        {
+            Some(bound) => *bound = button,
+            None => bindings.push(button),
+        }
+    }
+}
+
+/// Rebuilds the actions of the players with the new bindings.
+fn rebind_players(
+    mut commands: Commands,
+    keybinds: Res<Keybinds>,
+    players: Query<Entity, (With<Player>, Without<Partner>)>,
+) {
+    if keybinds.is_added() {
+        return;
+    }
+    for player in players.iter() {
+        // Inserting the player again runs `inject_bindings`.
+        commands
+            .entity(player)
+            .despawn_related::<Actions<Player>>()
+            .insert(Player);
+    }
+}
+
+fn save_keybinds(keybinds: Res<Keybinds>) {
+    if keybinds.is_added() {
+        return;
+    }
+    let serialized = match ron::ser::to_string_pretty(&*keybinds, default()) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            error!("failed to serialize bindings: {err}");
+            return;
+        }
+    };
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(err) = fs::write(PATH, serialized) {
+                error!("failed to write {PATH}: {err}");
+            }
+        })
+        .detach();
+}
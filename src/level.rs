@@ -1,48 +1,161 @@
 #[cfg(feature = "debug")]
 use crate::inspector;
-use crate::{player::Player, weapon::Bullet};
+use crate::{
+    assets,
+    audio::{PlaySfx, Sfx},
+    bullet_time::reset_bullet_time,
+    collision::Layer,
+    coop::Partner,
+    damage::{Died, Health},
+    music::LevelMusic,
+    player::{ExternalVelocity, Grounded, Hurt, Killed, PickUp, Player},
+    registry::{Registry, register_level_entity, register_serialized},
+    settings::Settings,
+    transition::{Transition, start_transition},
+    weapon::{Bullet, BulletImpact, ImpactSet, despawn_bullet},
+};
 use avian2d::{
     dynamics::solver::islands::BodyIslandNode,
     prelude::{
-        Collider, ColliderConstructor, CollisionEventsEnabled, CollisionLayers, CollisionStart,
-        Gravity, LayerMask, LinearVelocity, PhysicsLayer, PhysicsSystems, RigidBody, Sensor,
-        WakeBody,
+        Collider, ColliderConstructor, ColliderDisabled, CollidingEntities, CollisionEnd,
+        CollisionEventsEnabled, CollisionLayers, CollisionStart, Gravity, GravityScale,
+        LinearDamping, LinearVelocity, Physics, PhysicsTime, Position, RigidBody,
+        RigidBodyDisabled, Sensor, ShapeCaster, ShapeHits, TransformInterpolation, WakeBody,
     },
 };
 use bevy::{
-    color::palettes::css::{BLUE, GREEN, RED, YELLOW},
+    asset::RenderAssetUsages,
+    color::palettes::css::{
+        AQUA, BLUE, CRIMSON, GOLD, GREEN, ORANGE, RED, SILVER, TEAL, TOMATO, VIOLET, WHITE, YELLOW,
+    },
     ecs::{lifecycle::HookContext, world::DeferredWorld},
+    mesh::{Indices, PrimitiveTopology},
     prelude::*,
-    scene::SceneInstance,
-    tasks::IoTaskPool,
+    reflect::TypeRegistryArc,
+    scene::{SceneInstance, SceneSpawner},
+    tasks::{IoTaskPool, Task, block_on, futures_lite::future},
+};
+use bevy_enhanced_input::prelude::Fire;
+use serde::de::DeserializeSeed;
+use std::{
+    fs::{self, File},
+    io::Write,
 };
-use std::{fs::File, io::Write};
 
 pub fn plugin(app: &mut App) {
     app.init_resource::<Level>()
+        .init_resource::<LevelTime>()
+        .init_resource::<ActiveCheckpoint>()
+        .init_resource::<LevelLoading>()
+        .init_resource::<LevelBoundary>()
         .add_systems(Startup, deserialize_level)
         .add_systems(
             Update,
             (
-                add_pickable_sprites,
+                spawn_pending_level.run_if(resource_exists::<PendingLevel>),
                 remove_dynamic_scene_root,
                 #[cfg(feature = "debug")]
                 user_serialize_level,
-                (user_reset_level, wake_bodies_after_gravity_change).chain(),
-                needs_serialized_collider,
+                #[cfg(feature = "debug")]
+                hot_reload_level.run_if(resource_exists::<LevelScene>),
+                user_reset_level,
+                (
+                    needs_serialized_collider,
+                    build_colliders,
+                    pause_physics_while_loading,
+                )
+                    .chain(),
             ),
         )
         .add_systems(
-            FixedPostUpdate,
-            (killbox_clock, killbox_gravity_switch).before(PhysicsSystems::First),
+            FixedUpdate,
+            (
+                level_time,
+                place_at_spawn_point,
+                record_level_start,
+                clear_checkpoint,
+                killbox_clock,
+                killbox_gravity_switch,
+                wake_bodies_after_gravity_change,
+                move_platforms,
+                carry_players,
+                swing,
+                ride_ziplines,
+                blow_wind,
+                press_plates,
+                close_timed_doors,
+                fire_lasers,
+                level_boundary,
+                out_of_bounds,
+            )
+                .chain()
+                .in_set(LevelSet),
         )
+        .add_systems(FixedUpdate, shoot_target.in_set(ImpactSet::Keys))
+        .add_observer(add_pickable_sprite)
         .add_observer(killbox)
+        .add_observer(reach_checkpoint)
         .add_observer(door)
+        .add_observer(leave_entrance)
         .add_observer(must_keep)
-        .add_observer(destroy_key)
-        .add_observer(destroy_geometry_from_keys);
+        .add_observer(key_died)
+        .add_observer(destroy_geometry_from_keys)
+        .add_observer(grab)
+        .add_observer(enter_physics_volume)
+        .add_observer(exit_physics_volume)
+        .add_observer(let_go_when_killed);
+    #[cfg(feature = "binary_levels")]
+    app.init_asset_loader::<BinarySceneLoader>();
+
+    register_serialized::<Serialize>(app);
+    register_serialized::<Name>(app);
+    register_serialized::<Transform>(app);
+    register_serialized::<GlobalTransform>(app);
+    register_serialized::<Visibility>(app);
+    register_serialized::<Children>(app);
+    register_serialized::<ChildOf>(app);
+    register_serialized::<LevelGeometry>(app);
+    register_serialized::<LevelBounds>(app);
+    register_serialized::<OutOfBounds>(app);
+    register_serialized::<LevelMusic>(app);
+    register_serialized::<Keys>(app);
+    register_serialized::<KeyOf>(app);
+    register_serialized::<Plates>(app);
+    register_serialized::<PlateOf>(app);
+    register_serialized::<KillboxClock>(app);
+    register_serialized::<KillboxGravitySwitch>(app);
+    register_serialized::<KillboxDamage>(app);
+    register_serialized::<Sensor>(app);
+    register_serialized::<CollisionEventsEnabled>(app);
+    register_serialized::<RigidBody>(app);
+    register_serialized::<SerializedColliderConstructor>(app);
+    register_level_entity::<Wall>(app);
+    register_level_entity::<KillBox>(app);
+    register_level_entity::<Door>(app);
+    register_level_entity::<Key>(app);
+    register_level_entity::<MovingPlatform>(app);
+    register_level_entity::<Checkpoint>(app);
+    register_level_entity::<SpawnPoint>(app);
+    register_serialized::<EntranceId>(app);
+    register_level_entity::<GrapplePoint>(app);
+    register_level_entity::<Zipline>(app);
+    register_level_entity::<PhysicsVolume>(app);
+    register_level_entity::<WindZone>(app);
+    register_level_entity::<TimedDoor>(app);
+    register_level_entity::<PressurePlate>(app);
+    register_level_entity::<ShotTarget>(app);
+    register_level_entity::<LaserEmitter>(app);
+    register_serialized::<MustDestroy>(app);
+    register_serialized::<MustKeep>(app);
 }
 
+/// Level timers and triggers, after [`EnemySet`](crate::enemy::EnemySet) and
+/// before physics.
+///
+/// See [`crate::game_plugin`] for the full ordering.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct LevelSet;
+
 // TODO: submit Avian issue
 fn wake_bodies_after_gravity_change(
     mut commands: Commands,
@@ -56,89 +169,1037 @@ fn wake_bodies_after_gravity_change(
     }
 }
 
-#[derive(Default, PhysicsLayer, Component)]
-pub enum Layer {
-    #[default]
-    Default,
-    Player,
-    Bullet,
-    Wall,
-    KillBox,
-    Key,
-    Pickups,
-}
-
 /// Marks a level entity for level serialization.
-///
-/// [`Serialize`] and [`Transient`] entities are cleared in [`reset_level`].
 #[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(DespawnOnLevelUnload)]
 #[reflect(Component)]
 pub struct Serialize;
 
-/// Marks a level entity as non-serializable.
+/// Scopes an entity to the current level.
 ///
-/// [`Serialize`] and [`Transient`] entities are cleared in [`reset_level`].
+/// Required by [`Serialize`], and by anything spawned during play that must
+/// not leak into the next level, such as bullets. Cleared in [`despawn_level`].
 #[derive(Default, Component)]
-pub struct Transient;
+pub struct DespawnOnLevelUnload;
 
 #[derive(Resource)]
 pub struct Level(pub String);
 
+/// Idents of the levels in `assets/scenes`, sorted.
+pub fn level_idents() -> Result<Vec<String>, String> {
+    let dir = assets::path("scenes");
+    let mut levels = fs::read_dir(&dir)
+        .map_err(|err| format!("{}: {err}", dir.display()))?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            name.strip_suffix(".scn.ron")
+                .or_else(|| name.strip_suffix(".scn.bin"))
+                .map(str::to_string)
+        })
+        .collect::<Vec<_>>();
+    // Directory order is platform specific.
+    levels.sort();
+    // Levels saved in both formats.
+    levels.dedup();
+    Ok(levels)
+}
+
+/// Extension that [`serialize_level`] writes.
+///
+/// The `binary_levels` feature writes postcard, which is smaller and faster to
+/// parse than RON but can not be edited by hand.
+pub const LEVEL_EXTENSION: &str = if cfg!(feature = "binary_levels") {
+    "scn.bin"
+} else {
+    "scn.ron"
+};
+
+/// Path of the level `ident` in the asset folder.
+///
+/// A binary level takes precedence over RON when both exist and the
+/// `binary_levels` feature can load it.
+pub fn level_path(ident: &str) -> String {
+    let binary = format!("scenes/{ident}.scn.bin");
+    if cfg!(feature = "binary_levels") && fs::exists(assets::path(&binary)).unwrap_or(false) {
+        binary
+    } else {
+        format!("scenes/{ident}.scn.ron")
+    }
+}
+
 impl Default for Level {
     fn default() -> Self {
         Self("gravity_5".to_string())
     }
 }
 
-#[derive(Component, Reflect)]
-#[reflect(Component)]
-pub struct LevelGeometry;
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LevelGeometry;
+
+/// Area the camera may show, on the [`LevelGeometry`] entity.
+///
+/// Levels without it are bounded by their [`Wall`]s, see
+/// [`CameraBounds`](crate::camera::CameraBounds).
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct LevelBounds(pub Rect);
+
+/// Area outside of which players are killed as if they entered a [`KillBox`],
+/// and other dynamic bodies are despawned, on the [`LevelGeometry`] entity.
+///
+/// Levels without it are bounded by their [`LevelBounds`] or else their
+/// [`Wall`]s, grown by [`OUT_OF_BOUNDS_MARGIN`], so that a player who clips
+/// out of a level does not fall forever.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct OutOfBounds(pub Rect);
+
+/// Distance past a level's bounds that is still in bounds, when it has no
+/// [`OutOfBounds`].
+pub const OUT_OF_BOUNDS_MARGIN: f32 = 500.0;
+
+/// The [`OutOfBounds`] area of the current level.
+///
+/// [`Rect::EMPTY`] while a level loads, when nothing is out of bounds.
+#[derive(Resource)]
+pub struct LevelBoundary(pub Rect);
+
+impl Default for LevelBoundary {
+    fn default() -> Self {
+        Self(Rect::EMPTY)
+    }
+}
+
+fn level_boundary(
+    loading: Res<LevelLoading>,
+    mut boundary: ResMut<LevelBoundary>,
+    level: Query<(Option<&OutOfBounds>, Option<&LevelBounds>), With<LevelGeometry>>,
+    walls: Query<(&Collider, &GlobalTransform), With<Wall>>,
+) {
+    if loading.0 {
+        boundary.0 = Rect::EMPTY;
+        return;
+    }
+    if !boundary.0.is_empty() {
+        return;
+    }
+    let Ok((out_of_bounds, bounds)) = level.single() else {
+        return;
+    };
+    boundary.0 = match (out_of_bounds, bounds) {
+        (Some(out_of_bounds), _) => out_of_bounds.0,
+        (None, Some(bounds)) => bounds.0.inflate(OUT_OF_BOUNDS_MARGIN),
+        (None, None) => walls
+            .iter()
+            .fold(Rect::EMPTY, |rect, (collider, transform)| {
+                let aabb = collider.aabb(transform.translation().xy(), transform.rotation());
+                rect.union(Rect::from_corners(aabb.min, aabb.max))
+            })
+            .inflate(OUT_OF_BOUNDS_MARGIN),
+    };
+}
+
+fn out_of_bounds(
+    mut commands: Commands,
+    boundary: Res<LevelBoundary>,
+    players: Query<(Entity, &Position), (With<Player>, Without<RigidBodyDisabled>)>,
+    bodies: Query<(Entity, &RigidBody, &Position, Has<Bullet>), Without<Player>>,
+) {
+    if boundary.0.is_empty() {
+        return;
+    }
+    for (entity, position) in players.iter() {
+        if !boundary.0.contains(position.0) {
+            commands.run_system_cached_with(kill_player, entity);
+        }
+    }
+    for (entity, body, position, bullet) in bodies.iter() {
+        if *body != RigidBody::Dynamic || boundary.0.contains(position.0) {
+            continue;
+        }
+        if bullet {
+            commands.entity(entity).queue_silenced(despawn_bullet);
+        } else {
+            commands.entity(entity).try_despawn();
+        }
+    }
+}
+
+/// Triggered when the player leaves a level through a [`Door`].
+#[derive(Event)]
+pub struct LevelComplete {
+    pub level: String,
+    /// [`LevelTime`] at completion.
+    pub time: f64,
+}
+
+/// Fixed time elapsed since the current level was spawned.
+///
+/// Level timers use this instead of [`Time::elapsed`] so that they do not
+/// depend on how long the level took to load.
+#[derive(Default, Clone, Copy, Resource)]
+pub struct LevelTime(pub f64);
+
+fn level_time(
+    time: Res<Time>,
+    mut level_time: ResMut<LevelTime>,
+    loading: Res<LevelLoading>,
+    new_level: Query<(), Added<LevelGeometry>>,
+) {
+    if new_level.is_empty() && !loading.0 {
+        level_time.0 += time.delta_secs_f64();
+    } else {
+        level_time.0 = 0.0;
+    }
+}
+
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    RigidBody::Static,
+    CollisionLayers = Layer::Wall.collision_layers(),
+    DebugPickingColor::new(BLUE),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct Wall;
+
+/// A [`Wall`] that moves between world space waypoints in order, looping back
+/// to the first, and carries the players standing on it.
+#[derive(Clone, Component, Reflect)]
+#[require(
+    Wall,
+    RigidBody::Kinematic,
+    TransformInterpolation,
+    DebugPickingColor::new(AQUA)
+)]
+#[reflect(Default, Component)]
+pub struct MovingPlatform {
+    pub points: Vec<Vec2>,
+    pub speed: f32,
+    /// Index of the waypoint being moved to.
+    pub next: usize,
+}
+
+impl Default for MovingPlatform {
+    fn default() -> Self {
+        Self {
+            points: Vec::new(),
+            speed: 100.0,
+            next: 0,
+        }
+    }
+}
+
+fn move_platforms(
+    time: Res<Time>,
+    mut platforms: Query<(&mut MovingPlatform, &Position, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    for (mut platform, position, mut velocity) in platforms.iter_mut() {
+        if platform.points.is_empty() || dt == 0.0 {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+        platform.next %= platform.points.len();
+        let offset = platform.points[platform.next] - position.0;
+        let step = platform.speed * dt;
+        if offset.length() <= step {
+            // Lands exactly on the waypoint this step.
+            velocity.0 = offset / dt;
+            platform.next = (platform.next + 1) % platform.points.len();
+        } else {
+            velocity.0 = offset.normalize() * platform.speed;
+        }
+    }
+}
+
+/// Moves players [`Grounded`] on a [`MovingPlatform`] along with it, after
+/// their own movement is applied.
+fn carry_players(
+    gravity: Res<Gravity>,
+    platforms: Query<&LinearVelocity, (With<MovingPlatform>, Without<Player>)>,
+    mut players: Query<(&ShapeHits, &mut LinearVelocity), (With<Player>, With<Grounded>)>,
+) {
+    let up = -gravity.0.y.signum();
+    for (hits, mut velocity) in players.iter_mut() {
+        let Some(platform) = hits.iter().find_map(|hit| platforms.get(hit.entity).ok()) else {
+            continue;
+        };
+        velocity.x += platform.x;
+        // Follow a platform moving down, unless already falling faster or
+        // jumping off of it.
+        let rising = velocity.y * up;
+        let platform_rising = platform.y * up;
+        if platform_rising < 0.0 && rising <= 0.0 && rising > platform_rising {
+            velocity.y = platform.y;
+        }
+    }
+}
+
+/// A point that a player within `range` swings from, grabbed and let go of
+/// with [`PickUp`].
+///
+/// The rope keeps the length it had when grabbed and only pulls, so the player
+/// swings under gravity and may go slack.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollisionLayers = Layer::Trigger.collision_layers(),
+    DebugPickingColor::new(VIOLET),
+    NeedsSerializedCollider(Vec2::splat(20.0))
+)]
+#[reflect(Default, Component)]
+pub struct GrapplePoint {
+    pub range: f32,
+}
+
+impl Default for GrapplePoint {
+    fn default() -> Self {
+        Self { range: 250.0 }
+    }
+}
+
+/// A line that carries a player grabbing it with [`PickUp`] toward its lower
+/// end at `speed`, letting go past the end.
+///
+/// Its ends are those of its rectangle collider along the local x axis, so it
+/// is placed, stretched and rotated like a [`Wall`].
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollisionLayers = Layer::Trigger.collision_layers(),
+    DebugPickingColor::new(SILVER),
+    NeedsSerializedCollider(Vec2::new(300.0, 4.0))
+)]
+#[reflect(Default, Component)]
+pub struct Zipline {
+    pub speed: f32,
+}
+
+impl Default for Zipline {
+    fn default() -> Self {
+        Self { speed: 400.0 }
+    }
+}
+
+impl Zipline {
+    /// World space ends of the line.
+    fn ends(transform: &GlobalTransform, collider: &Collider) -> Option<(Vec2, Vec2)> {
+        let half = collider.shape().as_cuboid()?.half_extents.x;
+        Some((
+            transform.transform_point(Vec3::NEG_X * half).xy(),
+            transform.transform_point(Vec3::X * half).xy(),
+        ))
+    }
+}
+
+/// Swinging from a [`GrapplePoint`] on a rope of `length`.
+#[derive(Clone, Copy, Component)]
+pub struct Grappling {
+    pub point: Entity,
+    pub length: f32,
+}
+
+/// Riding a [`Zipline`], toward its second end if `forward`.
+#[derive(Clone, Copy, Component)]
+pub struct Ziplining {
+    pub zipline: Entity,
+    pub forward: bool,
+}
+
+/// Distance from a [`Zipline`] within which a player can grab it.
+const ZIPLINE_REACH: f32 = 40.0;
+
+/// Distance under a [`Zipline`] that a riding player hangs.
+const ZIPLINE_HANG: f32 = 24.0;
+
+/// Fraction of the distance off a rope or line corrected per step.
+const ROPE_STIFFNESS: f32 = 0.5;
+
+/// Lets go of a grapple or zipline, or else grabs the closest [`Zipline`] in
+/// reach or [`GrapplePoint`] in range.
+fn grab(
+    pick_up: On<Fire<PickUp>>,
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    players: Query<
+        (
+            &GlobalTransform,
+            &LinearVelocity,
+            Has<Grappling>,
+            Has<Ziplining>,
+        ),
+        With<Player>,
+    >,
+    points: Query<(Entity, &GlobalTransform, &GrapplePoint)>,
+    ziplines: Query<(Entity, &GlobalTransform, &Collider), With<Zipline>>,
+) {
+    let Ok((transform, velocity, grappling, ziplining)) = players.get(pick_up.context) else {
+        return;
+    };
+    if grappling || ziplining {
+        commands
+            .entity(pick_up.context)
+            .remove::<(Grappling, Ziplining)>();
+        return;
+    }
+    let position = transform.translation().xy();
+
+    let zipline = ziplines
+        .iter()
+        .filter_map(|(entity, transform, collider)| {
+            let (start, end) = Zipline::ends(transform, collider)?;
+            let closest = start + (position - start).project_onto(end - start);
+            let distance = closest.distance(position);
+            (distance < ZIPLINE_REACH
+                && Rect::from_corners(start, end)
+                    .inflate(1.0)
+                    .contains(closest))
+            .then_some((entity, start, end, distance))
+        })
+        .min_by(|a, b| a.3.total_cmp(&b.3));
+    if let Some((zipline, start, end, _)) = zipline {
+        let drop = (end - start).dot(gravity.0.normalize_or(Vec2::NEG_Y));
+        // Downhill, or the way the player is moving along a level line.
+        let forward = if drop.abs() > 1.0 {
+            drop > 0.0
+        } else {
+            velocity.dot(end - start) >= 0.0
+        };
+        commands
+            .entity(pick_up.context)
+            .insert(Ziplining { zipline, forward });
+        return;
+    }
+
+    if let Some((point, length)) = points
+        .iter()
+        .map(|(entity, transform, point)| {
+            let distance = transform.translation().xy().distance(position);
+            (entity, distance, point.range)
+        })
+        .filter(|(_, distance, range)| distance <= range)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, distance, _)| (entity, distance))
+    {
+        commands
+            .entity(pick_up.context)
+            .insert(Grappling { point, length });
+    }
+}
+
+fn let_go_when_killed(killed: On<Killed>, mut commands: Commands) {
+    commands
+        .entity(killed.entity)
+        .try_remove::<(Grappling, Ziplining)>();
+}
+
+/// Keeps [`Grappling`] players within their rope's length of the point.
+fn swing(
+    mut commands: Commands,
+    time: Res<Time>,
+    points: Query<&GlobalTransform, With<GrapplePoint>>,
+    mut players: Query<(Entity, &Grappling, &Position, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, grappling, position, mut velocity) in players.iter_mut() {
+        let Ok(point) = points.get(grappling.point) else {
+            commands.entity(entity).remove::<Grappling>();
+            continue;
+        };
+        let offset = position.0 - point.translation().xy();
+        let distance = offset.length();
+        if distance <= grappling.length || dt == 0.0 {
+            continue;
+        }
+        let outward = offset / distance;
+        // Only the outward part, so the swing keeps its speed around the point.
+        let speed = velocity.dot(outward);
+        if speed > 0.0 {
+            velocity.0 -= outward * speed;
+        }
+        velocity.0 -= outward * (distance - grappling.length) / dt * ROPE_STIFFNESS;
+    }
+}
+
+/// Carries [`Ziplining`] players along their line, hanging under it.
+fn ride_ziplines(
+    mut commands: Commands,
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    ziplines: Query<(&GlobalTransform, &Collider, &Zipline)>,
+    mut players: Query<(Entity, &Ziplining, &Position, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    if dt == 0.0 {
+        return;
+    }
+    let up = -gravity.0.normalize_or(Vec2::NEG_Y);
+    for (entity, ziplining, position, mut velocity) in players.iter_mut() {
+        let Some((zipline, (start, end))) =
+            ziplines
+                .get(ziplining.zipline)
+                .ok()
+                .and_then(|(transform, collider, zipline)| {
+                    Some((zipline, Zipline::ends(transform, collider)?))
+                })
+        else {
+            commands.entity(entity).remove::<Ziplining>();
+            continue;
+        };
+        let (from, to) = if ziplining.forward {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let along = (to - from).normalize_or_zero();
+        let handle = position.0 + up * ZIPLINE_HANG;
+        if (to - handle).dot(along) <= zipline.speed * dt {
+            // Let go with the line's speed.
+            commands.entity(entity).remove::<Ziplining>();
+            continue;
+        }
+        let closest = from + along * (handle - from).dot(along);
+        velocity.0 = along * zipline.speed + (closest - handle) / dt * ROPE_STIFFNESS;
+        // Gravity is applied before the step, cancel it so the player does not sag.
+        velocity.0 -= gravity.0 * dt;
+    }
+}
+
+/// A hazard that casts a beam along its local x axis up to the first wall,
+/// killing the players in it, or hurting them by `damage` if set.
+///
+/// Cycles between `on` and `off` seconds of [`LevelTime`], `offset` seconds
+/// into the cycle when the level starts. A zero `off` is always on. Placed and
+/// rotated in the editor like a [`Wall`].
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    CollisionLayers = Layer::Wall.collision_layers(),
+    DebugPickingColor::new(CRIMSON),
+    NeedsSerializedCollider(Vec2::splat(20.0)),
+    ShapeCaster = Self::caster(),
+    LaserBeam
+)]
+#[reflect(Default, Component)]
+pub struct LaserEmitter {
+    pub on: f32,
+    pub off: f32,
+    pub offset: f32,
+    pub damage: Option<f32>,
+}
+
+impl Default for LaserEmitter {
+    fn default() -> Self {
+        Self {
+            on: 2.0,
+            off: 2.0,
+            offset: 0.0,
+            damage: None,
+        }
+    }
+}
+
+impl LaserEmitter {
+    fn caster() -> ShapeCaster {
+        let mut caster =
+            ShapeCaster::new(Collider::circle(3.0), Vec2::ZERO, 0.0, Dir2::X).with_max_hits(8);
+        caster.query_filter = caster.query_filter.with_mask([Layer::Wall, Layer::Player]);
+        caster
+    }
+
+    fn active(&self, time: f64) -> bool {
+        let cycle = (self.on + self.off) as f64;
+        self.off <= 0.0 || (time + self.offset as f64).rem_euclid(cycle) < self.on as f64
+    }
+}
+
+/// State of a [`LaserEmitter`]'s beam, for drawing it.
+#[derive(Default, Clone, Copy, Component)]
+pub struct LaserBeam {
+    pub active: bool,
+    /// Distance to the first wall.
+    pub length: f32,
+}
+
+/// Length of a [`LaserBeam`] that hits no wall.
+const LASER_RANGE: f32 = 4_000.0;
+
+fn fire_lasers(
+    mut commands: Commands,
+    level_time: Res<LevelTime>,
+    mut emitters: Query<(&LaserEmitter, &ShapeHits, &GlobalTransform, &mut LaserBeam)>,
+    players: Query<(), With<Player>>,
+) {
+    for (emitter, hits, transform, mut beam) in emitters.iter_mut() {
+        beam.active = emitter.active(level_time.0);
+        beam.length = hits
+            .iter()
+            .filter(|hit| !players.contains(hit.entity))
+            .map(|hit| hit.distance)
+            .fold(LASER_RANGE, f32::min);
+        if !beam.active {
+            continue;
+        }
+        for hit in hits.iter() {
+            if hit.distance > beam.length || !players.contains(hit.entity) {
+                continue;
+            }
+            match emitter.damage {
+                Some(amount) => commands.trigger(Hurt {
+                    entity: hit.entity,
+                    amount,
+                    source: transform.translation().xy(),
+                }),
+                None => commands.trigger(Killed { entity: hit.entity }),
+            }
+        }
+    }
+}
+
+/// Water, low gravity and the like, for the players and bullets inside.
+///
+/// Scales their [`GravityScale`] and jump impulse and replaces their
+/// [`LinearDamping`], leaving the global [`Gravity`] alone. Bodies in
+/// overlapping volumes take on the last one entered.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollisionEventsEnabled,
+    CollisionLayers = Layer::Volume.collision_layers(),
+    DebugPickingColor::new(BLUE.with_alpha(0.3)),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct PhysicsVolume {
+    pub gravity_scale: f32,
+    pub damping: f32,
+    pub jump_scale: f32,
+}
+
+impl Default for PhysicsVolume {
+    fn default() -> Self {
+        Self {
+            gravity_scale: 0.4,
+            damping: 2.0,
+            jump_scale: 0.6,
+        }
+    }
+}
+
+/// The [`PhysicsVolume`] a body is in, with the values to restore when it
+/// leaves.
+#[derive(Clone, Copy, Component)]
+pub struct InPhysicsVolume {
+    pub volume: Entity,
+    pub jump_scale: f32,
+    gravity_scale: f32,
+    damping: f32,
+}
+
+fn enter_physics_volume(
+    enter: On<CollisionStart>,
+    mut commands: Commands,
+    volumes: Query<&PhysicsVolume>,
+    bodies: Query<(
+        Option<&GravityScale>,
+        Option<&LinearDamping>,
+        Option<&InPhysicsVolume>,
+    )>,
+) {
+    let (Ok(volume), Ok((gravity_scale, damping, inside))) =
+        (volumes.get(enter.collider1), bodies.get(enter.collider2))
+    else {
+        return;
+    };
+    let (gravity_scale, damping) = match inside {
+        Some(inside) => (inside.gravity_scale, inside.damping),
+        None => (
+            gravity_scale.map_or(1.0, |scale| scale.0),
+            damping.map_or(0.0, |damping| damping.0),
+        ),
+    };
+    commands.entity(enter.collider2).try_insert((
+        InPhysicsVolume {
+            volume: enter.collider1,
+            jump_scale: volume.jump_scale,
+            gravity_scale,
+            damping,
+        },
+        GravityScale(gravity_scale * volume.gravity_scale),
+        LinearDamping(volume.damping),
+    ));
+}
+
+fn exit_physics_volume(
+    exit: On<CollisionEnd>,
+    mut commands: Commands,
+    bodies: Query<&InPhysicsVolume>,
+) {
+    let Ok(inside) = bodies.get(exit.collider2) else {
+        return;
+    };
+    if inside.volume != exit.collider1 {
+        return;
+    }
+    commands
+        .entity(exit.collider2)
+        .try_remove::<InPhysicsVolume>()
+        .try_insert((
+            GravityScale(inside.gravity_scale),
+            LinearDamping(inside.damping),
+        ));
+}
+
+/// Pushes the players, bullets and dynamic bodies inside it with `force`, an
+/// acceleration in pixels per second squared.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollidingEntities,
+    CollisionLayers = Layer::Volume.collision_layers(),
+    DebugPickingColor::new(WHITE.with_alpha(0.15)),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct WindZone {
+    pub force: Vec2,
+}
+
+impl Default for WindZone {
+    fn default() -> Self {
+        Self {
+            force: Vec2::new(800.0, 0.0),
+        }
+    }
+}
+
+fn blow_wind(
+    time: Res<Time>,
+    zones: Query<(&WindZone, &CollidingEntities)>,
+    mut players: Query<(&mut ExternalVelocity, &mut LinearVelocity), With<Player>>,
+    mut bodies: Query<(&RigidBody, &mut LinearVelocity), Without<Player>>,
+) {
+    let dt = time.delta_secs();
+    for (zone, colliding) in zones.iter() {
+        let push = zone.force * dt;
+        for entity in colliding.iter() {
+            if let Ok((mut external, mut velocity)) = players.get_mut(*entity) {
+                // Movement overrides the player's x velocity each step.
+                external.0 += push.x;
+                velocity.y += push.y;
+            } else if let Ok((body, mut velocity)) = bodies.get_mut(*entity)
+                && body.is_dynamic()
+            {
+                velocity.0 += push;
+            }
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    RigidBody::Static,
+    Sensor,
+    CollisionEventsEnabled,
+    CollisionLayers = Self::collision_layers(),
+    DebugPickingColor::new(RED),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct KillBox;
+
+impl KillBox {
+    pub fn collision_layers() -> CollisionLayers {
+        Layer::KillBox.collision_layers()
+    }
+}
+
+/// Where the player is placed when the level loads or resets.
+///
+/// The player is saved with the level for their weapons, but where they stood
+/// when it was saved is ignored, so that playing a level in the editor does
+/// not move its start.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollisionLayers = CollisionLayers::NONE,
+    DebugPickingColor::new(ORANGE.with_alpha(0.3)),
+    NeedsSerializedCollider(Vec2::new(25.0, 40.0))
+)]
+#[reflect(Default, Component)]
+pub struct SpawnPoint;
+
+/// Names a [`Door`] or [`SpawnPoint`] that doors of other levels lead to.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Component, Reflect)]
+#[require(Serialize)]
+#[reflect(Default, Component)]
+pub struct EntranceId(pub String);
+
+/// The entrance of `level` that the last [`Door`] led to.
+///
+/// Kept until another door is taken, so that the level resets to the same
+/// entrance. Ignored once the [`Level`] is changed some other way.
+#[derive(Debug, Clone, Resource)]
+pub struct LevelEntrance {
+    pub level: String,
+    pub entrance: String,
+}
+
+/// The entrance [`Door`] a player was placed in, which does not take them
+/// back until they have left it.
+#[derive(Clone, Copy, Component)]
+pub struct EnteredThrough(pub Entity);
+
+fn place_at_spawn_point(
+    mut commands: Commands,
+    spawn_points: Query<(&GlobalTransform, Has<EntranceId>), With<SpawnPoint>>,
+    entrances: Query<(Entity, &EntranceId, &GlobalTransform, Has<Door>)>,
+    level: Res<Level>,
+    entrance: Option<Res<LevelEntrance>>,
+    mut players: Query<
+        (Entity, &mut Transform, Option<&mut Position>),
+        (Added<Player>, Without<Partner>),
+    >,
+) {
+    if players.is_empty() {
+        return;
+    }
+    let entrance = entrance
+        .filter(|entrance| entrance.level == level.0)
+        .and_then(|entrance| {
+            let found = entrances
+                .iter()
+                .find(|(_, id, _, _)| id.0 == entrance.entrance);
+            if found.is_none() {
+                warn!("{}: no entrance `{}`", entrance.level, entrance.entrance);
+            }
+            found
+        });
+    let (door, spawn_point) = match entrance {
+        Some((entity, _, transform, is_door)) => (is_door.then_some(entity), transform),
+        // Prefer the spawn point that is not an entrance.
+        None => match spawn_points.iter().min_by_key(|(_, entrance)| *entrance) {
+            Some((transform, _)) => (None, transform),
+            None => return,
+        },
+    };
+    let spawn_point = spawn_point.translation().xy();
+    for (entity, mut transform, position) in players.iter_mut() {
+        transform.translation = spawn_point.extend(transform.translation.z);
+        if let Some(mut position) = position {
+            position.0 = spawn_point;
+        }
+        if let Some(door) = door {
+            commands.entity(entity).insert(EnteredThrough(door));
+        }
+    }
+}
+
+fn leave_entrance(exit: On<CollisionEnd>, mut commands: Commands, players: Query<&EnteredThrough>) {
+    if players
+        .get(exit.collider2)
+        .is_ok_and(|entered| entered.0 == exit.collider1)
+    {
+        commands
+            .entity(exit.collider2)
+            .try_remove::<EnteredThrough>();
+    }
+}
+
+/// Where a player was when the level loaded.
+#[derive(Clone, Copy, Component)]
+pub struct LevelStart(pub Vec2);
 
-#[derive(Default, Clone, Copy, Component, Reflect)]
-#[require(
-    Serialize,
-    RigidBody::Static,
-    CollisionLayers::new(Layer::Wall, LayerMask::ALL),
-    DebugPickingColor::new(BLUE),
-    NeedsSerializedCollider
-)]
-#[reflect(Default, Component)]
-pub struct Wall;
+fn record_level_start(mut commands: Commands, players: Query<(Entity, &Transform), Added<Player>>) {
+    for (entity, transform) in players.iter() {
+        commands
+            .entity(entity)
+            .insert(LevelStart(transform.translation.xy()));
+    }
+}
 
+/// Where players respawn when killed, instead of reloading the level.
+///
+/// Destroyed keys and other changes to the level since are kept.
 #[derive(Default, Clone, Copy, Component, Reflect)]
 #[require(
     Serialize,
+    Transform,
     RigidBody::Static,
     Sensor,
     CollisionEventsEnabled,
-    CollisionLayers = Self::collision_layers(),
-    DebugPickingColor::new(RED),
+    CollisionLayers = Layer::Trigger.collision_layers(),
+    DebugPickingColor::new(GOLD),
     NeedsSerializedCollider
 )]
 #[reflect(Default, Component)]
-pub struct KillBox;
+pub struct Checkpoint;
 
-impl KillBox {
-    pub fn collision_layers() -> CollisionLayers {
-        CollisionLayers::new(Layer::KillBox, LayerMask::ALL)
+/// The last [`Checkpoint`] touched in the current level.
+#[derive(Default, Clone, Copy, Resource)]
+pub struct ActiveCheckpoint(pub Option<CheckpointState>);
+
+#[derive(Clone, Copy)]
+pub struct CheckpointState {
+    pub entity: Entity,
+    pub position: Vec2,
+    /// Gravity when the checkpoint was reached, restored with the position.
+    pub gravity: Vec2,
+}
+
+fn clear_checkpoint(
+    mut checkpoint: ResMut<ActiveCheckpoint>,
+    new_level: Query<(), Added<LevelGeometry>>,
+) {
+    if !new_level.is_empty() {
+        checkpoint.0 = None;
+    }
+}
+
+fn reach_checkpoint(
+    enter: On<CollisionStart>,
+    checkpoints: Query<&GlobalTransform, With<Checkpoint>>,
+    players: Query<(), With<Player>>,
+    gravity: Res<Gravity>,
+    mut active: ResMut<ActiveCheckpoint>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let Ok(transform) = checkpoints.get(enter.collider1) else {
+        return;
+    };
+    if !players.contains(enter.collider2)
+        || active
+            .0
+            .is_some_and(|active| active.entity == enter.collider1)
+    {
+        return;
+    }
+    let position = transform.translation().xy();
+    active.0 = Some(CheckpointState {
+        entity: enter.collider1,
+        position,
+        gravity: gravity.0,
+    });
+    sfx.write(PlaySfx::at(Sfx::Click, position));
+}
+
+/// Moves a killed player back to the [`ActiveCheckpoint`].
+pub fn respawn_at_checkpoint(
+    player: In<Entity>,
+    checkpoint: Res<ActiveCheckpoint>,
+    mut gravity: ResMut<Gravity>,
+    mut players: Query<(&mut Transform, &mut Position, &mut LinearVelocity), With<Player>>,
+) {
+    let (Some(checkpoint), Ok((mut transform, mut position, mut velocity))) =
+        (checkpoint.0, players.get_mut(*player))
+    else {
+        return;
+    };
+    // Both, since this may run in the middle of the physics step.
+    transform.translation = checkpoint.position.extend(transform.translation.z);
+    position.0 = checkpoint.position;
+    velocity.0 = Vec2::ZERO;
+    if gravity.0 != checkpoint.gravity {
+        gravity.0 = checkpoint.gravity;
     }
 }
 
 fn killbox(
     enter: On<CollisionStart>,
     mut commands: Commands,
-    player: Single<Entity, With<Player>>,
-    killboxes: Query<&KillBox>,
+    players: Query<(), With<Player>>,
+    killboxes: Query<(&GlobalTransform, Option<&KillboxDamage>), With<KillBox>>,
 ) {
-    if killboxes.contains(enter.collider1) {
-        if enter.collider2 == *player {
-            commands.run_system_cached(reset_level);
+    if let Ok((killbox_transform, damage)) = killboxes.get(enter.collider1) {
+        if players.contains(enter.collider2) {
+            if let Some(damage) = damage {
+                commands.trigger(Hurt {
+                    entity: enter.collider2,
+                    amount: damage.0,
+                    source: killbox_transform.translation().xy(),
+                });
+                return;
+            }
+            commands.run_system_cached_with(kill_player, enter.collider2);
         } else {
             commands.entity(enter.collider2).despawn();
         }
     }
 }
 
+/// Kills a player that entered a [`KillBox`] or left the [`LevelBoundary`],
+/// or moves them back to the [`ActiveCheckpoint`] or their [`LevelStart`]
+/// when the difficulty allows it.
+fn kill_player(
+    player: In<Entity>,
+    mut commands: Commands,
+    settings: Res<Settings>,
+    checkpoint: Res<ActiveCheckpoint>,
+    mut players: Query<
+        (
+            &mut Transform,
+            &mut Position,
+            &mut LinearVelocity,
+            Option<&LevelStart>,
+        ),
+        With<Player>,
+    >,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let Ok((mut transform, mut position, mut velocity, start)) = players.get_mut(*player) else {
+        return;
+    };
+    let start = checkpoint
+        .0
+        .map(|checkpoint| checkpoint.position)
+        .or(start.map(|start| start.0));
+    if settings.difficulty.table().killbox_respawn
+        && let Some(start) = start
+    {
+        sfx.write(PlaySfx::new(Sfx::Death));
+        // Both, since this may run in the middle of the physics step.
+        transform.translation = start.extend(transform.translation.z);
+        position.0 = start;
+        velocity.0 = Vec2::ZERO;
+        return;
+    }
+    commands.trigger(Killed { entity: *player });
+}
+
+/// Makes a [`KillBox`] [`Hurt`] players by this amount instead of killing
+/// them outright.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(KillBox)]
+#[reflect(Default, Component)]
+pub struct KillboxDamage(pub f32);
+
+impl Default for KillboxDamage {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
 #[derive(Clone, Copy, Component, Reflect)]
 #[require(KillBox)]
 #[reflect(Default, Component)]
@@ -156,8 +1217,12 @@ impl Default for KillboxClock {
     }
 }
 
-fn killbox_clock(boxes: Query<(Entity, &KillboxClock)>, time: Res<Time>, mut commands: Commands) {
-    let elapsed = time.elapsed().as_secs_f64();
+fn killbox_clock(
+    boxes: Query<(Entity, &KillboxClock)>,
+    level_time: Res<LevelTime>,
+    mut commands: Commands,
+) {
+    let elapsed = level_time.0;
 
     for (entity, clock) in &boxes {
         let region = (elapsed / clock.seconds as f64) as i64;
@@ -211,37 +1276,62 @@ fn killbox_gravity_switch(
     RigidBody::Static,
     Sensor,
     CollisionEventsEnabled,
-    CollisionLayers::new(Layer::Default, LayerMask::ALL),
+    CollisionLayers = Layer::Door.collision_layers(),
     DebugPickingColor::new(GREEN),
     NeedsSerializedCollider
 )]
 #[reflect(Default, Component)]
-pub struct Door(pub String);
+pub struct Door {
+    /// Ident of the level the door leads to.
+    pub level: String,
+    /// [`EntranceId`] in `level` to place the player at, instead of its
+    /// [`SpawnPoint`].
+    pub entrance: Option<String>,
+}
 
 impl Default for Door {
     fn default() -> Self {
-        Self("shotgun_1".to_string())
+        Self {
+            level: "shotgun_1".to_string(),
+            entrance: None,
+        }
     }
 }
 
-#[derive(Component)]
+#[derive(Clone, Copy, Component)]
 pub struct Locked;
 
 fn door(
     start: On<CollisionStart>,
     mut commands: Commands,
-    player: Single<Entity, With<Player>>,
-    doors: Query<(&Door, Option<&Keys>), Without<Locked>>,
+    players: Query<Option<&EnteredThrough>, With<Player>>,
+    doors: Query<(&Door, &GlobalTransform, Option<&Keys>), Without<Locked>>,
     must_keep: Query<&MustKeep>,
     mut level: ResMut<Level>,
+    level_time: Res<LevelTime>,
+    transition: Option<Res<Transition>>,
+    mut sfx: MessageWriter<PlaySfx>,
 ) {
-    if *player == start.collider2
-        && let Ok((door, keys)) = doors.get(start.collider1)
+    if transition.is_none()
+        && let Ok(entered) = players.get(start.collider2)
+        && entered.is_none_or(|entered| entered.0 != start.collider1)
+        && let Ok((door, transform, keys)) = doors.get(start.collider1)
         && keys.is_none_or(|keys| keys.iter().all(|entity| must_keep.contains(entity)))
     {
-        level.0 = door.0.clone();
-        commands.run_system_cached(despawn_level);
-        commands.run_system_cached(reset_level);
+        commands.trigger(LevelComplete {
+            level: level.0.clone(),
+            time: level_time.0,
+        });
+        level.0 = door.level.clone();
+        match &door.entrance {
+            Some(entrance) => commands.insert_resource(LevelEntrance {
+                level: door.level.clone(),
+                entrance: entrance.clone(),
+            }),
+            None => commands.remove_resource::<LevelEntrance>(),
+        }
+        sfx.write(PlaySfx::at(Sfx::Door, transform.translation().xy()));
+        commands.run_system_cached(start_transition);
     }
 }
 
@@ -259,11 +1349,12 @@ pub struct KeyOf(pub Entity);
 #[require(
     Serialize,
     Transform,
+    Health,
     RigidBody::Static,
     CollisionEventsEnabled,
     LinearVelocity::default(),
     DebugPickingColor::new(YELLOW),
-    CollisionLayers::new(Layer::Key, LayerMask::ALL),
+    CollisionLayers = Layer::Key.collision_layers(),
     NeedsSerializedCollider
 )]
 #[reflect(Component)]
@@ -274,6 +1365,147 @@ pub struct Key;
 #[reflect(Default, Component)]
 pub struct MustDestroy;
 
+/// A [`Wall`] that opens for `duration` seconds when one of its [`Plates`] is
+/// pressed, fading back in and blinking as it is about to close.
+#[derive(Clone, Copy, Component, Reflect)]
+#[require(Wall, DebugPickingColor::new(TEAL))]
+#[reflect(Default, Component)]
+pub struct TimedDoor {
+    pub duration: f32,
+}
+
+impl Default for TimedDoor {
+    fn default() -> Self {
+        Self { duration: 3.0 }
+    }
+}
+
+/// Seconds until an open [`TimedDoor`] closes.
+#[derive(Clone, Copy, Component)]
+pub struct DoorOpen(pub f32);
+
+/// Seconds before closing that an open [`TimedDoor`] blinks.
+const DOOR_BLINK: f32 = 1.0;
+
+#[derive(Component, Reflect)]
+#[relationship_target(relationship = PlateOf)]
+#[reflect(Component)]
+pub struct Plates(Vec<Entity>);
+
+/// Links a [`PressurePlate`] or [`ShotTarget`] to the [`TimedDoor`] it opens.
+#[derive(Component, Reflect)]
+#[relationship(relationship_target = Plates)]
+#[reflect(Component)]
+pub struct PlateOf(pub Entity);
+
+/// Keeps its [`TimedDoor`] open while a player stands on it.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollidingEntities,
+    CollisionLayers = Layer::Trigger.collision_layers(),
+    DebugPickingColor::new(TOMATO),
+    NeedsSerializedCollider(Vec2::new(60.0, 10.0))
+)]
+#[reflect(Default, Component)]
+pub struct PressurePlate;
+
+/// Opens its [`TimedDoor`] when shot.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    CollisionEventsEnabled,
+    CollisionLayers = Layer::Wall.collision_layers(),
+    DebugPickingColor::new(CRIMSON),
+    NeedsSerializedCollider(Vec2::splat(30.0))
+)]
+#[reflect(Default, Component)]
+pub struct ShotTarget;
+
+/// Opens the [`TimedDoor`] `entity`, or restarts its timer if already open.
+fn open_timed_door(
+    commands: &mut Commands,
+    doors: &Query<(&TimedDoor, Has<DoorOpen>, &GlobalTransform)>,
+    sfx: &mut MessageWriter<PlaySfx>,
+    entity: Entity,
+) {
+    let Ok((door, open, transform)) = doors.get(entity) else {
+        return;
+    };
+    if !open {
+        sfx.write(PlaySfx::at(Sfx::Click, transform.translation().xy()));
+    }
+    commands
+        .entity(entity)
+        .insert((DoorOpen(door.duration), ColliderDisabled));
+}
+
+fn press_plates(
+    mut commands: Commands,
+    plates: Query<(&CollidingEntities, &PlateOf), With<PressurePlate>>,
+    players: Query<(), With<Player>>,
+    doors: Query<(&TimedDoor, Has<DoorOpen>, &GlobalTransform)>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    for (colliding, plate_of) in plates.iter() {
+        if colliding.iter().any(|entity| players.contains(*entity)) {
+            open_timed_door(&mut commands, &doors, &mut sfx, plate_of.0);
+        }
+    }
+}
+
+fn shoot_target(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    targets: Query<&PlateOf, With<ShotTarget>>,
+    doors: Query<(&TimedDoor, Has<DoorOpen>, &GlobalTransform)>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    for impact in impacts.read() {
+        if let Ok(plate_of) = targets.get(impact.target) {
+            open_timed_door(&mut commands, &doors, &mut sfx, plate_of.0);
+        }
+    }
+}
+
+/// Counts down open [`TimedDoor`]s, fading them in as they are about to close.
+fn close_timed_doors(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut doors: Query<(
+        Entity,
+        &TimedDoor,
+        &mut DoorOpen,
+        &DebugPickingColor,
+        Option<&mut Sprite>,
+    )>,
+) {
+    for (entity, door, mut open, color, sprite) in doors.iter_mut() {
+        open.0 -= time.delta_secs();
+        if open.0 <= 0.0 {
+            commands
+                .entity(entity)
+                .remove::<(DoorOpen, ColliderDisabled)>();
+            if let Some(mut sprite) = sprite {
+                sprite.color = color.0;
+            }
+            continue;
+        }
+        let Some(mut sprite) = sprite else {
+            continue;
+        };
+        let closed = 1.0 - open.0 / door.duration.max(f32::EPSILON);
+        let blink = open.0 < DOOR_BLINK && (open.0 * 8.0).fract() < 0.5;
+        let alpha = if blink { 0.8 } else { 0.1 + 0.4 * closed };
+        sprite.color = color.0.with_alpha(alpha);
+    }
+}
+
 #[derive(Default, Clone, Copy, Component, Reflect)]
 #[require(Key)]
 #[reflect(Default, Component)]
@@ -285,14 +1517,9 @@ fn must_keep(remove: On<Remove, MustKeep>, mut commands: Commands, key_ofs: Quer
     }
 }
 
-fn destroy_key(
-    enter: On<CollisionStart>,
-    mut commands: Commands,
-    keys: Query<&Key>,
-    bullets: Query<&Bullet>,
-) {
-    if keys.contains(enter.collider1) && bullets.contains(enter.collider2) {
-        commands.entity(enter.collider1).despawn();
+fn key_died(died: On<Died>, keys: Query<(), With<Key>>, mut sfx: MessageWriter<PlaySfx>) {
+    if keys.contains(died.entity) {
+        sfx.write(PlaySfx::at(Sfx::KeyDestroyed, died.position));
     }
 }
 
@@ -315,36 +1542,93 @@ impl DebugPickingColor {
     }
 }
 
-fn add_pickable_sprites(
+/// Shows the collider of an entity with a [`DebugPickingColor`].
+///
+/// Rectangles are drawn as sprites and other shapes as meshes, which are
+/// skipped without a renderer.
+fn add_pickable_sprite(
+    add: On<Add, Collider>,
     mut commands: Commands,
-    walls: Query<(Entity, &Collider, &DebugPickingColor), Without<Sprite>>,
+    walls: Query<(&Collider, &DebugPickingColor), (Without<Sprite>, Without<Mesh2d>)>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<ColorMaterial>>>,
 ) {
-    for (entity, collider, color) in walls.iter() {
-        let shape = collider.shape().as_cuboid().unwrap();
-        commands.entity(entity).insert(Sprite::from_color(
+    let Ok((collider, color)) = walls.get(add.entity) else {
+        return;
+    };
+    if let Some(shape) = collider.shape().as_cuboid() {
+        commands.entity(add.entity).insert(Sprite::from_color(
             color.0,
             Vec2::new(shape.half_extents.x * 2.0, shape.half_extents.y * 2.0),
         ));
+        return;
+    }
+    let (Some(mut meshes), Some(mut materials)) = (meshes, materials) else {
+        return;
+    };
+    let Some(mesh) = collider_mesh(collider) else {
+        warn!("no mesh for the collider of {}", add.entity);
+        return;
+    };
+    commands.entity(add.entity).insert((
+        Mesh2d(meshes.add(mesh)),
+        MeshMaterial2d(materials.add(color.0)),
+    ));
+}
+
+/// Mesh of a ball, capsule, triangle or convex polygon collider.
+fn collider_mesh(collider: &Collider) -> Option<Mesh> {
+    let shape = collider.shape();
+    if let Some(ball) = shape.as_ball() {
+        return Some(Circle::new(ball.radius).into());
     }
+    if let Some(capsule) = shape.as_capsule() {
+        return Some(Capsule2d::new(capsule.radius, capsule.height()).into());
+    }
+    let points = if let Some(triangle) = shape.as_triangle() {
+        triangle.vertices().to_vec()
+    } else {
+        shape.as_convex_polygon()?.points().to_vec()
+    };
+    let positions = points
+        .iter()
+        .map(|point| [point.x, point.y, 0.0])
+        .collect::<Vec<_>>();
+    // Convex, so a fan from the first vertex covers it.
+    let indices = (1..points.len().saturating_sub(1) as u32)
+        .flat_map(|i| [0, i, i + 1])
+        .collect();
+    Some(
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U32(indices)),
+    )
 }
 
 /// Hack for `requiring` a [`SerializedColliderConstructor`] without requiring it
 /// on the target and breaking the deserialization.
-#[derive(Default, Component)]
-struct NeedsSerializedCollider;
+///
+/// Holds the size of the default rectangle.
+#[derive(Component)]
+pub struct NeedsSerializedCollider(pub Vec2);
+
+impl Default for NeedsSerializedCollider {
+    fn default() -> Self {
+        Self(Vec2::splat(100.0))
+    }
+}
 
 fn needs_serialized_collider(
     mut commands: Commands,
-    needs: Query<
-        Entity,
-        (
-            With<NeedsSerializedCollider>,
-            Without<SerializedColliderConstructor>,
-        ),
-    >,
+    needs: Query<(Entity, &NeedsSerializedCollider), Without<SerializedColliderConstructor>>,
 ) {
-    for entity in needs.iter() {
-        commands.entity(entity).insert(rectangle(100.0, 100.0));
+    for (entity, needs) in needs.iter() {
+        commands
+            .entity(entity)
+            .insert(rectangle(needs.0.x, needs.0.y));
     }
 }
 
@@ -364,7 +1648,61 @@ impl SerializedColliderConstructor {
             .commands()
             .entity(ctx.entity)
             .remove::<(ColliderConstructor, Collider)>()
-            .insert(constructor);
+            .insert(PendingCollider(constructor));
+    }
+}
+
+/// Colliders built per frame, so that large levels do not hitch on the frame
+/// their scene spawns.
+const COLLIDERS_PER_FRAME: usize = 64;
+
+/// Whether the current level is still loading.
+///
+/// Set when the level scene is requested and cleared once its instance is
+/// ready and its colliders are built. Physics is paused in the meantime, and
+/// the [`PlayerSet`](crate::player::PlayerSet) and
+/// [`WeaponSet`](crate::weapon::WeaponSet) wait for [`level_loaded`].
+#[derive(Default, Resource)]
+pub struct LevelLoading(pub bool);
+
+/// Run condition for systems that expect the whole level to be spawned.
+pub fn level_loaded(loading: Res<LevelLoading>) -> bool {
+    !loading.0
+}
+
+/// A [`ColliderConstructor`] waiting for [`build_colliders`].
+#[derive(Component)]
+struct PendingCollider(ColliderConstructor);
+
+fn build_colliders(
+    mut commands: Commands,
+    mut loading: ResMut<LevelLoading>,
+    pending: Query<(Entity, &PendingCollider)>,
+    level: Query<(), With<LevelGeometry>>,
+    scene_roots: Query<(), With<DynamicSceneRoot>>,
+) {
+    let remaining = pending.iter().count();
+    for (entity, constructor) in pending.iter().take(COLLIDERS_PER_FRAME) {
+        commands
+            .entity(entity)
+            .remove::<PendingCollider>()
+            .insert(constructor.0.clone());
+    }
+    // The root is removed once its instance is ready.
+    if loading.0 && scene_roots.is_empty() && !level.is_empty() && remaining <= COLLIDERS_PER_FRAME
+    {
+        loading.0 = false;
+    }
+}
+
+fn pause_physics_while_loading(loading: Res<LevelLoading>, mut time: ResMut<Time<Physics>>) {
+    if !loading.is_changed() {
+        return;
+    }
+    if loading.0 {
+        time.pause();
+    } else {
+        time.unpause();
     }
 }
 
@@ -380,77 +1718,413 @@ pub fn user_serialize_level(
     mut commands: Commands,
     input: Res<ButtonInput<KeyCode>>,
     disable_input: Query<&inspector::DisableInput>,
+    scene: Option<ResMut<LevelScene>>,
 ) {
     if !disable_input.is_empty() || !input.just_pressed(KeyCode::KeyP) {
         return;
     }
+    if let Some(mut scene) = scene {
+        scene.saved = true;
+    }
     commands.run_system_cached(serialize_level);
 }
 
+/// Scene of the current level, kept loaded so that edits to its file are
+/// noticed by `hot_reload_level` in debug builds.
+#[derive(Resource)]
+#[cfg_attr(not(feature = "debug"), allow(dead_code))]
+pub struct LevelScene {
+    handle: Handle<DynamicScene>,
+    /// Set when the level is saved from the game, which should not reset it.
+    saved: bool,
+}
+
+/// Resets the level when its file changes on disk.
+#[cfg(feature = "debug")]
+fn hot_reload_level(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<DynamicScene>>,
+    mut scene: ResMut<LevelScene>,
+) {
+    for event in events.read() {
+        if !event.is_modified(&scene.handle) || std::mem::take(&mut scene.saved) {
+            continue;
+        }
+        info!("level file changed, reloading");
+        commands.run_system_cached(reset_level);
+    }
+}
+
 pub fn serialize_level(
     world: &World,
     serialize: Query<Entity, With<Serialize>>,
     level: Res<Level>,
 ) {
-    use crate::weapon::*;
+    let _span = info_span!("serialize_level", level = %level.0).entered();
+    let filter = world.resource::<Registry>().components.clone();
     let scene = DynamicSceneBuilder::from_world(world)
-        .allow_component::<Serialize>()
-        .allow_component::<Name>()
-        .allow_component::<Transform>()
-        .allow_component::<GlobalTransform>()
-        .allow_component::<Visibility>()
-        .allow_component::<Player>()
-        .allow_component::<Children>()
-        .allow_component::<ChildOf>()
-        .allow_component::<SelectedWeapon>()
-        .allow_component::<WeaponPickup>()
-        .allow_component::<MaxAmmo>()
-        .allow_component::<Shotgun>()
-        .allow_component::<AssaultRifle>()
-        .allow_component::<GravityGun>()
-        .allow_component::<Rocket>()
-        .allow_component::<Laser>()
-        .allow_component::<LevelGeometry>()
-        .allow_component::<Door>()
-        .allow_component::<MustDestroy>()
-        .allow_component::<MustKeep>()
-        .allow_component::<Keys>()
-        .allow_component::<KeyOf>()
-        .allow_component::<Wall>()
-        .allow_component::<KillBox>()
-        .allow_component::<KillboxClock>()
-        .allow_component::<KillboxGravitySwitch>()
-        .allow_component::<Sensor>()
-        .allow_component::<CollisionEventsEnabled>()
-        .allow_component::<RigidBody>()
-        .allow_component::<SerializedColliderConstructor>()
+        .with_component_filter(filter)
         .extract_entities(serialize.iter())
         .build();
     let type_registry = world.resource::<AppTypeRegistry>().read();
-    let serialized_scene = scene.serialize(&type_registry).unwrap();
+    #[cfg(feature = "binary_levels")]
+    let serialized_scene = postcard::to_allocvec(&(
+        LEVEL_VERSION,
+        bevy::scene::serde::SceneSerializer::new(&scene, &type_registry),
+    ))
+    .unwrap();
+    #[cfg(not(feature = "binary_levels"))]
+    let serialized_scene = format!(
+        "{VERSION_HEADER}{LEVEL_VERSION}\n{}",
+        scene.serialize(&type_registry).unwrap()
+    )
+    .into_bytes();
 
-    let level_ident = level.0.clone();
+    let path = assets::path(format!("scenes/{}.{LEVEL_EXTENSION}", level.0));
     IoTaskPool::get()
         .spawn(async move {
-            File::create(format!("assets/scenes/{}.scn.ron", level_ident))
-                .and_then(|mut file| file.write(serialized_scene.as_bytes()))
+            File::create(path)
+                .and_then(|mut file| file.write(&serialized_scene))
                 .expect("error while writing scene to file");
         })
         .detach();
 }
 
-pub fn deserialize_level(mut commands: Commands, server: Res<AssetServer>, level: Res<Level>) {
+pub fn deserialize_level(
+    mut commands: Commands,
+    type_registry: Res<AppTypeRegistry>,
+    level: Res<Level>,
+    mut loading: ResMut<LevelLoading>,
+) {
+    let _span = info_span!("deserialize_level", level = %level.0).entered();
+    loading.0 = true;
+    let ident = level.0.clone();
+    let type_registry = type_registry.0.clone();
+    let task = IoTaskPool::get().spawn(async move {
+        let path = level_path(&ident);
+        let scene = migrate_level(&path, &type_registry);
+        (path, scene)
+    });
+    commands.insert_resource(PendingLevel {
+        ident: level.0.clone(),
+        task,
+    });
+}
+
+/// A level read and migrated off the main thread, spawned by
+/// `spawn_pending_level` once it is done.
+#[derive(Resource)]
+struct PendingLevel {
+    ident: String,
+    /// The path of the level, and its scene if it had to be migrated.
+    task: Task<(String, Option<DynamicScene>)>,
+}
+
+fn spawn_pending_level(
+    mut commands: Commands,
+    server: Res<AssetServer>,
+    mut scenes: ResMut<Assets<DynamicScene>>,
+    mut pending: ResMut<PendingLevel>,
+) {
+    let Some((path, migrated)) = block_on(future::poll_once(&mut pending.task)) else {
+        return;
+    };
+    commands.remove_resource::<PendingLevel>();
+    // Migrated levels are not hot reloaded until they are saved.
+    let scene = match migrated {
+        Some(scene) => scenes.add(scene),
+        None => server.load(path),
+    };
+    commands.insert_resource(LevelScene {
+        handle: scene.clone(),
+        saved: false,
+    });
     commands.spawn((
-        Name::from(level.0.clone()),
-        DynamicSceneRoot(server.load(format!("scenes/{}.scn.ron", level.0))),
+        Name::from(std::mem::take(&mut pending.ident)),
+        DynamicSceneRoot(scene),
     ));
 }
 
+/// Version of the level format written by [`serialize_level`].
+///
+/// RON levels start with a `// version N` comment, binary levels with the
+/// number. Levels without one are version `0`.
+pub const LEVEL_VERSION: u32 = 6;
+
+const VERSION_HEADER: &str = "// version ";
+
+/// Upgrades a RON level from the version at its index to the next.
+///
+/// Add one whenever a serialized component changes shape, usually by renaming
+/// or rewriting its entries, and bump [`LEVEL_VERSION`].
+const MIGRATIONS: [fn(String) -> String; LEVEL_VERSION as usize] = [
+    // Levels from before versioning only lack the header.
+    |level| level,
+    // The gravity gun opened wells instead of flipping gravity.
+    |level| rewrite_component(level, "shplat::weapon::GravityGun", "()", "(flip: true)"),
+    // The selected weapon became the primary of two weapon slots.
+    |level| {
+        rename_component(
+            level,
+            "shplat::weapon::SelectedWeapon",
+            "shplat::weapon::PrimaryWeapon",
+        )
+    },
+    // Weapon pickups had a fixed radius.
+    |level| {
+        rewrite_component(
+            level,
+            "shplat::weapon::WeaponPickup",
+            "()",
+            "(radius: 100.0)",
+        )
+    },
+    // Players spawned where they were saved.
+    add_spawn_point,
+    // Doors only named the level they led to.
+    add_door_entrance,
+];
+
+/// The entry of the component `type_path` in an entity.
+///
+/// Matching on the key leaves names and other values that mention the type
+/// alone.
+fn component_key(type_path: &str) -> String {
+    format!("\"{type_path}\": ")
+}
+
+fn rename_component(level: String, from: &str, to: &str) -> String {
+    level.replace(&component_key(from), &component_key(to))
+}
+
+/// Replaces the value `from` of the component `type_path` with `to`.
+fn rewrite_component(level: String, type_path: &str, from: &str, to: &str) -> String {
+    let key = component_key(type_path);
+    level.replace(&format!("{key}{from}"), &format!("{key}{to}"))
+}
+
+/// Adds a [`SpawnPoint`] where the player of `level` was saved, unless it
+/// already has one.
+fn add_spawn_point(level: String) -> String {
+    const TRANSLATION: &str = "translation: (";
+    if level.contains(&component_key("shplat::level::SpawnPoint")) {
+        return level;
+    }
+    let Some(player) = level.find(&component_key("shplat::player::Player")) else {
+        return level;
+    };
+    // The components of the player, which are sorted by type path.
+    let components = level[..player].rfind("components: {").unwrap_or_default();
+    let components_end = level[player..]
+        .find("\n      },")
+        .map_or(level.len(), |end| player + end);
+    let Some(transform) = level[components..components_end]
+        .find(&component_key(
+            "bevy_transform::components::transform::Transform",
+        ))
+        .map(|transform| components + transform)
+    else {
+        return level;
+    };
+    let Some(start) = level[transform..components_end]
+        .find(TRANSLATION)
+        .map(|start| transform + start + TRANSLATION.len())
+    else {
+        return level;
+    };
+    let Some(end) = level[start..].find(')').map(|end| start + end) else {
+        return level;
+    };
+    // The closing brace of the entity map.
+    let Some(entities_end) = level.rfind("\n  },") else {
+        return level;
+    };
+    let id = level
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("    ")?
+                .strip_suffix(": (")?
+                .parse::<u64>()
+                .ok()
+        })
+        .max()
+        .unwrap_or_default()
+        + 1;
+    let translation = &level[start..end];
+    format!(
+        r#"{}
+    {id}: (
+      components: {{
+        "bevy_ecs::name::Name": "Spawn Point",
+        "bevy_transform::components::transform::Transform": (
+          translation: ({translation}),
+          rotation: (0.0, 0.0, 0.0, 1.0),
+          scale: (1.0, 1.0, 1.0),
+        ),
+        "shplat::level::Serialize": (),
+        "shplat::level::SpawnPoint": (),
+      }},
+    ),{}"#,
+        &level[..entities_end],
+        &level[entities_end..],
+    )
+}
+
+/// Rewrites each [`Door`] to lead to the [`SpawnPoint`] of its level.
+fn add_door_entrance(level: String) -> String {
+    let door = format!("{}(", component_key("shplat::level::Door"));
+    let mut migrated = String::with_capacity(level.len());
+    let mut rest = level.as_str();
+    while let Some(start) = rest.find(&door).map(|start| start + door.len()) {
+        // Doors that already have fields were written after the migration.
+        if !rest[start..].starts_with('"') {
+            migrated.push_str(&rest[..start]);
+            rest = &rest[start..];
+            continue;
+        }
+        let Some(end) = rest[start + 1..].find('"').map(|end| start + 1 + end + 1) else {
+            break;
+        };
+        migrated.push_str(&rest[..start]);
+        migrated.push_str("level: ");
+        migrated.push_str(&rest[start..end]);
+        migrated.push_str(", entrance: None");
+        rest = &rest[end..];
+    }
+    migrated.push_str(rest);
+    migrated
+}
+
+/// Migrates the RON `level` to [`LEVEL_VERSION`] and rewrites its header.
+///
+/// Returns the version the level was written as along with the migrated level,
+/// which is left as is if it was current.
+pub fn migrate(level: String) -> Result<(u32, String), String> {
+    let header = level
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix(VERSION_HEADER));
+    let version = match header {
+        Some(version) => version
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| "unreadable version header".to_string())?,
+        None => 0,
+    };
+    if version == LEVEL_VERSION {
+        return Ok((version, level));
+    }
+    if version > LEVEL_VERSION {
+        return Err(format!("version {version} is newer than {LEVEL_VERSION}"));
+    }
+
+    let mut migrated = match header {
+        Some(_) => level
+            .split_once('\n')
+            .map_or(String::new(), |(_, level)| level.to_string()),
+        None => level,
+    };
+    for migrate in &MIGRATIONS[version as usize..] {
+        migrated = migrate(migrated);
+    }
+    Ok((
+        version,
+        format!("{VERSION_HEADER}{LEVEL_VERSION}\n{migrated}"),
+    ))
+}
+
+/// Reads the RON level at `path` and migrates it if it is out of date.
+///
+/// `None` if the level is current, or can not be migrated, in which case the
+/// [`AssetServer`] loads it as is.
+fn migrate_level(path: &str, type_registry: &TypeRegistryArc) -> Option<DynamicScene> {
+    if !path.ends_with(".scn.ron") {
+        return None;
+    }
+    let level = fs::read_to_string(assets::path(path))
+        .inspect_err(|err| error!("{path}: {err}"))
+        .ok()?;
+    let (version, level) = migrate(level)
+        .inspect_err(|err| warn!("{path}: {err}"))
+        .ok()?;
+    if version == LEVEL_VERSION {
+        return None;
+    }
+
+    let mut deserializer = ron::Deserializer::from_str(&level)
+        .inspect_err(|err| error!("{path}: {err}"))
+        .ok()?;
+    let scene = bevy::scene::serde::SceneDeserializer {
+        type_registry: &type_registry.read(),
+    }
+    .deserialize(&mut deserializer)
+    .inspect_err(|err| error!("{path}: failed to migrate: {err}"))
+    .ok()?;
+    warn!(
+        "{path}: migrated from version {version} to {LEVEL_VERSION}, save it to keep the upgrade"
+    );
+    Some(scene)
+}
+
+/// Loads levels written with the `binary_levels` feature.
+#[cfg(feature = "binary_levels")]
+struct BinarySceneLoader {
+    type_registry: bevy::reflect::TypeRegistryArc,
+}
+
+#[cfg(feature = "binary_levels")]
+impl FromWorld for BinarySceneLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            type_registry: world.resource::<AppTypeRegistry>().0.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "binary_levels")]
+impl bevy::asset::AssetLoader for BinarySceneLoader {
+    type Asset = DynamicScene;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _: &(),
+        _: &mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut deserializer = postcard::Deserializer::from_bytes(&bytes);
+        // Binary levels are written from the current version and not migrated.
+        let version = <u32 as serde::Deserialize>::deserialize(&mut deserializer)?;
+        if version != LEVEL_VERSION {
+            return Err(format!(
+                "binary level version {version}, expected {LEVEL_VERSION}, resave it"
+            )
+            .into());
+        }
+        Ok(bevy::scene::serde::SceneDeserializer {
+            type_registry: &self.type_registry.read(),
+        }
+        .deserialize(&mut deserializer)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scn.bin"]
+    }
+}
+
 fn remove_dynamic_scene_root(
     mut commands: Commands,
-    dynamic_scenes: Query<(Entity, &Children), With<SceneInstance>>,
+    scene_spawner: Res<SceneSpawner>,
+    dynamic_scenes: Query<(Entity, &SceneInstance, &Children)>,
 ) {
-    for (entity, children) in dynamic_scenes.iter() {
+    for (entity, instance, children) in dynamic_scenes.iter() {
+        if !scene_spawner.instance_is_ready(**instance) {
+            continue;
+        }
+        let _span = info_span!("spawn_level_scene", entities = children.len()).entered();
         for child in children.iter() {
             commands.entity(child).remove::<ChildOf>();
         }
@@ -458,10 +2132,7 @@ fn remove_dynamic_scene_root(
     }
 }
 
-pub fn despawn_level(
-    mut commands: Commands,
-    entities: Query<Entity, (Or<(With<Serialize>, With<Transient>)>, Without<ChildOf>)>,
-) {
+pub fn despawn_level(mut commands: Commands, entities: Query<Entity, With<DespawnOnLevelUnload>>) {
     for entity in entities.iter() {
         commands.entity(entity).try_despawn();
     }
@@ -485,6 +2156,7 @@ fn user_reset_level(
 pub fn reset_level(mut commands: Commands, mut gravity: ResMut<Gravity>) {
     let signum = gravity.0.signum();
     gravity.0 *= -signum;
+    commands.run_system_cached(reset_bullet_time);
     commands.run_system_cached(despawn_level);
     commands.run_system_cached(deserialize_level);
 }
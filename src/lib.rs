@@ -0,0 +1,184 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::too_many_arguments)]
+
+use avian2d::prelude::*;
+use bevy::{input::InputPlugin, prelude::*, scene::ScenePlugin, state::app::StatesPlugin};
+
+pub mod achievements;
+pub mod assets;
+pub mod audio;
+#[cfg(feature = "debug")]
+pub mod bench;
+pub mod bullet_time;
+pub mod camera;
+pub mod collision;
+pub mod coop;
+pub mod daily;
+pub mod damage;
+pub mod determinism;
+pub mod difficulty;
+pub mod effects;
+pub mod enemy;
+pub mod ghost;
+#[cfg(feature = "debug")]
+pub mod inspector;
+pub mod juice;
+pub mod keybinds;
+pub mod level;
+pub mod menu;
+pub mod music;
+pub mod navigation;
+#[cfg(feature = "netplay")]
+pub mod netplay;
+pub mod physics;
+pub mod player;
+pub mod registry;
+pub mod replay;
+pub mod save;
+pub mod script;
+pub mod settings;
+pub mod spawner;
+pub mod stats;
+pub mod trail;
+pub mod transition;
+pub mod tuning;
+pub mod versus;
+pub mod weapon;
+
+pub use registry::{
+    register_achievement, register_level_entity, register_serialized, register_weapon,
+};
+
+pub const WIDTH: f32 = 1280.0;
+pub const HEIGHT: f32 = 720.0;
+
+/// Seed for the global RNG.
+///
+/// Must be inserted before [`game_plugin`] is added, otherwise a random seed
+/// is chosen for the run.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct Seed(pub u64);
+
+impl Default for Seed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// Simulation and game logic.
+///
+/// Does not require a window or renderer, see [`headless_plugin`].
+///
+/// Each fixed step runs in order:
+/// 1. input actions in [`FixedPreUpdate`]
+/// 2. [`PlayerSet`](player::PlayerSet) movement in [`FixedUpdate`]
+/// 3. [`WeaponSet`](weapon::WeaponSet), starting with the
+///    [`ImpactSet`](weapon::ImpactSet) stages
+///
+///    Both wait until the level is no longer
+///    [`LevelLoading`](level::LevelLoading).
+/// 4. [`EnemySet`](enemy::EnemySet) AI
+/// 5. [`LevelSet`](level::LevelSet) timers and triggers
+/// 6. physics in [`FixedPostUpdate`]
+pub fn game_plugin(app: &mut App) {
+    let seed = *app.world_mut().get_resource_or_init::<Seed>();
+    info!("seed {}", seed.0);
+    app.add_plugins((
+        bevy_rand::prelude::EntropyPlugin::<bevy_rand::prelude::WyRand>::with_seed(
+            seed.0.to_le_bytes(),
+        ),
+        avian2d::PhysicsPlugins::default().with_length_unit(20.0),
+        bevy_enhanced_input::EnhancedInputPlugin,
+    ))
+    .add_plugins((
+        achievements::plugin,
+        bullet_time::plugin,
+        coop::plugin,
+        daily::plugin,
+        damage::plugin,
+        enemy::plugin,
+        keybinds::plugin,
+        level::plugin,
+        menu::plugin,
+        navigation::plugin,
+        physics::plugin,
+        player::plugin,
+    ))
+    .add_plugins((
+        replay::plugin,
+        save::plugin,
+        script::plugin,
+        settings::plugin,
+        spawner::plugin,
+        stats::plugin,
+        transition::plugin,
+        tuning::plugin,
+        versus::plugin,
+        weapon::plugin,
+    ))
+    // Gameplay requests sound effects whether or not anything plays them.
+    .add_message::<audio::PlaySfx>()
+    .configure_sets(
+        FixedUpdate,
+        (
+            player::PlayerSet,
+            weapon::WeaponSet,
+            enemy::EnemySet,
+            level::LevelSet,
+        )
+            .chain(),
+    )
+    .configure_sets(
+        FixedUpdate,
+        (player::PlayerSet, weapon::WeaponSet).run_if(level::level_loaded),
+    )
+    .insert_resource(Gravity(Vec2::NEG_Y * tuning::Tuning::default().gravity));
+    #[cfg(feature = "netplay")]
+    app.add_plugins(netplay::plugin);
+    #[cfg(feature = "mods")]
+    registry::load_mods(app);
+}
+
+/// Audio, camera and visual feedback on top of [`game_plugin`].
+pub fn presentation_plugin(app: &mut App) {
+    app.add_plugins((
+        bevy_tween::DefaultTweenPlugins,
+        audio::plugin,
+        camera::plugin,
+        effects::plugin,
+        ghost::plugin,
+        juice::plugin,
+        music::plugin,
+        trail::plugin,
+    ));
+}
+
+/// Runs [`game_plugin`] without a window or renderer.
+///
+/// Deterministic, so tests can load a [`level::Level`], simulate `N` steps
+/// with [`App::update`] and assert on the world.
+pub fn headless_plugin(app: &mut App) {
+    app.add_plugins((
+        MinimalPlugins,
+        assets::asset_plugin(),
+        ScenePlugin,
+        TransformPlugin,
+        InputPlugin,
+        StatesPlugin,
+    ))
+    .add_plugins((game_plugin, determinism::plugin));
+}
+
+/// Builds an app with the [`headless_plugin`].
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(headless_plugin);
+    app
+}
+
+#[cfg(not(debug_assertions))]
+pub fn name(_: impl Into<std::borrow::Cow<'static, str>>) -> () {}
+#[cfg(debug_assertions)]
+pub fn name(name: impl Into<std::borrow::Cow<'static, str>>) -> Name {
+    Name::new(name)
+}
@@ -1,88 +1,216 @@
-#![allow(clippy::type_complexity)]
-#![allow(clippy::too_many_arguments)]
-
-use avian2d::prelude::*;
 use bevy::log::LogPlugin;
 use bevy::prelude::*;
 #[cfg(feature = "debug")]
 use bevy::window::PrimaryWindow;
+use shplat::{
+    HEIGHT, Seed, WIDTH,
+    daily::Daily,
+    level::Level,
+    replay::{Playback, Recording, Replay},
+    versus::Versus,
+};
+use std::{net::SocketAddr, path::Path};
 
-#[cfg(feature = "debug")]
-mod inspector;
-mod level;
-mod player;
-mod weapon;
+const USAGE: &str = "Usage: shplat [--level <ident>] [--seed <u64>] [--editor] [--headless] [--deterministic] [--replay <path>] [--daily] [--versus] [--netplay <port> <peer> <handle>] [--package <dir>]";
+
+/// Command-line options.
+#[derive(Default)]
+struct Args {
+    /// Level to start in instead of the default.
+    level: Option<String>,
+    seed: Option<u64>,
+    /// Opens the inspector on startup.
+    editor: bool,
+    /// Runs the simulation without a window or renderer.
+    headless: bool,
+    /// Advances exactly one fixed timestep per frame.
+    deterministic: bool,
+    /// Plays back a recorded replay instead of recording one.
+    replay: Option<String>,
+    /// Plays today's daily challenge.
+    daily: bool,
+    /// Starts in versus mode, where players shoot each other.
+    versus: bool,
+    /// Local port, address of the other peer and handle of a netplay session.
+    netplay: Option<(u16, SocketAddr, usize)>,
+    /// Copies the executable and assets into a distributable folder and exits.
+    package: Option<String>,
+}
 
-pub const WIDTH: f32 = 1280.0;
-pub const HEIGHT: f32 = 720.0;
-pub const GRAVITY: f32 = 2000.0;
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Self::default();
+        let mut input = std::env::args().skip(1);
+        while let Some(arg) = input.next() {
+            match arg.as_str() {
+                "--level" => {
+                    args.level = Some(input.next().ok_or("`--level` expects an ident")?);
+                }
+                "--seed" => {
+                    let seed = input.next().ok_or("`--seed` expects a number")?;
+                    args.seed = Some(seed.parse().map_err(|_| format!("invalid seed `{seed}`"))?);
+                }
+                "--editor" => args.editor = true,
+                "--headless" => args.headless = true,
+                "--deterministic" => args.deterministic = true,
+                "--replay" => {
+                    args.replay = Some(input.next().ok_or("`--replay` expects a path")?);
+                }
+                "--daily" => args.daily = true,
+                "--versus" => args.versus = true,
+                "--netplay" => {
+                    const EXPECTED: &str =
+                        "`--netplay` expects a port, the peer's address and a handle of 0 or 1";
+                    let port = input.next().and_then(|port| port.parse().ok());
+                    let peer = input.next().and_then(|peer| peer.parse().ok());
+                    let handle = input
+                        .next()
+                        .and_then(|handle| handle.parse::<usize>().ok())
+                        .filter(|handle| *handle < 2);
+                    let (Some(port), Some(peer), Some(handle)) = (port, peer, handle) else {
+                        return Err(EXPECTED.to_string());
+                    };
+                    args.netplay = Some((port, peer, handle));
+                }
+                "--package" => {
+                    args.package = Some(input.next().ok_or("`--package` expects a directory")?);
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                arg => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        Ok(args)
+    }
+}
 
 fn main() {
+    let args = Args::parse().unwrap_or_else(|err| {
+        eprintln!("{err}\n{USAGE}");
+        std::process::exit(2);
+    });
+
+    if let Some(out) = &args.package {
+        if let Err(err) = shplat::assets::package(Path::new(out)) {
+            eprintln!("failed to package into {out}: {err}");
+            std::process::exit(1);
+        }
+        println!("packaged into {out}");
+        return;
+    }
+
+    let replay = args.replay.as_deref().map(|path| {
+        Replay::load(path).unwrap_or_else(|err| {
+            eprintln!("failed to load replay {err}");
+            std::process::exit(1);
+        })
+    });
+
+    let daily = (args.daily && replay.is_none()).then(|| {
+        Daily::today().unwrap_or_else(|err| {
+            eprintln!("failed to start the daily challenge: {err}");
+            std::process::exit(1);
+        })
+    });
+
     let mut app = App::default();
+    if let Some(seed) = replay
+        .as_ref()
+        .map(|replay| replay.seed)
+        .or(daily.as_ref().map(|daily| daily.seed))
+        .or(args.seed)
+    {
+        app.insert_resource(Seed(seed));
+    }
 
-    #[cfg(feature = "debug")]
-    let log = LogPlugin {
-        custom_layer: inspector::term_layer,
-        ..Default::default()
-    };
-    #[cfg(not(feature = "debug"))]
-    let log = LogPlugin::default();
-
-    app.add_plugins((
-        DefaultPlugins
-            .set(ImagePlugin::default_nearest())
-            .set(WindowPlugin {
-                primary_window: Some(Window {
-                    resolution: (WIDTH as u32, HEIGHT as u32).into(),
-                    ..Default::default()
-                }),
-                ..Default::default()
-            })
-            .set(log),
-        bevy_tween::DefaultTweenPlugins,
-        bevy_rand::prelude::EntropyPlugin::<bevy_rand::prelude::WyRand>::with_seed(
-            69u64.to_le_bytes(),
-        ),
+    if args.versus {
+        app.insert_resource(Versus::default());
+    }
+
+    if let Some((port, peer, handle)) = args.netplay {
+        #[cfg(feature = "netplay")]
+        app.insert_resource(shplat::netplay::Netplay { port, peer, handle });
+        #[cfg(not(feature = "netplay"))]
+        {
+            _ = (port, peer, handle);
+            eprintln!("`--netplay` requires the `netplay` feature");
+        }
+    }
+
+    if args.headless {
+        app.add_plugins(shplat::headless_plugin);
+    } else {
         #[cfg(feature = "debug")]
-        inspector::plugin,
-    ))
-    .add_plugins((
-        avian2d::PhysicsPlugins::default().with_length_unit(20.0),
+        let log = LogPlugin {
+            custom_layer: shplat::inspector::term_layer,
+            ..Default::default()
+        };
+        #[cfg(not(feature = "debug"))]
+        let log = LogPlugin::default();
+
+        app.add_plugins((
+            DefaultPlugins
+                .set(shplat::assets::asset_plugin())
+                .set(ImagePlugin::default_nearest())
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        resolution: (WIDTH as u32, HEIGHT as u32).into(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .set(log),
+            #[cfg(feature = "debug")]
+            shplat::inspector::plugin,
+            shplat::game_plugin,
+            #[cfg(feature = "debug")]
+            avian2d::debug_render::PhysicsDebugPlugin,
+            shplat::presentation_plugin,
+        ));
+        if args.deterministic || replay.is_some() || daily.is_some() {
+            app.add_plugins(shplat::determinism::plugin);
+        }
+
         #[cfg(feature = "debug")]
-        avian2d::debug_render::PhysicsDebugPlugin,
-        bevy_enhanced_input::EnhancedInputPlugin,
-        level::plugin,
-        player::plugin,
-        weapon::plugin,
-    ))
-    .insert_resource(Gravity(Vec2::NEG_Y * GRAVITY));
+        {
+            app.add_systems(Startup, maximize);
+            if args.editor {
+                app.add_systems(Startup, shplat::inspector::open_inspector);
+            }
+        }
+        #[cfg(not(feature = "debug"))]
+        if args.editor {
+            eprintln!("`--editor` requires the `debug` feature");
+        }
+    }
+
+    if let Some(level) = replay
+        .as_ref()
+        .map(|replay| replay.level.clone())
+        .or(daily.as_ref().map(|daily| daily.levels[0].clone()))
+        .or(args.level)
+    {
+        app.insert_resource(Level(level));
+    }
+    if let Some(daily) = daily {
+        app.insert_resource(daily);
+    }
+    if let Some(replay) = replay {
+        app.insert_resource(Playback::new(replay));
+    } else if !args.headless && args.netplay.is_none() {
+        // Rolled back steps would be recorded again.
+        app.init_resource::<Recording>();
+    }
 
     #[cfg(not(feature = "debug"))]
     app.set_error_handler(bevy::ecs::error::warn);
 
-    app.add_systems(
-        Startup,
-        (
-            camera,
-            #[cfg(feature = "debug")]
-            maximize,
-        ),
-    )
-    .run();
-}
-
-#[cfg(not(debug_assertions))]
-pub fn name(_: impl Into<std::borrow::Cow<'static, str>>) -> () {}
-#[cfg(debug_assertions)]
-pub fn name(name: impl Into<std::borrow::Cow<'static, str>>) -> Name {
-    Name::new(name)
+    app.run();
 }
 
 #[cfg(feature = "debug")]
 fn maximize(mut window: Single<&mut Window, With<PrimaryWindow>>) {
     window.set_maximized(true);
 }
-
-fn camera(mut commands: Commands) {
-    commands.spawn(Camera2d);
-}
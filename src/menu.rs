@@ -0,0 +1,829 @@
+//! Menus and the [`GameState`].
+//!
+//! `escape` pauses the game, with buttons to resume, restart the level, open
+//! the level select, open the controls or settings, cycle the
+//! [`Difficulty`](crate::difficulty::Difficulty) or quit. `L` opens the level
+//! select directly, which lists the levels in `assets/scenes` with their best
+//! completion time from the [`SaveData`] and their [`ParTimes`]. `escape`
+//! closes either menu.
+//!
+//! The controls menu lists the [`Keybinds`]. Choosing an action waits for the
+//! next key, mouse or gamepad button to bind to it, `escape` cancels. Movement
+//! and aim directions only take keys. `escape` goes back to the pause menu.
+//!
+//! The settings menu changes the volumes and toggles in the [`Settings`].
+//! Left and right step the highlighted row, choosing it steps a volume up and
+//! wraps around, or flips a toggle. `escape` goes back to the pause menu.
+//!
+//! Menu buttons are clicked, or highlighted with the arrow keys or d-pad and
+//! chosen with enter or south.
+//!
+//! Virtual time is paused while a menu is open, which stops the fixed
+//! schedules, and with them input contexts and physics, and tweens.
+
+#[cfg(feature = "debug")]
+use crate::inspector;
+use crate::{
+    daily::Daily,
+    keybinds::{InputButton, Keybinds},
+    level::{Level, level_idents, reset_level},
+    replay::Playback,
+    save::SaveData,
+    settings::Settings,
+    stats::ParTimes,
+};
+use bevy::prelude::*;
+use std::fmt::Write;
+
+pub fn plugin(app: &mut App) {
+    app.init_state::<GameState>()
+        .init_resource::<Rebinding>()
+        .add_systems(OnEnter(GameState::Paused), (pause_time, spawn_pause_menu))
+        .add_systems(OnExit(GameState::Paused), unpause_time)
+        .add_systems(
+            OnEnter(GameState::LevelSelect),
+            (pause_time, spawn_level_select),
+        )
+        .add_systems(OnExit(GameState::LevelSelect), unpause_time)
+        .add_systems(
+            OnEnter(GameState::Controls),
+            (pause_time, spawn_controls_menu),
+        )
+        .add_systems(OnExit(GameState::Controls), (unpause_time, stop_rebinding))
+        .add_systems(
+            OnEnter(GameState::Settings),
+            (pause_time, spawn_settings_menu),
+        )
+        .add_systems(OnExit(GameState::Settings), unpause_time)
+        .add_systems(
+            Update,
+            (
+                toggle_menus,
+                navigate_menu
+                    .run_if(
+                        in_state(GameState::Paused)
+                            .or(in_state(GameState::LevelSelect))
+                            .or(in_state(GameState::Controls))
+                            .or(in_state(GameState::Settings)),
+                    )
+                    .run_if(not_rebinding),
+                choose_pause_action.run_if(in_state(GameState::Paused)),
+                choose_level.run_if(in_state(GameState::LevelSelect)),
+                (choose_rebind.run_if(not_rebinding), capture_rebind)
+                    .chain()
+                    .run_if(in_state(GameState::Controls)),
+                update_binding_texts.run_if(
+                    in_state(GameState::Controls)
+                        .and(resource_changed::<Keybinds>.or(resource_changed::<Rebinding>)),
+                ),
+                (
+                    choose_setting,
+                    step_setting,
+                    update_setting_values.run_if(resource_changed::<Settings>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Settings)),
+            )
+                .chain(),
+        );
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, States)]
+pub enum GameState {
+    #[default]
+    Playing,
+    Paused,
+    LevelSelect,
+    Controls,
+    Settings,
+    /// The inspector is open, see [`crate::inspector`].
+    Editor,
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn toggle_menus(
+    input: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    daily: Option<Res<Daily>>,
+    playback: Option<Res<Playback>>,
+    rebinding: Res<Rebinding>,
+    #[cfg(feature = "debug")] disable_input: Query<&inspector::DisableInput>,
+) {
+    #[cfg(feature = "debug")]
+    if !disable_input.is_empty() {
+        return;
+    }
+    match state.get() {
+        GameState::Playing if input.just_pressed(KeyCode::Escape) => {
+            next_state.set(GameState::Paused);
+        }
+        // Daily runs and replays choose their own levels.
+        GameState::Playing
+            if input.just_pressed(KeyCode::KeyL) && daily.is_none() && playback.is_none() =>
+        {
+            next_state.set(GameState::LevelSelect);
+        }
+        // While rebinding, `escape` cancels instead, see `capture_rebind`.
+        GameState::Controls if input.just_pressed(KeyCode::Escape) && rebinding.0.is_none() => {
+            next_state.set(GameState::Paused);
+        }
+        GameState::Settings if input.just_pressed(KeyCode::Escape) => {
+            next_state.set(GameState::Paused);
+        }
+        GameState::Paused | GameState::LevelSelect if input.just_pressed(KeyCode::Escape) => {
+            next_state.set(GameState::Playing);
+        }
+        _ => {}
+    }
+}
+
+/// Index of the [`MenuButton`] highlighted for keyboard and gamepad input.
+///
+/// Reset whenever a menu opens.
+#[derive(Default, Resource)]
+struct MenuCursor(usize);
+
+#[derive(Component)]
+struct MenuButton;
+
+/// Spawns the root node of a menu, which closes when leaving `state`.
+fn spawn_menu<'a>(commands: &'a mut Commands, state: GameState, title: &str) -> EntityCommands<'a> {
+    let mut menu = commands.spawn((
+        DespawnOnExit(state),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            ..Default::default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        GlobalZIndex(5),
+    ));
+    menu.with_child((
+        Text::new(title),
+        Node {
+            margin: UiRect::bottom(Val::Px(12.0)),
+            ..Default::default()
+        },
+    ));
+    menu
+}
+
+fn menu_button() -> impl Bundle {
+    (
+        MenuButton,
+        Button,
+        Node {
+            width: Val::Px(320.0),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            justify_content: JustifyContent::SpaceBetween,
+            border: UiRect::all(Val::Px(2.0)),
+            ..Default::default()
+        },
+        BorderColor::all(Color::NONE),
+    )
+}
+
+/// Moves the [`MenuCursor`] and presses the highlighted button when chosen.
+fn navigate_menu(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut cursor: ResMut<MenuCursor>,
+    mut buttons: Query<(&mut Interaction, &mut BorderColor), With<MenuButton>>,
+) {
+    let count = buttons.iter().len();
+    if count == 0 {
+        return;
+    }
+
+    let pressed = |key, button| {
+        keys.just_pressed(key) || gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+    };
+    if pressed(KeyCode::ArrowUp, GamepadButton::DPadUp) {
+        cursor.0 = (cursor.0 + count - 1) % count;
+    } else if pressed(KeyCode::ArrowDown, GamepadButton::DPadDown) {
+        cursor.0 = (cursor.0 + 1) % count;
+    }
+    let chosen = pressed(KeyCode::Enter, GamepadButton::South);
+
+    // Hovering moves the cursor too, so only one button is highlighted.
+    if let Some(hovered) = buttons
+        .iter()
+        .position(|(interaction, _)| *interaction == Interaction::Hovered)
+    {
+        cursor.0 = hovered;
+    }
+    for (i, (mut interaction, mut border)) in buttons.iter_mut().enumerate() {
+        let highlighted = i == cursor.0;
+        *border = BorderColor::all(if highlighted {
+            Color::WHITE
+        } else {
+            Color::NONE
+        });
+        // Reset by the UI once the pointer is checked again next frame.
+        if highlighted && chosen {
+            *interaction = Interaction::Pressed;
+        }
+    }
+}
+
+/// A button of the pause menu.
+#[derive(Clone, Copy, Component)]
+enum PauseButton {
+    Resume,
+    Restart,
+    LevelSelect,
+    Controls,
+    Settings,
+    /// Cycles through each [`Difficulty`](crate::difficulty::Difficulty).
+    Difficulty,
+    Quit,
+}
+
+impl PauseButton {
+    const ALL: [Self; 7] = [
+        Self::Resume,
+        Self::Restart,
+        Self::LevelSelect,
+        Self::Controls,
+        Self::Settings,
+        Self::Difficulty,
+        Self::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Resume => "Resume",
+            Self::Restart => "Restart level",
+            Self::LevelSelect => "Level select",
+            Self::Controls => "Controls",
+            Self::Settings => "Settings",
+            Self::Difficulty => "Difficulty",
+            Self::Quit => "Quit",
+        }
+    }
+}
+
+/// Text of the current [`Settings::difficulty`] in the pause menu.
+#[derive(Component)]
+struct DifficultyText;
+
+fn spawn_pause_menu(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(MenuCursor::default());
+    spawn_menu(&mut commands, GameState::Paused, "Paused").with_children(|menu| {
+        for button in PauseButton::ALL {
+            let mut row = menu.spawn((button, menu_button(), children![Text::new(button.label())]));
+            if matches!(button, PauseButton::Difficulty) {
+                row.with_child((
+                    DifficultyText,
+                    Text::new(format!("{:?}", settings.difficulty)),
+                ));
+            }
+        }
+    });
+}
+
+fn choose_pause_action(
+    mut commands: Commands,
+    buttons: Query<(&PauseButton, &Interaction), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings: ResMut<Settings>,
+    mut difficulty_texts: Query<&mut Text, With<DifficultyText>>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some((button, _)) = buttons
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+    match button {
+        PauseButton::Resume => next_state.set(GameState::Playing),
+        PauseButton::Restart => {
+            commands.run_system_cached(reset_level);
+            next_state.set(GameState::Playing);
+        }
+        PauseButton::LevelSelect => next_state.set(GameState::LevelSelect),
+        PauseButton::Controls => next_state.set(GameState::Controls),
+        PauseButton::Settings => next_state.set(GameState::Settings),
+        PauseButton::Difficulty => {
+            settings.difficulty = settings.difficulty.next();
+            info!("setting difficulty to {:?}", settings.difficulty);
+            for mut text in difficulty_texts.iter_mut() {
+                text.0 = format!("{:?}", settings.difficulty);
+            }
+        }
+        PauseButton::Quit => {
+            exit.write(AppExit::Success);
+        }
+    }
+}
+
+/// A level in the level select.
+#[derive(Component)]
+struct LevelButton(String);
+
+fn spawn_level_select(
+    mut commands: Commands,
+    save: Res<SaveData>,
+    par_times: Res<ParTimes>,
+    settings: Res<Settings>,
+    level: Res<Level>,
+) {
+    let levels = level_idents().unwrap_or_else(|err| {
+        error!("failed to list levels: {err}");
+        Vec::new()
+    });
+    let cursor = levels
+        .iter()
+        .position(|ident| *ident == level.0)
+        .unwrap_or_default();
+    commands.insert_resource(MenuCursor(cursor));
+
+    spawn_menu(&mut commands, GameState::LevelSelect, "Levels").with_children(|menu| {
+        for ident in levels {
+            let best = save.completed.get(&ident).copied();
+            let par = par_times.get(&ident, settings.difficulty);
+            let mut times = best.map_or("-".to_string(), |time| format!("{time:.2}s"));
+            if let Some(par) = par {
+                _ = write!(times, " / par {par:.2}s");
+            }
+            // Gold once the best run is under par.
+            let color = match (best, par) {
+                (Some(best), Some(par)) if best <= par => Color::srgb(1.0, 0.8, 0.2),
+                _ => Color::WHITE,
+            };
+            menu.spawn((
+                LevelButton(ident.clone()),
+                menu_button(),
+                children![Text::new(ident), (Text::new(times), TextColor(color))],
+            ));
+        }
+    });
+}
+
+fn choose_level(
+    mut commands: Commands,
+    buttons: Query<(&LevelButton, &Interaction), Changed<Interaction>>,
+    mut level: ResMut<Level>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some((button, _)) = buttons
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+    info!("loading {}", button.0);
+    level.0 = button.0.clone();
+    commands.run_system_cached(reset_level);
+    next_state.set(GameState::Playing);
+}
+
+/// A row of the controls menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+enum Rebind {
+    MoveUp,
+    MoveLeft,
+    MoveDown,
+    MoveRight,
+    AimUp,
+    AimLeft,
+    AimDown,
+    AimRight,
+    Jump,
+    Attack,
+    SecondaryAttack,
+    PickUp,
+    Deflect,
+    Dash,
+    SlowMotion,
+    /// Restores the default [`Keybinds`].
+    Reset,
+}
+
+impl Rebind {
+    const ALL: [Self; 16] = [
+        Self::MoveUp,
+        Self::MoveLeft,
+        Self::MoveDown,
+        Self::MoveRight,
+        Self::AimUp,
+        Self::AimLeft,
+        Self::AimDown,
+        Self::AimRight,
+        Self::Jump,
+        Self::Attack,
+        Self::SecondaryAttack,
+        Self::PickUp,
+        Self::Deflect,
+        Self::Dash,
+        Self::SlowMotion,
+        Self::Reset,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::MoveUp => "Move up",
+            Self::MoveLeft => "Move left",
+            Self::MoveDown => "Move down",
+            Self::MoveRight => "Move right",
+            Self::AimUp => "Aim up",
+            Self::AimLeft => "Aim left",
+            Self::AimDown => "Aim down",
+            Self::AimRight => "Aim right",
+            Self::Jump => "Jump",
+            Self::Attack => "Attack",
+            Self::SecondaryAttack => "Secondary attack",
+            Self::PickUp => "Pick up",
+            Self::Deflect => "Deflect",
+            Self::Dash => "Dash",
+            Self::SlowMotion => "Bullet time",
+            Self::Reset => "Reset to defaults",
+        }
+    }
+
+    fn key(self, keybinds: &mut Keybinds) -> Option<&mut KeyCode> {
+        Some(match self {
+            Self::MoveUp => &mut keybinds.move_keys.up,
+            Self::MoveLeft => &mut keybinds.move_keys.left,
+            Self::MoveDown => &mut keybinds.move_keys.down,
+            Self::MoveRight => &mut keybinds.move_keys.right,
+            Self::AimUp => &mut keybinds.aim_keys.up,
+            Self::AimLeft => &mut keybinds.aim_keys.left,
+            Self::AimDown => &mut keybinds.aim_keys.down,
+            Self::AimRight => &mut keybinds.aim_keys.right,
+            _ => return None,
+        })
+    }
+
+    fn buttons(self, keybinds: &mut Keybinds) -> Option<&mut Vec<InputButton>> {
+        Some(match self {
+            Self::Jump => &mut keybinds.jump,
+            Self::Attack => &mut keybinds.attack,
+            Self::SecondaryAttack => &mut keybinds.secondary_attack,
+            Self::PickUp => &mut keybinds.pick_up,
+            Self::Deflect => &mut keybinds.deflect,
+            Self::Dash => &mut keybinds.dash,
+            Self::SlowMotion => &mut keybinds.slow_motion,
+            _ => return None,
+        })
+    }
+
+    fn binding_label(self, keybinds: &Keybinds) -> String {
+        let buttons = match self {
+            Self::MoveUp => &vec![InputButton::Key(keybinds.move_keys.up)],
+            Self::MoveLeft => &vec![InputButton::Key(keybinds.move_keys.left)],
+            Self::MoveDown => &vec![InputButton::Key(keybinds.move_keys.down)],
+            Self::MoveRight => &vec![InputButton::Key(keybinds.move_keys.right)],
+            Self::AimUp => &vec![InputButton::Key(keybinds.aim_keys.up)],
+            Self::AimLeft => &vec![InputButton::Key(keybinds.aim_keys.left)],
+            Self::AimDown => &vec![InputButton::Key(keybinds.aim_keys.down)],
+            Self::AimRight => &vec![InputButton::Key(keybinds.aim_keys.right)],
+            Self::Jump => &keybinds.jump,
+            Self::Attack => &keybinds.attack,
+            Self::SecondaryAttack => &keybinds.secondary_attack,
+            Self::PickUp => &keybinds.pick_up,
+            Self::Deflect => &keybinds.deflect,
+            Self::Dash => &keybinds.dash,
+            Self::SlowMotion => &keybinds.slow_motion,
+            Self::Reset => return String::new(),
+        };
+        buttons
+            .iter()
+            .map(|button| button.label())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Binds `button`, returns false if this row does not take it.
+    fn rebind(self, keybinds: &mut Keybinds, button: InputButton) -> bool {
+        if let Some(key) = self.key(keybinds) {
+            let InputButton::Key(code) = button else {
+                return false;
+            };
+            *key = code;
+        } else if let Some(buttons) = self.buttons(keybinds) {
+            Keybinds::rebind(buttons, button);
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+/// The [`Rebind`] row waiting for a button, if any.
+#[derive(Default, Resource)]
+struct Rebinding(Option<Rebind>);
+
+fn not_rebinding(rebinding: Res<Rebinding>) -> bool {
+    rebinding.0.is_none()
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+/// Text of the current binding of a [`Rebind`] row.
+#[derive(Component)]
+struct BindingText(Rebind);
+
+fn spawn_controls_menu(mut commands: Commands, keybinds: Res<Keybinds>) {
+    commands.insert_resource(MenuCursor::default());
+    spawn_menu(&mut commands, GameState::Controls, "Controls").with_children(|menu| {
+        for row in Rebind::ALL {
+            menu.spawn((
+                row,
+                menu_button(),
+                children![
+                    Text::new(row.label()),
+                    (BindingText(row), Text::new(row.binding_label(&keybinds))),
+                ],
+            ));
+        }
+    });
+}
+
+fn choose_rebind(
+    buttons: Query<(&Rebind, &Interaction), Changed<Interaction>>,
+    mut keybinds: ResMut<Keybinds>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    let Some((row, _)) = buttons
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+    match row {
+        Rebind::Reset => *keybinds = Keybinds::default(),
+        row => rebinding.0 = Some(*row),
+    }
+}
+
+/// Binds the next button pressed to the [`Rebinding`] row.
+fn capture_rebind(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut keybinds: ResMut<Keybinds>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    // The press that chose the row is not the new binding.
+    if rebinding.is_changed() {
+        return;
+    }
+    let Some(row) = rebinding.0 else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+
+    let Some(button) = keys
+        .get_just_pressed()
+        .map(|key| InputButton::Key(*key))
+        .chain(
+            mouse
+                .get_just_pressed()
+                .map(|button| InputButton::Mouse(*button)),
+        )
+        .chain(gamepads.iter().flat_map(|gamepad| {
+            gamepad
+                .get_just_pressed()
+                .map(|button| InputButton::Gamepad(*button))
+        }))
+        .next()
+    else {
+        return;
+    };
+    if row.rebind(&mut keybinds, button) {
+        rebinding.0 = None;
+    }
+}
+
+fn update_binding_texts(
+    keybinds: Res<Keybinds>,
+    rebinding: Res<Rebinding>,
+    mut texts: Query<(&BindingText, &mut Text)>,
+) {
+    for (BindingText(row), mut text) in texts.iter_mut() {
+        text.0 = if rebinding.0 == Some(*row) {
+            "press a button...".to_string()
+        } else {
+            row.binding_label(&keybinds)
+        };
+    }
+}
+
+/// A row of the settings menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+enum SettingRow {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    UiVolume,
+    MuteOnFocusLoss,
+    Bloom,
+    DamageEffects,
+}
+
+/// Change of a volume per step of a [`SettingRow`].
+const VOLUME_STEP: f32 = 0.1;
+
+impl SettingRow {
+    const ALL: [Self; 7] = [
+        Self::MasterVolume,
+        Self::MusicVolume,
+        Self::SfxVolume,
+        Self::UiVolume,
+        Self::MuteOnFocusLoss,
+        Self::Bloom,
+        Self::DamageEffects,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::MasterVolume => "Master volume",
+            Self::MusicVolume => "Music volume",
+            Self::SfxVolume => "Sound effects volume",
+            Self::UiVolume => "Interface volume",
+            Self::MuteOnFocusLoss => "Mute in background",
+            Self::Bloom => "Bloom",
+            Self::DamageEffects => "Damage effects",
+        }
+    }
+
+    fn volume(self, settings: &Settings) -> Option<f32> {
+        Some(match self {
+            Self::MasterVolume => settings.master_volume,
+            Self::MusicVolume => settings.music_volume,
+            Self::SfxVolume => settings.sfx_volume,
+            Self::UiVolume => settings.ui_volume,
+            _ => return None,
+        })
+    }
+
+    fn volume_mut(self, settings: &mut Settings) -> Option<&mut f32> {
+        Some(match self {
+            Self::MasterVolume => &mut settings.master_volume,
+            Self::MusicVolume => &mut settings.music_volume,
+            Self::SfxVolume => &mut settings.sfx_volume,
+            Self::UiVolume => &mut settings.ui_volume,
+            _ => return None,
+        })
+    }
+
+    fn toggle(self, settings: &mut Settings) -> Option<&mut bool> {
+        Some(match self {
+            Self::MuteOnFocusLoss => &mut settings.mute_on_focus_loss,
+            Self::Bloom => &mut settings.bloom,
+            Self::DamageEffects => &mut settings.damage_effects,
+            _ => return None,
+        })
+    }
+
+    fn value_label(self, settings: &Settings) -> String {
+        if let Some(volume) = self.volume(settings) {
+            return format!("{:.0}%", volume * 100.0);
+        }
+        let on = match self {
+            Self::MuteOnFocusLoss => settings.mute_on_focus_loss,
+            Self::Bloom => settings.bloom,
+            Self::DamageEffects => settings.damage_effects,
+            _ => false,
+        };
+        if on { "On" } else { "Off" }.to_string()
+    }
+
+    /// Moves a volume `steps` of [`VOLUME_STEP`], wrapping around if `wrap`,
+    /// or flips a toggle.
+    fn step(self, settings: &mut Settings, steps: f32, wrap: bool) {
+        if let Some(volume) = self.volume_mut(settings) {
+            // Rounded so that repeated steps land on whole percentages.
+            let stepped = ((*volume + steps * VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+            *volume = if wrap && stepped > 1.0 {
+                0.0
+            } else {
+                stepped.clamp(0.0, 1.0)
+            };
+        } else if let Some(toggle) = self.toggle(settings) {
+            *toggle = !*toggle;
+        }
+    }
+}
+
+/// Text of the current value of a [`SettingRow`].
+#[derive(Component)]
+struct SettingValue(SettingRow);
+
+/// Bar filled up to the volume of a [`SettingRow`].
+#[derive(Component)]
+struct VolumeFill(SettingRow);
+
+/// Width of the bar behind a [`VolumeFill`].
+const VOLUME_BAR_WIDTH: f32 = 80.0;
+
+fn spawn_settings_menu(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(MenuCursor::default());
+    spawn_menu(&mut commands, GameState::Settings, "Settings").with_children(|menu| {
+        for row in SettingRow::ALL {
+            let value = (
+                SettingValue(row),
+                Text::new(row.value_label(&settings)),
+                Node {
+                    margin: UiRect::left(Val::Auto),
+                    ..Default::default()
+                },
+            );
+            let mut button =
+                menu.spawn((row, menu_button(), children![Text::new(row.label()), value]));
+            let Some(volume) = row.volume(&settings) else {
+                continue;
+            };
+            button.with_child((
+                Node {
+                    width: Val::Px(VOLUME_BAR_WIDTH),
+                    height: Val::Px(8.0),
+                    margin: UiRect::left(Val::Px(8.0)),
+                    align_self: AlignSelf::Center,
+                    ..Default::default()
+                },
+                BackgroundColor(Color::WHITE.with_alpha(0.2)),
+                children![(
+                    VolumeFill(row),
+                    Node {
+                        width: Val::Percent(100.0 * volume),
+                        height: Val::Percent(100.0),
+                        ..Default::default()
+                    },
+                    BackgroundColor(Color::WHITE),
+                )],
+            ));
+        }
+    });
+}
+
+fn choose_setting(
+    buttons: Query<(&SettingRow, &Interaction), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some((row, _)) = buttons
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+    else {
+        return;
+    };
+    row.step(&mut settings, 1.0, true);
+}
+
+/// Steps the row under the [`MenuCursor`] with left and right.
+fn step_setting(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    cursor: Res<MenuCursor>,
+    rows: Query<&SettingRow, With<MenuButton>>,
+    mut settings: ResMut<Settings>,
+) {
+    let pressed = |key, button| {
+        keys.just_pressed(key) || gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+    };
+    let steps = if pressed(KeyCode::ArrowLeft, GamepadButton::DPadLeft) {
+        -1.0
+    } else if pressed(KeyCode::ArrowRight, GamepadButton::DPadRight) {
+        1.0
+    } else {
+        return;
+    };
+    if let Some(row) = rows.iter().nth(cursor.0) {
+        row.step(&mut settings, steps, false);
+    }
+}
+
+fn update_setting_values(
+    settings: Res<Settings>,
+    mut texts: Query<(&SettingValue, &mut Text)>,
+    mut fills: Query<(&VolumeFill, &mut Node)>,
+) {
+    for (SettingValue(row), mut text) in texts.iter_mut() {
+        text.0 = row.value_label(&settings);
+    }
+    for (VolumeFill(row), mut node) in fills.iter_mut() {
+        if let Some(volume) = row.volume(&settings) {
+            node.width = Val::Percent(100.0 * volume);
+        }
+    }
+}
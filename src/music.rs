@@ -0,0 +1,268 @@
+use crate::{
+    assets,
+    audio::AudioBus,
+    coop::Partner,
+    damage::Damaged,
+    level::{KillBox, LevelGeometry, MustDestroy},
+    menu::GameState,
+    player::Player,
+    settings::Settings,
+};
+use avian2d::prelude::{ColliderAabb, LinearVelocity};
+use bevy::{audio::Volume, prelude::*};
+use std::fs;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<MusicTrack>()
+        .init_resource::<MusicDuck>()
+        .init_resource::<MusicIntensity>()
+        .add_systems(
+            Update,
+            (
+                level_music,
+                play_music,
+                (duck_music, music_intensity, crossfade_music),
+            )
+                .chain(),
+        )
+        .add_observer(duck_on_damage);
+}
+
+/// Seconds it takes to fade a track fully in or out.
+const CROSSFADE: f32 = 1.5;
+
+/// Volume multiplier while [`MusicDuck`] is active or the game is paused.
+const DUCKED: f32 = 0.25;
+
+/// Music for a level, stored on the [`LevelGeometry`] entity.
+///
+/// Names a file in `assets/music/` without the extension. Intensity layers are
+/// stored next to it as `{track}_1.wav`, `{track}_2.wav`, and so on, a track
+/// without any plays its base stem at full volume. An empty name plays no
+/// music.
+#[derive(Clone, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct LevelMusic(pub String);
+
+impl Default for LevelMusic {
+    fn default() -> Self {
+        Self("calm".to_string())
+    }
+}
+
+/// The track that should be playing.
+///
+/// Changing this crossfades from the current track.
+#[derive(Default, Resource)]
+pub struct MusicTrack(pub Option<String>);
+
+/// Seconds remaining for which the music is ducked.
+#[derive(Default, Resource)]
+pub struct MusicDuck(pub f32);
+
+/// How intense the current gameplay is in `[0, 1]`.
+///
+/// Each intensity layer of a track fades in over its share of the range.
+#[derive(Default, Resource)]
+pub struct MusicIntensity(pub f32);
+
+#[derive(Component)]
+struct Music {
+    track: String,
+    /// `0` is the base stem, intensity layers start at `1`.
+    layer: usize,
+    /// Total number of intensity layers in the track.
+    layers: usize,
+    /// Crossfade volume in `[0, 1]`.
+    fade: f32,
+}
+
+impl Music {
+    fn layer_volume(&self, intensity: f32) -> f32 {
+        if self.layer == 0 {
+            1.0
+        } else {
+            (intensity * self.layers as f32 - (self.layer - 1) as f32).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Tracks in `assets/music`, sorted, without their intensity layers.
+pub fn music_tracks() -> Result<Vec<String>, String> {
+    let dir = assets::path("music");
+    let files = fs::read_dir(&dir)
+        .map_err(|err| format!("{}: {err}", dir.display()))?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            name.strip_suffix(".wav").map(str::to_string)
+        })
+        .collect::<Vec<_>>();
+    let mut tracks = files
+        .iter()
+        .filter(|name| {
+            !name.rsplit_once('_').is_some_and(|(track, layer)| {
+                layer.parse::<usize>().is_ok() && files.iter().any(|file| file == track)
+            })
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    // Directory order is platform specific.
+    tracks.sort();
+    Ok(tracks)
+}
+
+/// Counts the `{track}_{n}.wav` stems in `assets/music/`.
+fn count_layers(track: &str) -> usize {
+    let mut layers = 0;
+    while fs::exists(assets::path(format!("music/{track}_{}.wav", layers + 1))).unwrap_or(false) {
+        layers += 1;
+    }
+    layers
+}
+
+fn level_music(
+    mut track: ResMut<MusicTrack>,
+    levels: Query<Option<&LevelMusic>, Added<LevelGeometry>>,
+) {
+    for music in levels.iter() {
+        let music = music.cloned().unwrap_or_default();
+        let ident = (!music.0.is_empty()).then_some(music.0);
+        if track.0 != ident {
+            track.0 = ident;
+        }
+    }
+}
+
+fn play_music(
+    mut commands: Commands,
+    server: Res<AssetServer>,
+    track: Res<MusicTrack>,
+    music: Query<&Music>,
+) {
+    if !track.is_changed() {
+        return;
+    }
+    let Some(ident) = &track.0 else {
+        return;
+    };
+    if music.iter().any(|music| music.track == *ident) {
+        return;
+    }
+
+    let layers = count_layers(ident);
+    for layer in 0..=layers {
+        let path = match layer {
+            0 => format!("music/{ident}.wav"),
+            layer => format!("music/{ident}_{layer}.wav"),
+        };
+        commands.spawn((
+            Name::new(format!("Music {ident} {layer}")),
+            Music {
+                track: ident.clone(),
+                layer,
+                layers,
+                fade: 0.0,
+            },
+            AudioBus::Music,
+            AudioPlayer::new(server.load(path)),
+            PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+        ));
+    }
+}
+
+/// Ducks the music briefly whenever a player is hurt.
+fn duck_on_damage(
+    damaged: On<Damaged>,
+    players: Query<(), With<Player>>,
+    mut duck: ResMut<MusicDuck>,
+) {
+    if players.contains(damaged.entity) {
+        duck.0 = duck.0.max(1.0);
+    }
+}
+
+fn duck_music(time: Res<Time>, mut duck: ResMut<MusicDuck>) {
+    if duck.0 > 0.0 {
+        duck.0 = (duck.0 - time.delta_secs()).max(0.0);
+    }
+}
+
+/// Blends gameplay signals into [`MusicIntensity`]:
+/// - player speed
+/// - fraction of [`MustDestroy`] keys destroyed this level
+/// - proximity to the nearest [`KillBox`]
+fn music_intensity(
+    time: Res<Time>,
+    mut intensity: ResMut<MusicIntensity>,
+    player: Option<Single<(&GlobalTransform, &LinearVelocity), (With<Player>, Without<Partner>)>>,
+    keys: Query<(), With<MustDestroy>>,
+    killboxes: Query<(&ColliderAabb, &Visibility), With<KillBox>>,
+    new_level: Query<(), Added<LevelGeometry>>,
+    mut total_keys: Local<usize>,
+) {
+    let Some(player) = player else {
+        return;
+    };
+    let (transform, velocity) = player.into_inner();
+    let position = transform.translation().xy();
+
+    let speed = (velocity.length() / 1_500.0).clamp(0.0, 1.0);
+
+    let remaining = keys.iter().count();
+    if !new_level.is_empty() {
+        *total_keys = remaining;
+    }
+    *total_keys = (*total_keys).max(remaining);
+    let keys = if *total_keys == 0 {
+        0.0
+    } else {
+        1.0 - remaining as f32 / *total_keys as f32
+    };
+
+    let danger = killboxes
+        .iter()
+        .filter(|(_, visibility)| **visibility != Visibility::Hidden)
+        .map(|(aabb, _)| position.clamp(aabb.min, aabb.max).distance(position))
+        .min_by(f32::total_cmp)
+        .map(|distance| 1.0 - (distance / 300.0).clamp(0.0, 1.0))
+        .unwrap_or_default();
+
+    let target = speed.max(keys * 0.7).max(danger);
+    intensity.0 = intensity
+        .0
+        .lerp(target, (time.delta_secs() * 2.0).clamp(0.0, 1.0));
+}
+
+fn crossfade_music(
+    mut commands: Commands,
+    time: Res<Time>,
+    track: Res<MusicTrack>,
+    duck: Res<MusicDuck>,
+    intensity: Res<MusicIntensity>,
+    settings: Res<Settings>,
+    state: Option<Res<State<GameState>>>,
+    mut music: Query<(Entity, &mut Music, Option<&mut AudioSink>)>,
+) {
+    let step = time.delta_secs() / CROSSFADE;
+    let paused = state.is_some_and(|state| *state.get() == GameState::Paused);
+    let duck = if duck.0 > 0.0 || paused { DUCKED } else { 1.0 };
+    let volume = AudioBus::Music.volume(&settings) * duck;
+
+    for (entity, mut music, sink) in music.iter_mut() {
+        let playing = track.0.as_ref() == Some(&music.track);
+        if playing {
+            music.fade = (music.fade + step).min(1.0);
+        } else {
+            music.fade = (music.fade - step).max(0.0);
+            if music.fade == 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        }
+
+        if let Some(mut sink) = sink {
+            let layer = music.layer_volume(intensity.0);
+            sink.set_volume(Volume::Linear(music.fade * layer * volume));
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Grid pathfinding over [`Wall`] geometry for ground enemies.
+
+use crate::level::{LevelLoading, Wall};
+use avian2d::prelude::{ColliderAabb, PhysicsSystems};
+use bevy::prelude::*;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<NavGrid>()
+        .add_systems(FixedPostUpdate, build_nav_grid.after(PhysicsSystems::Last));
+}
+
+/// Side length of a [`NavGrid`] cell.
+pub const CELL_SIZE: f32 = 20.0;
+
+/// Cells a jump link may span horizontally.
+const JUMP_WIDTH: i32 = 5;
+
+/// Cells a jump link may climb or drop.
+const JUMP_HEIGHT: i32 = 4;
+
+/// Extra cost of a jump link, so that walking is preferred.
+const JUMP_COST: u32 = 2;
+
+/// Empty cells padded around the level bounds.
+const PADDING: i32 = 2;
+
+/// [`Wall`] colliders rasterized into cells, rebuilt when a level loads.
+///
+/// A cell is walkable when it is empty and the cell below is solid. Walkable
+/// cells link to their neighbours, to the ground below a ledge and to cells
+/// within jumping distance. Assumes gravity pulls down.
+#[derive(Default, Resource)]
+pub struct NavGrid {
+    /// World position of the bottom left corner of cell `(0, 0)`.
+    origin: Vec2,
+    size: IVec2,
+    solid: Vec<bool>,
+}
+
+impl NavGrid {
+    /// Rasterizes the `(min, max)` bounds of each wall.
+    pub fn from_walls(walls: impl IntoIterator<Item = (Vec2, Vec2)>) -> Self {
+        let walls = walls.into_iter().collect::<Vec<_>>();
+        if walls.is_empty() {
+            return Self::default();
+        }
+
+        let (min, max) = walls.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), (wall_min, wall_max)| (min.min(*wall_min), max.max(*wall_max)),
+        );
+        let origin = min - CELL_SIZE * PADDING as f32;
+        let size = ((max - origin) / CELL_SIZE).ceil().as_ivec2() + PADDING;
+        let mut grid = Self {
+            origin,
+            size,
+            solid: vec![false; (size.x * size.y) as usize],
+        };
+
+        for (wall_min, wall_max) in walls {
+            // Cells whose centers lie inside the wall.
+            let start = grid.cell(wall_min + CELL_SIZE * 0.5);
+            let end = grid.cell(wall_max - CELL_SIZE * 0.5);
+            for y in start.y..=end.y {
+                for x in start.x..=end.x {
+                    if let Some(index) = grid.index(IVec2::new(x, y)) {
+                        grid.solid[index] = true;
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// The cell containing `position`.
+    pub fn cell(&self, position: Vec2) -> IVec2 {
+        ((position - self.origin) / CELL_SIZE).floor().as_ivec2()
+    }
+
+    /// The world position at the center of `cell`.
+    pub fn center(&self, cell: IVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * CELL_SIZE
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        (cell.cmpge(IVec2::ZERO).all() && cell.cmplt(self.size).all())
+            .then(|| (cell.y * self.size.x + cell.x) as usize)
+    }
+
+    pub fn is_solid(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some_and(|index| self.solid[index])
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some() && !self.is_solid(cell) && self.is_solid(cell - IVec2::Y)
+    }
+
+    /// The first walkable cell at or below `cell`.
+    fn ground(&self, mut cell: IVec2) -> Option<IVec2> {
+        while cell.y >= 0 {
+            if self.is_solid(cell) {
+                return None;
+            }
+            if self.is_walkable(cell) {
+                return Some(cell);
+            }
+            cell.y -= 1;
+        }
+        None
+    }
+
+    fn links(&self, cell: IVec2, links: &mut Vec<(IVec2, u32)>) {
+        links.clear();
+
+        for dx in [-1, 1] {
+            let next = cell + IVec2::new(dx, 0);
+            if self.is_walkable(next) {
+                links.push((next, 1));
+            } else if !self.is_solid(next)
+                && let Some(ground) = self.ground(next)
+            {
+                links.push((ground, 1 + (cell.y - ground.y) as u32));
+            }
+        }
+
+        let headroom = (1..=JUMP_HEIGHT)
+            .take_while(|dy| !self.is_solid(cell + IVec2::new(0, *dy)))
+            .count() as i32;
+        if headroom == 0 {
+            return;
+        }
+        for dy in -JUMP_HEIGHT..=headroom {
+            for dx in -JUMP_WIDTH..=JUMP_WIDTH {
+                if dx.abs() <= 1 && dy <= 0 {
+                    continue;
+                }
+                let next = cell + IVec2::new(dx, dy);
+                if self.is_walkable(next) {
+                    links.push((next, (dx.abs() + dy.abs()) as u32 + JUMP_COST));
+                }
+            }
+        }
+    }
+
+    /// The shortest route of cell centers from the ground below `from` to the
+    /// ground below `to`, excluding the starting cell.
+    ///
+    /// `None` if either point is not above walkable ground or `to` cannot be
+    /// reached.
+    pub fn path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let start = self.ground(self.cell(from))?;
+        let goal = self.ground(self.cell(to))?;
+        if start == goal {
+            return Some(Vec::new());
+        }
+        let heuristic = |cell: IVec2| (goal - cell).abs().element_sum() as u32;
+
+        let len = self.solid.len();
+        let mut cost = vec![u32::MAX; len];
+        let mut came_from = vec![None; len];
+        let mut open = BinaryHeap::new();
+        let mut links = Vec::new();
+
+        cost[self.index(start)?] = 0;
+        open.push(Reverse((heuristic(start), start.x, start.y)));
+
+        while let Some(Reverse((_, x, y))) = open.pop() {
+            let cell = IVec2::new(x, y);
+            if cell == goal {
+                let mut path = vec![self.center(cell)];
+                let mut current = cell;
+                while let Some(previous) = came_from[self.index(current)?] {
+                    if previous == start {
+                        break;
+                    }
+                    path.push(self.center(previous));
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let current_cost = cost[self.index(cell)?];
+            self.links(cell, &mut links);
+            for &(next, step) in &links {
+                let index = self.index(next)?;
+                let next_cost = current_cost + step;
+                if next_cost < cost[index] {
+                    cost[index] = next_cost;
+                    came_from[index] = Some(cell);
+                    open.push(Reverse((next_cost + heuristic(next), next.x, next.y)));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn build_nav_grid(
+    loading: Res<LevelLoading>,
+    mut stale: Local<bool>,
+    walls: Query<&ColliderAabb, With<Wall>>,
+    mut grid: ResMut<NavGrid>,
+) {
+    if loading.0 {
+        *stale = true;
+        return;
+    }
+    if !*stale {
+        return;
+    }
+    *stale = false;
+
+    let _span = info_span!("build_nav_grid").entered();
+    *grid = NavGrid::from_walls(walls.iter().map(|aabb| (aabb.min, aabb.max)));
+}
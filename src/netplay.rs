@@ -0,0 +1,570 @@
+//! Rollback netplay between two peers over UDP, with the `netplay` feature.
+//!
+//! Started by inserting [`Netplay`], see `--netplay`. Both peers should start
+//! in the same level with the same [`Seed`](crate::Seed), usually in
+//! [`Versus`](crate::versus::Versus) mode. Handle `0` plays the first player
+//! and handle `1` the [`Partner`], who joins on both peers with the level.
+//!
+//! The session starts once the partner has joined, GGRS then waits for the other
+//! peer and takes over the fixed timestep: each rollback frame feeds every
+//! player's [`NetInput`] through their input context, like a
+//! [`Playback`](crate::replay::Playback), and runs [`FixedMain`] once. The
+//! local input is read from a [`LocalInput`] context with the first player's
+//! [`Keybinds`]. When the other peer's input arrives and differs from the
+//! prediction, the components and resources registered in [`plugin`] are
+//! restored and the frames since are simulated again.
+//!
+//! Level resets and changes are not synchronized, a session lasts until
+//! either peer leaves the level.
+//!
+//! Inserting a [`SyncTest`] instead plays both players locally and rolls back
+//! every frame, which finds state that is missing from the rollback.
+
+use crate::{
+    bullet_time::BulletTime,
+    coop::{Coop, Downed, Partner},
+    damage::Health,
+    enemy::{ChaseState, Patrol, PatrolPath, TurretState},
+    keybinds::Keybinds,
+    level::{
+        self, ActiveCheckpoint, DoorOpen, Grappling, InPhysicsVolume, LaserBeam, LaserEmitter,
+        LevelTime, Locked, MovingPlatform, TimedDoor, Ziplining, level_loaded,
+    },
+    physics::PhysicsConfig,
+    player::{
+        Aim, AimVector, AirJumps, AirTime, Attack, BufferedJump, CycleWeapon, Dash, DashCooldown,
+        Dashing, Deflect, DeflectCooldown, Deflecting, ExternalVelocity, GroundNormal, Grounded,
+        InputVelocity, Invulnerable, Jump, JumpImpulse, Jumping, Move, MoveLockout, MoveVector,
+        OnWall, PickUp, Player, SecondaryAttack, SlowMotion, WallSlide, WeaponVelocity,
+        keybind_actions,
+    },
+    replay::Frame,
+    versus::{Round, Score},
+    weapon::{
+        Ammo, Beam, Bullet, BulletLifetime, BulletPool, BulletSlowdown, Burst, Charge, Cooldown,
+        GravityWell, GravityWellShot, Pooled, PrimaryWeapon, ReserveAmmo, Ricochet,
+        SecondaryWeapon, Spent, StickyBombShot, Stuck,
+    },
+};
+use avian2d::prelude::*;
+use bevy::{
+    app::{RunFixedMainLoop, RunFixedMainLoopSystems},
+    input::mouse::MouseMotion,
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_enhanced_input::prelude::*;
+use bevy_ggrs::{
+    LocalInputs, LocalPlayers,
+    ggrs::{PlayerType, SessionBuilder, UdpNonBlockingSocket},
+    prelude::*,
+};
+use bevy_rand::{global::GlobalRng, prelude::WyRand};
+use serde::{Deserialize, Serialize};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    net::SocketAddr,
+};
+
+pub fn plugin(app: &mut App) {
+    let hz = app.world().resource::<PhysicsConfig>().hz;
+    app.add_plugins(GgrsPlugin::<NetConfig>::default())
+        .set_rollback_schedule_fps(hz as usize)
+        // Evaluated once per rollback frame, like the players' actions are
+        // once per fixed step.
+        .add_input_context_to::<ReadInputs, LocalInput>()
+        .init_resource::<LocalFrame>()
+        .configure_sets(
+            RunFixedMainLoop,
+            RunFixedMainLoopSystems::FixedMainLoop.run_if(not(in_session)),
+        )
+        .register_required_components::<level::Serialize, Tracked>()
+        .register_required_components::<Bullet, Tracked>()
+        .add_systems(
+            Startup,
+            join_remote_partner.run_if(resource_exists::<Netplay>.or(resource_exists::<SyncTest>)),
+        )
+        .add_systems(
+            Update,
+            (
+                start_session.run_if(
+                    resource_exists::<Netplay>
+                        .and(not(in_session))
+                        .and(level_loaded),
+                ),
+                start_sync_test.run_if(
+                    resource_exists::<SyncTest>
+                        .and(not(in_session))
+                        .and(level_loaded),
+                ),
+                log_events.run_if(in_session),
+                rebind_local_input.run_if(resource_changed::<Keybinds>),
+            ),
+        )
+        .add_systems(
+            ReadInputs,
+            (
+                sample_aim,
+                sample::<Move, 0>,
+                sample::<Aim, 1>,
+                sample::<Jump, 2>,
+                sample::<Attack, 3>,
+                sample::<SecondaryAttack, 4>,
+                sample::<PickUp, 5>,
+                sample::<Deflect, 6>,
+                sample::<CycleWeapon, 7>,
+                sample::<Dash, 8>,
+                sample::<SlowMotion, 9>,
+                read_local_inputs,
+            )
+                .chain()
+                .after(EnhancedInputSystems::Apply),
+        )
+        .add_systems(
+            GgrsSchedule,
+            (
+                feed_aim,
+                feed::<Move, 0>,
+                feed::<Aim, 1>,
+                feed::<Jump, 2>,
+                feed::<Attack, 3>,
+                feed::<SecondaryAttack, 4>,
+                feed::<PickUp, 5>,
+                feed::<Deflect, 6>,
+                feed::<CycleWeapon, 7>,
+                feed::<Dash, 8>,
+                feed::<SlowMotion, 9>,
+                run_fixed_main,
+            )
+                .chain(),
+        )
+        .add_observer(track)
+        .add_observer(unpool);
+
+    // Everything the fixed schedules change, by module.
+    app.rollback_component_with_clone::<Transform>()
+        .rollback_component_with_clone::<Visibility>()
+        .rollback_component_with_clone::<ChildOf>()
+        .rollback_component_with_clone::<WyRand>()
+        .rollback_component_with_clone::<Position>()
+        .rollback_component_with_clone::<Rotation>()
+        .rollback_component_with_clone::<LinearVelocity>()
+        .rollback_component_with_clone::<AngularVelocity>()
+        .rollback_component_with_clone::<GravityScale>()
+        .rollback_component_with_clone::<LinearDamping>()
+        .rollback_component_with_clone::<CollisionLayers>()
+        .rollback_component_with_clone::<RigidBodyDisabled>()
+        .rollback_component_with_clone::<ColliderDisabled>()
+        .rollback_resource_with_clone::<Time<Fixed>>()
+        .rollback_resource_with_clone::<Gravity>();
+
+    app.rollback_component_with_clone::<ContextActivity<Player>>()
+        .rollback_component_with_clone::<Health>()
+        .rollback_component_with_clone::<AimVector>()
+        .rollback_component_with_clone::<MoveVector>()
+        .rollback_component_with_clone::<Grounded>()
+        .rollback_component_with_clone::<GroundNormal>()
+        .rollback_component_with_clone::<Jumping>()
+        .rollback_component_with_clone::<AirTime>()
+        .rollback_component_with_clone::<BufferedJump>()
+        .rollback_component_with_clone::<AirJumps>()
+        .rollback_component_with_clone::<OnWall>()
+        .rollback_component_with_clone::<WallSlide>()
+        .rollback_component_with_clone::<MoveLockout>()
+        .rollback_component_with_clone::<InputVelocity>()
+        .rollback_component_with_clone::<WeaponVelocity>()
+        .rollback_component_with_clone::<ExternalVelocity>()
+        .rollback_component_with_clone::<Dashing>()
+        .rollback_component_with_clone::<DashCooldown>()
+        .rollback_component_with_clone::<Deflecting>()
+        .rollback_component_with_clone::<DeflectCooldown>()
+        .rollback_component_with_clone::<Invulnerable>()
+        .rollback_component_with_clone::<Downed>()
+        .rollback_component_with_clone::<Score>()
+        .rollback_resource_with_clone::<BulletTime>()
+        .rollback_resource_with_clone::<Round>();
+
+    app.rollback_component_with_clone::<PrimaryWeapon>()
+        .rollback_component_with_clone::<SecondaryWeapon>()
+        .rollback_component_with_clone::<Ammo>()
+        .rollback_component_with_clone::<ReserveAmmo>()
+        .rollback_component_with_clone::<Cooldown>()
+        .rollback_component_with_clone::<Burst>()
+        .rollback_component_with_clone::<Charge>()
+        .rollback_component_with_clone::<Beam>()
+        .rollback_component_with_clone::<BulletLifetime>()
+        .rollback_component_with_clone::<BulletSlowdown>()
+        .rollback_component_with_clone::<Spent>()
+        .rollback_component_with_clone::<Ricochet>()
+        .rollback_component_with_clone::<StickyBombShot>()
+        .rollback_component_with_clone::<Stuck>()
+        .rollback_component_with_clone::<GravityWellShot>()
+        .rollback_component_with_clone::<GravityWell>()
+        .rollback_resource_with_clone::<BulletPool>();
+
+    app.rollback_component_with_clone::<Grappling>()
+        .rollback_component_with_clone::<Ziplining>()
+        .rollback_component_with_clone::<InPhysicsVolume>()
+        .rollback_component_with_clone::<MovingPlatform>()
+        .rollback_component_with_clone::<LaserEmitter>()
+        .rollback_component_with_clone::<LaserBeam>()
+        .rollback_component_with_clone::<TimedDoor>()
+        .rollback_component_with_clone::<DoorOpen>()
+        .rollback_component_with_clone::<Locked>()
+        .rollback_resource_with_clone::<LevelTime>()
+        .rollback_resource_with_clone::<ActiveCheckpoint>();
+
+    app.rollback_component_with_clone::<Patrol>()
+        .rollback_component_with_clone::<PatrolPath>()
+        .rollback_component_with_clone::<ChaseState>()
+        .rollback_component_with_clone::<TurretState>();
+
+    // Compared between the peers, and between the simulations of a
+    // [`SyncTest`], to detect desyncs.
+    app.checksum_component::<Position>(|position| checksum(&position.to_array()))
+        .checksum_component::<LinearVelocity>(|velocity| checksum(&velocity.to_array()))
+        .checksum_component::<Health>(|health| checksum(&[health.0]));
+}
+
+/// Hash of `values` that is the same on every peer.
+fn checksum(values: &[f32]) -> u64 {
+    // Unlike `RandomState`, `DefaultHasher::new` is not seeded per process.
+    let mut hasher = DefaultHasher::new();
+    for value in values {
+        value.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Number of [`Player`] actions in a [`NetInput`].
+const ACTIONS: usize = 10;
+
+/// Frames the local input is delayed by, which hides most rollbacks.
+const INPUT_DELAY: usize = 2;
+
+pub type NetConfig = GgrsConfig<NetInput, SocketAddr>;
+
+/// A netplay session to start with the other peer.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct Netplay {
+    /// Local UDP port.
+    pub port: u16,
+    /// Address of the other peer.
+    pub peer: SocketAddr,
+    /// Player handle of this peer, `0` or `1`.
+    pub handle: usize,
+}
+
+/// A sync test session to start in place of [`Netplay`].
+///
+/// Both players are driven by the local input, and each frame is simulated
+/// again `check_distance` frames later from its snapshot. GGRS reports the
+/// frames whose checksums differ between the two simulations.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct SyncTest {
+    pub check_distance: usize,
+}
+
+/// One player's input over a rollback frame, exchanged between the peers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NetInput {
+    /// [`AimVector`], which includes mouse aim.
+    aim: Frame,
+    /// [`Player`] actions, in the order they are sampled and fed.
+    actions: [Frame; ACTIONS],
+}
+
+/// Input context of the local peer, read into its [`NetInput`].
+#[derive(Component)]
+pub struct LocalInput;
+
+/// Entities snapshotted during a session.
+///
+/// Required by the components of the entities to snapshot, so that each is
+/// only added to the rollback once.
+#[derive(Default, Clone, Copy, Component)]
+struct Tracked;
+
+/// The [`NetInput`] sampled for the next frame.
+#[derive(Default, Resource)]
+struct LocalFrame(NetInput);
+
+pub fn in_session(session: Option<Res<Session<NetConfig>>>) -> bool {
+    session.is_some()
+}
+
+/// The partner joins with the level, so that they and the copies of the first
+/// player's weapons exist before the session starts.
+///
+/// In a [`SyncTest`] they are driven by the local input too.
+fn join_remote_partner(mut coop: ResMut<Coop>) {
+    coop.remote = true;
+}
+
+/// Starts the session once the partner has joined the loaded level.
+fn start_session(
+    mut commands: Commands,
+    netplay: Res<Netplay>,
+    config: Res<PhysicsConfig>,
+    keybinds: Res<Keybinds>,
+    mut coop: ResMut<Coop>,
+    mut pool: ResMut<BulletPool>,
+    jump: Single<&JumpImpulse, (With<Player>, Without<Partner>)>,
+    _partner: Single<(), With<Partner>>,
+    tracked: Query<Entity, Or<(With<Tracked>, With<GlobalRng>)>>,
+) {
+    let session = SessionBuilder::<NetConfig>::new()
+        .with_num_players(2)
+        .with_input_delay(INPUT_DELAY)
+        .with_fps(config.hz as usize)
+        .and_then(|builder| builder.add_player(PlayerType::Local, netplay.handle))
+        .and_then(|builder| {
+            builder.add_player(PlayerType::Remote(netplay.peer), 1 - netplay.handle)
+        })
+        .map_err(|err| err.to_string())
+        .and_then(|builder| {
+            let socket =
+                UdpNonBlockingSocket::bind_to_port(netplay.port).map_err(|err| err.to_string())?;
+            builder
+                .start_p2p_session(socket)
+                .map_err(|err| err.to_string())
+        });
+    let session = match session {
+        Ok(session) => session,
+        Err(err) => {
+            error!("failed to start netplay with {}: {err}", netplay.peer);
+            commands.remove_resource::<Netplay>();
+            coop.remote = false;
+            return;
+        }
+    };
+
+    info!(
+        "netplay on port {} as player {} with {}",
+        netplay.port, netplay.handle, netplay.peer
+    );
+    begin_session(
+        &mut commands,
+        Session::P2P(session),
+        &keybinds,
+        &jump,
+        &mut pool,
+        &tracked,
+    );
+}
+
+/// Starts the [`SyncTest`] once the partner has joined the loaded level.
+fn start_sync_test(
+    mut commands: Commands,
+    sync_test: Res<SyncTest>,
+    config: Res<PhysicsConfig>,
+    keybinds: Res<Keybinds>,
+    mut coop: ResMut<Coop>,
+    mut pool: ResMut<BulletPool>,
+    jump: Single<&JumpImpulse, (With<Player>, Without<Partner>)>,
+    _partner: Single<(), With<Partner>>,
+    tracked: Query<Entity, Or<(With<Tracked>, With<GlobalRng>)>>,
+) {
+    let session = SessionBuilder::<NetConfig>::new()
+        .with_num_players(2)
+        .with_check_distance(sync_test.check_distance)
+        .with_fps(config.hz as usize)
+        .and_then(|builder| builder.add_player(PlayerType::Local, 0))
+        .and_then(|builder| builder.add_player(PlayerType::Local, 1))
+        .and_then(|builder| builder.start_synctest_session());
+    let session = match session {
+        Ok(session) => session,
+        Err(err) => {
+            error!("failed to start the sync test: {err}");
+            commands.remove_resource::<SyncTest>();
+            coop.remote = false;
+            return;
+        }
+    };
+
+    info!(
+        "sync test checking {} frames back",
+        sync_test.check_distance
+    );
+    begin_session(
+        &mut commands,
+        Session::SyncTest(session),
+        &keybinds,
+        &jump,
+        &mut pool,
+        &tracked,
+    );
+}
+
+fn begin_session(
+    commands: &mut Commands,
+    session: Session<NetConfig>,
+    keybinds: &Keybinds,
+    jump: &JumpImpulse,
+    pool: &mut BulletPool,
+    tracked: &Query<Entity, Or<(With<Tracked>, With<GlobalRng>)>>,
+) {
+    commands.insert_resource(session);
+    // Pooled bullets are disabled, so they would be left out of snapshots.
+    for bullet in pool.iter() {
+        commands.entity(bullet).despawn();
+    }
+    *pool = BulletPool::default();
+    for entity in tracked.iter() {
+        commands.entity(entity).add_rollback();
+    }
+    commands.spawn((
+        LocalInput,
+        keybind_actions::<LocalInput>(keybinds, jump.duration),
+    ));
+}
+
+fn log_events(mut session: ResMut<Session<NetConfig>>) {
+    if let Session::P2P(session) = &mut *session {
+        for event in session.events() {
+            info!("netplay: {event:?}");
+        }
+    }
+}
+
+/// Rebuilds the [`LocalInput`] actions with the new bindings.
+fn rebind_local_input(
+    mut commands: Commands,
+    keybinds: Res<Keybinds>,
+    input: Single<Entity, With<LocalInput>>,
+    jump: Single<&JumpImpulse, (With<Player>, Without<Partner>)>,
+) {
+    commands
+        .entity(*input)
+        .despawn_related::<Actions<LocalInput>>()
+        .insert(keybind_actions::<LocalInput>(&keybinds, jump.duration));
+}
+
+/// Snapshots entities spawned during a session.
+fn track(add: On<Add, Tracked>, mut commands: Commands, session: Option<Res<Session<NetConfig>>>) {
+    if session.is_some() {
+        commands.entity(add.entity).add_rollback();
+    }
+}
+
+/// Disabled entities are left out of snapshots, so bullets are despawned
+/// rather than pooled during a session.
+fn unpool(add: On<Add, Pooled>, mut commands: Commands, session: Option<Res<Session<NetConfig>>>) {
+    if session.is_some() {
+        commands.entity(add.entity).remove::<Pooled>();
+    }
+}
+
+/// Aims with the stick, or at the cursor once the mouse moves, like the first
+/// player does offline.
+fn sample_aim(
+    mut local: ResMut<LocalFrame>,
+    netplay: Option<Res<Netplay>>,
+    input: Single<&Actions<LocalInput>>,
+    aim: Query<&ActionValue, With<Action<Aim>>>,
+    window: Option<Single<&Window, With<PrimaryWindow>>>,
+    camera: Option<Single<(&Camera, &GlobalTransform)>>,
+    players: Query<(&GlobalTransform, Has<Partner>), With<Player>>,
+    mut motion: MessageReader<MouseMotion>,
+) {
+    let stick = input
+        .iter()
+        .find_map(|action| aim.get(action).ok())
+        .map_or(Vec2::ZERO, |value| value.as_axis2d());
+    if stick != Vec2::ZERO {
+        local.0.aim = Frame::aim(stick.normalize());
+        return;
+    }
+    if motion.read().last().is_none() {
+        return;
+    }
+    let (Some(window), Some(camera)) = (window, camera) else {
+        return;
+    };
+    // Both players are local in a sync test, the mouse aims for the first.
+    let handle = netplay.map_or(0, |netplay| netplay.handle);
+    let Some((player, _)) = players
+        .iter()
+        .find(|(_, partner)| player_handle(*partner) == handle)
+    else {
+        return;
+    };
+    let (camera, camera_transform) = camera.into_inner();
+    if let Some(Ok(cursor)) = window
+        .cursor_position()
+        .map(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    {
+        let target = cursor - player.translation().xy();
+        local.0.aim = Frame::aim(target.normalize_or(Vec2::X));
+    }
+}
+
+fn sample<A: InputAction, const I: usize>(
+    mut local: ResMut<LocalFrame>,
+    input: Single<&Actions<LocalInput>>,
+    actions: Query<(&ActionState, &ActionValue), With<Action<A>>>,
+) {
+    if let Some((state, value)) = input.iter().find_map(|action| actions.get(action).ok()) {
+        local.0.actions[I] = Frame::new(*state, *value);
+    }
+}
+
+fn read_local_inputs(
+    mut commands: Commands,
+    local: Res<LocalFrame>,
+    local_players: Res<LocalPlayers>,
+) {
+    let inputs = local_players
+        .0
+        .iter()
+        .map(|handle| (*handle, local.0))
+        .collect();
+    commands.insert_resource(LocalInputs::<NetConfig>(inputs));
+}
+
+/// The handle of the peer playing the first player, or the [`Partner`].
+fn player_handle(partner: bool) -> usize {
+    usize::from(partner)
+}
+
+fn feed_aim(
+    inputs: Res<PlayerInputs<NetConfig>>,
+    mut players: Query<(&mut AimVector, Has<Partner>), With<Player>>,
+) {
+    for (mut aim, partner) in players.iter_mut() {
+        let (input, _) = inputs[player_handle(partner)];
+        if input.aim.value() != Vec2::ZERO {
+            aim.0 = input.aim.value();
+        }
+    }
+}
+
+fn feed<A: InputAction, const I: usize>(
+    mut commands: Commands,
+    inputs: Res<PlayerInputs<NetConfig>>,
+    players: Query<(&Actions<Player>, Has<Partner>)>,
+    actions: Query<&ActionValue, With<Action<A>>>,
+) {
+    for (player, partner) in players.iter() {
+        let Some((entity, value)) = player
+            .iter()
+            .find_map(|action| Some((action, actions.get(action).ok()?)))
+        else {
+            continue;
+        };
+        let (input, _) = inputs[player_handle(partner)];
+        commands.entity(entity).insert(input.actions[I].mock(value));
+    }
+}
+
+/// Runs one fixed step per rollback frame, in place of the fixed main loop.
+fn run_fixed_main(world: &mut World) {
+    let mut fixed = world.resource_mut::<Time<Fixed>>();
+    let timestep = fixed.timestep();
+    fixed.advance_by(timestep);
+    let fixed = fixed.as_generic();
+    *world.resource_mut::<Time>() = fixed;
+    world.run_schedule(FixedMain);
+    let virtual_time = world.resource::<Time<Virtual>>().as_generic();
+    *world.resource_mut::<Time>() = virtual_time;
+}
@@ -0,0 +1,41 @@
+use avian2d::prelude::SubstepCount;
+use bevy::prelude::*;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PhysicsConfig>()
+        .add_systems(PreStartup, apply_physics_config)
+        .add_systems(Update, apply_physics_config);
+}
+
+/// Simulation parameters applied to avian and the fixed timestep.
+///
+/// Inserting this resource before the app runs changes the startup values.
+#[derive(Debug, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct PhysicsConfig {
+    /// Fixed timestep frequency that physics and movement run at.
+    pub hz: f64,
+    /// Solver substeps per fixed step.
+    pub substeps: u32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            hz: 64.0,
+            substeps: 6,
+        }
+    }
+}
+
+fn apply_physics_config(
+    config: Res<PhysicsConfig>,
+    mut substeps: ResMut<SubstepCount>,
+    mut time: ResMut<Time<Fixed>>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    substeps.0 = config.substeps.max(1);
+    time.set_timestep_hz(config.hz.max(1.0));
+}
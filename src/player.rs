@@ -1,21 +1,62 @@
-use crate::level::{DebugPickingColor, Layer, Serialize, Wall};
+use crate::{
+    audio::{PlaySfx, Sfx},
+    collision::Layer,
+    coop::{Downed, Partner, RESPAWN_DELAY},
+    damage::{Damaged, Health},
+    juice::Juice,
+    keybinds::Keybinds,
+    level::{
+        ActiveCheckpoint, DebugPickingColor, InPhysicsVolume, Serialize,
+        SerializedColliderConstructor, Wall, rectangle, reset_level, respawn_at_checkpoint,
+    },
+    registry::{register_level_entity, register_serialized},
+    replay::Playback,
+    tuning::Tuning,
+    versus::Versus,
+};
 use avian2d::prelude::*;
 use bevy::{
-    color::palettes::css::ORANGE, input::mouse::MouseMotion, prelude::*, window::PrimaryWindow,
+    color::palettes::css::{ORANGE, SKY_BLUE},
+    input::mouse::MouseMotion,
+    prelude::*,
+    window::PrimaryWindow,
 };
 use bevy_enhanced_input::{prelude::Cancel, prelude::Press, prelude::*};
 use bevy_tween::prelude::EaseKind;
 
 pub fn plugin(app: &mut App) {
-    app.add_input_context::<Player>()
+    // Actions drive physics, so they are evaluated with the fixed timestep.
+    app.add_input_context_to::<FixedPreUpdate, Player>()
         .add_systems(
-            FixedPostUpdate,
-            (grounded, apply_movement)
+            FixedUpdate,
+            (
+                apply_tuning,
+                grounded,
+                wall_contact,
+                jump_timers,
+                dash_timers,
+                apply_movement,
+                wall_slide,
+                deflect_timers,
+                invulnerability,
+            )
                 .chain()
-                .in_set(PhysicsSystems::Last),
+                .in_set(PlayerSet),
+        )
+        .add_systems(
+            Update,
+            (
+                #[cfg(not(feature = "netplay"))]
+                aim_with_mouse_input.run_if(not(resource_exists::<Playback>)),
+                // Netplay reads the mouse into the local peer's input instead.
+                #[cfg(feature = "netplay")]
+                aim_with_mouse_input
+                    .run_if(not(resource_exists::<Playback>).and(not(crate::netplay::in_session))),
+                flip_with_gravity,
+            ),
         )
-        .add_systems(Update, aim_with_mouse_input)
         .add_observer(inject_bindings)
+        .add_observer(land)
         .add_observer(handle_movement)
         .add_observer(stop_movement)
         .add_observer(start_jump)
@@ -23,9 +64,25 @@ pub fn plugin(app: &mut App) {
         .add_observer(cancel_jump)
         .add_observer(end_jump)
         .add_observer(handle_aim)
-        .add_observer(handle_attack);
+        .add_observer(handle_attack::<Attack>)
+        .add_observer(handle_attack::<SecondaryAttack>)
+        .add_observer(start_deflect)
+        .add_observer(start_dash)
+        .add_observer(hurt)
+        .add_observer(killed)
+        .add_observer(double_jump_pickup);
+
+    register_serialized::<Player>(app);
+    register_serialized::<MaxAirJumps>(app);
+    register_level_entity::<DoubleJumpPickup>(app);
 }
 
+/// Player movement, after input and before [`WeaponSet`](crate::weapon::WeaponSet).
+///
+/// See [`crate::game_plugin`] for the full ordering.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct PlayerSet;
+
 /// The player marker component.
 #[derive(Component, Reflect)]
 #[require(
@@ -35,13 +92,15 @@ pub fn plugin(app: &mut App) {
     // Avian Components
     RigidBody::Dynamic,
     LockedAxes::ROTATION_LOCKED,
+    // Physics runs in fixed steps, smooth the rendered transform between them.
+    TransformInterpolation,
     Collider = Self::collider(),
     ShapeCaster = Self::ground_caster(),
     Friction = Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
     Restitution = Restitution::ZERO.with_combine_rule(CoefficientCombine::Min),
     // Bounce???
     // Restitution::PERFECTLY_ELASTIC,
-    CollisionLayers::new(Layer::Player, [Layer::Default, Layer::Wall, Layer::KillBox]),
+    CollisionLayers = Layer::Player.collision_layers(),
     // Input Components
     OrientationMethod,
     MoveVector,
@@ -50,10 +109,28 @@ pub fn plugin(app: &mut App) {
     InputVelocity(300.0),
     WeaponVelocity,
     WeaponVelocityDamp(10.0),
+    ExternalVelocity,
     JumpImpulse {
         impulse_range: Vec2::new(500.0, 700.0),
         duration: 0.2,
     },
+    CoyoteTime(0.1),
+    JumpBuffer(0.1),
+    AirTime,
+    MaxSlope(50f32.to_radians()),
+    GroundNormal,
+    WallSlide { max_fall_speed: 150.0 },
+    WallJump {
+        impulse: 400.0,
+        lockout: 0.15,
+    },
+    DashImpulse {
+        speed: 900.0,
+        duration: 0.15,
+        cooldown: 0.6,
+        invulnerable: true,
+    },
+    Health(PLAYER_HEALTH),
 )]
 #[reflect(Component)]
 pub struct Player;
@@ -76,33 +153,274 @@ impl Player {
     }
 }
 
-#[derive(Component)]
+/// Points the ground caster of each player along [`Gravity`], which is the
+/// ceiling caster while it is flipped.
+pub fn orient_ground_casters(
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    players: Query<Entity, With<Player>>,
+) {
+    for player in players.iter() {
+        if gravity.0.y > 0.0 {
+            commands.entity(player).insert(Player::ceiling_caster());
+        } else {
+            commands.entity(player).insert(Player::ground_caster());
+        }
+    }
+}
+
+fn apply_tuning(
+    tuning: Res<Tuning>,
+    mut players: Query<(
+        Ref<Player>,
+        &mut InputVelocity,
+        &mut WeaponVelocityDamp,
+        &mut JumpImpulse,
+        &mut CoyoteTime,
+        &mut JumpBuffer,
+        &mut MaxSlope,
+        &mut WallSlide,
+        &mut WallJump,
+        &mut DashImpulse,
+    )>,
+) {
+    for (
+        player,
+        mut input_velocity,
+        mut damp,
+        mut jump,
+        mut coyote,
+        mut buffer,
+        mut max_slope,
+        mut slide,
+        mut wall_jump,
+        mut dash,
+    ) in players.iter_mut()
+    {
+        if !tuning.is_changed() && !player.is_added() {
+            continue;
+        }
+        input_velocity.0 = tuning.input_velocity;
+        damp.0 = tuning.weapon_velocity_damp;
+        jump.impulse_range = Vec2::from_array(tuning.jump_impulse_range);
+        jump.duration = tuning.jump_duration;
+        coyote.0 = tuning.coyote_time;
+        buffer.0 = tuning.jump_buffer;
+        max_slope.0 = tuning.max_slope.to_radians();
+        slide.max_fall_speed = tuning.wall_slide_speed;
+        wall_jump.impulse = tuning.wall_jump_impulse;
+        wall_jump.lockout = tuning.wall_jump_lockout;
+        dash.speed = tuning.dash_speed;
+        dash.duration = tuning.dash_duration;
+        dash.cooldown = tuning.dash_cooldown;
+    }
+}
+
+#[derive(Clone, Copy, Component)]
 pub struct Grounded;
 
+/// Steepest ground in radians that the player stands on. Steeper walls are
+/// slid down.
+#[derive(Component)]
+pub struct MaxSlope(pub f32);
+
+/// Surface normal of the ground the player last stood on.
+#[derive(Clone, Copy, Component)]
+pub struct GroundNormal(pub Vec2);
+
+impl Default for GroundNormal {
+    fn default() -> Self {
+        Self(Vec2::Y)
+    }
+}
+
 fn grounded(
     mut commands: Commands,
-    player: Single<(Entity, &ShapeHits, Has<Grounded>), With<Player>>,
+    gravity: Res<Gravity>,
+    mut players: Query<
+        (
+            Entity,
+            &ShapeHits,
+            &MaxSlope,
+            &mut GroundNormal,
+            Has<Grounded>,
+        ),
+        With<Player>,
+    >,
     walls: Query<&Wall>,
 ) {
-    let (entity, hits, has_grounded) = player.into_inner();
-    let is_grounded = hits.iter().any(|data| walls.contains(data.entity));
-    if is_grounded && !has_grounded {
-        commands.entity(entity).insert(Grounded);
-    } else if !is_grounded && has_grounded {
-        commands.entity(entity).remove::<Grounded>();
+    let up = Vec2::Y * -gravity.0.y.signum();
+    for (entity, hits, max_slope, mut ground_normal, has_grounded) in players.iter_mut() {
+        // The flattest ground under the player.
+        let normal = hits
+            .iter()
+            .filter(|data| walls.contains(data.entity))
+            .map(|data| data.normal1)
+            .max_by(|a, b| a.dot(up).total_cmp(&b.dot(up)));
+        let is_grounded = normal.is_some_and(|normal| normal.angle_to(up).abs() <= max_slope.0);
+        if let Some(normal) = normal
+            && is_grounded
+        {
+            ground_normal.0 = normal;
+        }
+        if is_grounded && !has_grounded {
+            commands.entity(entity).insert(Grounded);
+        } else if !is_grounded && has_grounded {
+            commands.entity(entity).remove::<Grounded>();
+        }
+    }
+}
+
+fn land(
+    land: On<Add, Grounded>,
+    mut commands: Commands,
+    velocities: Query<&LinearVelocity>,
+    buffered: Query<(), With<BufferedJump>>,
+    max_air_jumps: Query<&MaxAirJumps>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    sfx.write(PlaySfx::new(Sfx::Land));
+    if let Ok(max) = max_air_jumps.get(land.entity) {
+        commands.entity(land.entity).insert(AirJumps(max.0));
+    }
+    if buffered.contains(land.entity) {
+        commands.entity(land.entity).remove::<BufferedJump>();
+        commands.run_system_cached_with(jump, land.entity);
+    }
+
+    // Only hard landings squash the player.
+    let impact = velocities
+        .get(land.entity)
+        .map_or(0.0, |velocity| velocity.y.abs());
+    if impact > 400.0 {
+        let amount = (impact / 2_000.0).min(0.4);
+        commands.trigger(Juice::squash(land.entity, amount));
+    }
+}
+
+/// Distance from the player's sides at which walls are touched.
+const WALL_PROBE: f32 = 4.0;
+
+/// Side of the wall an airborne player touches, `-1.0` on the left and `1.0`
+/// on the right.
+#[derive(Clone, Copy, Component)]
+pub struct OnWall(pub f32);
+
+fn wall_contact(
+    mut commands: Commands,
+    spatial: SpatialQuery,
+    players: Query<
+        (Entity, &Position, &Collider, Option<&OnWall>),
+        (With<Player>, Without<Grounded>),
+    >,
+    grounded: Query<Entity, (With<Player>, With<Grounded>, With<OnWall>)>,
+    walls: Query<(), With<Wall>>,
+) {
+    for entity in grounded.iter() {
+        commands.entity(entity).remove::<OnWall>();
+    }
+
+    let filter = SpatialQueryFilter::from_mask(Layer::Wall);
+    for (entity, position, collider, on_wall) in players.iter() {
+        // Shrunk vertically so that floors and ceilings are not walls.
+        let mut body = collider.clone();
+        body.set_scale(collider.scale() * Vec2::new(1.0, 0.8), 10);
+        let side = [(Dir2::NEG_X, -1.0), (Dir2::X, 1.0)]
+            .into_iter()
+            .find(|(direction, _)| {
+                spatial
+                    .cast_shape(
+                        &body,
+                        position.0,
+                        0.0,
+                        *direction,
+                        &ShapeCastConfig::from_max_distance(WALL_PROBE),
+                        &filter,
+                    )
+                    .is_some_and(|hit| walls.contains(hit.entity))
+            })
+            .map(|(_, side)| side);
+        match (side, on_wall) {
+            (Some(side), on_wall) if on_wall.is_none_or(|on_wall| on_wall.0 != side) => {
+                commands.entity(entity).insert(OnWall(side));
+            }
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<OnWall>();
+            }
+            _ => {}
+        }
     }
 }
 
+/// Caps the fall speed of a player pushing against the wall they are
+/// [`OnWall`].
+#[derive(Clone, Copy, Component)]
+pub struct WallSlide {
+    pub max_fall_speed: f32,
+}
+
+fn wall_slide(
+    gravity: Res<Gravity>,
+    mut players: Query<(&mut LinearVelocity, &WallSlide, &OnWall, &MoveVector), With<Player>>,
+) {
+    let down = gravity.0.y.signum();
+    for (mut velocity, slide, on_wall, move_vector) in players.iter_mut() {
+        if move_vector.0.x * on_wall.0 > 0.0 && velocity.y * down > slide.max_fall_speed {
+            velocity.y = slide.max_fall_speed * down;
+        }
+    }
+}
+
+/// Jumping while [`OnWall`] pushes the player away from the wall with
+/// `impulse`, ignoring movement input for `lockout` seconds so that holding
+/// towards the wall does not cancel the push.
+#[derive(Component)]
+pub struct WallJump {
+    pub impulse: f32,
+    pub lockout: f32,
+}
+
+/// Seconds left in which movement input is ignored.
+#[derive(Clone, Copy, Component)]
+pub(crate) struct MoveLockout(f32);
+
+fn wall_jump(
+    entity: In<Entity>,
+    mut commands: Commands,
+    mut players: Query<(&mut WeaponVelocity, &WallJump, &OnWall), With<Player>>,
+) {
+    let Ok((mut weapon_velocity, wall_jump, on_wall)) = players.get_mut(*entity) else {
+        return;
+    };
+    // Weapon velocity overrides movement input until it is damped.
+    weapon_velocity.0.x = -on_wall.0 * wall_jump.impulse;
+    commands
+        .entity(*entity)
+        .insert(MoveLockout(wall_jump.lockout));
+    commands.run_system_cached_with(jump, *entity);
+}
+
 /// X-axis velocity applied to the player from input.
-#[derive(Default, Component)]
+#[derive(Default, Clone, Copy, Component)]
 pub struct InputVelocity(pub f32);
 
-#[derive(Default, Component)]
+#[derive(Default, Clone, Copy, Component)]
 pub struct WeaponVelocity(pub Vec2);
 
 #[derive(Component)]
 pub struct WeaponVelocityDamp(pub f32);
 
+/// X-axis velocity applied to the player by
+/// [`WindZone`](crate::level::WindZone)s, added on top of the input or weapon
+/// velocity that [`apply_movement`] otherwise sets.
+///
+/// Damped by [`EXTERNAL_VELOCITY_DAMP`], so a steady force settles at a top
+/// speed.
+#[derive(Default, Clone, Copy, Component)]
+pub struct ExternalVelocity(pub f32);
+
+const EXTERNAL_VELOCITY_DAMP: f32 = 3.0;
+
 #[derive(Component)]
 pub struct JumpImpulse {
     pub impulse_range: Vec2,
@@ -119,12 +437,20 @@ pub enum OrientationMethod {
 fn aim_with_mouse_input(
     window: Single<&Window, With<PrimaryWindow>>,
     camera: Single<(&Camera, &GlobalTransform)>,
-    player: Single<(&mut AimVector, &GlobalTransform, &mut OrientationMethod), With<Player>>,
-    input_ctx: Single<&ContextActivity<Player>>,
+    // The mouse belongs to the first player.
+    player: Single<
+        (
+            &mut AimVector,
+            &GlobalTransform,
+            &mut OrientationMethod,
+            &ContextActivity<Player>,
+        ),
+        (With<Player>, Without<Partner>),
+    >,
     mut motion: MessageReader<MouseMotion>,
 ) {
-    let (mut aim_vector, player_transform, mut orientation) = player.into_inner();
-    if !***input_ctx {
+    let (mut aim_vector, player_transform, mut orientation, input_ctx) = player.into_inner();
+    if !**input_ctx {
         return;
     }
 
@@ -150,20 +476,100 @@ fn aim_with_mouse_input(
     }
 }
 
+fn flip_with_gravity(gravity: Res<Gravity>, mut sprites: Query<&mut Sprite, With<Player>>) {
+    let flip = gravity.0.y > 0.0;
+    for mut sprite in sprites.iter_mut() {
+        if sprite.flip_y != flip {
+            sprite.flip_y = flip;
+        }
+    }
+}
+
+/// Gamepad bindings for a [`Partner`], and [`Keybinds`] for everyone else.
 fn inject_bindings(
     trigger: On<Insert, Player>,
     mut commands: Commands,
-    jump_impulse: Query<&JumpImpulse>,
+    players: Query<(&JumpImpulse, Has<Partner>)>,
+    keybinds: Res<Keybinds>,
 ) -> Result {
-    let jump_impulse = jump_impulse.get(trigger.entity)?;
-    commands.entity(trigger.entity).insert(actions!(Player[
+    let (jump_impulse, partner) = players.get(trigger.entity)?;
+    if partner {
+        // Partners only read their own gamepad, see `GamepadDevice`.
+        commands.entity(trigger.entity).insert(actions!(Player[
+            (
+                Action::<Move>::new(),
+                DeadZone::default(),
+                Bindings::spawn(Axial::left_stick()),
+            ),
+            (
+                Action::<Aim>::new(),
+                DeadZone {
+                    lower_threshold: 0.5,
+                    ..Default::default()
+                },
+                SmoothNudge::new(16.0),
+                Bindings::spawn(Axial::right_stick()),
+            ),
+            (
+                Action::<Jump>::new(),
+                Hold::new(jump_impulse.duration),
+                bindings![GamepadButton::South],
+            ),
+            (
+                Action::<Attack>::new(),
+                bindings![GamepadButton::RightTrigger2],
+            ),
+            (
+                Action::<SecondaryAttack>::new(),
+                bindings![GamepadButton::LeftTrigger2],
+            ),
+            (
+                Action::<PickUp>::new(),
+                Press::default(),
+                bindings![GamepadButton::North],
+            ),
+            (
+                Action::<Deflect>::new(),
+                Press::default(),
+                bindings![GamepadButton::West],
+            ),
+            (
+                Action::<Dash>::new(),
+                Press::default(),
+                bindings![GamepadButton::East],
+            ),
+            (
+                Action::<SlowMotion>::new(),
+                bindings![GamepadButton::LeftThumb],
+            ),
+            (
+                Action::<CycleWeapon>::new(),
+                Press::default(),
+                bindings![
+                    GamepadButton::RightTrigger,
+                    (GamepadButton::LeftTrigger, Negate::all()),
+                ],
+            ),
+        ]));
+        return Ok(());
+    }
+    commands
+        .entity(trigger.entity)
+        .insert(keybind_actions::<Player>(&keybinds, jump_impulse.duration));
+    Ok(())
+}
+
+/// The actions of the context `C` bound to [`Keybinds`], with a [`Jump`] held
+/// for up to `jump_duration`.
+pub(crate) fn keybind_actions<C: Component>(
+    keybinds: &Keybinds,
+    jump_duration: f32,
+) -> impl Bundle {
+    actions!(C[
         (
             Action::<Move>::new(),
             DeadZone::default(),
-            Bindings::spawn((
-                Cardinal::wasd_keys(),
-                Axial::left_stick(),
-            )),
+            keybinds.move_bindings(),
         ),
         (
             Action::<Aim>::new(),
@@ -172,71 +578,135 @@ fn inject_bindings(
                 ..Default::default()
             },
             SmoothNudge::new(16.0),
-            Bindings::spawn((
-                Cardinal::arrows(),
-                Axial::right_stick(),
-            )),
+            keybinds.aim_bindings(),
         ),
         (
             Action::<Jump>::new(),
-            Hold::new(jump_impulse.duration),
-            bindings![KeyCode::Space, KeyCode::ShiftLeft, GamepadButton::South],
+            Hold::new(jump_duration),
+            Bindings::spawn(Keybinds::buttons(&keybinds.jump)),
         ),
         (
             Action::<Attack>::new(),
-            Press::default(),
-            bindings![MouseButton::Left, GamepadButton::RightTrigger2],
+            Bindings::spawn(Keybinds::buttons(&keybinds.attack)),
+        ),
+        (
+            Action::<SecondaryAttack>::new(),
+            Bindings::spawn(Keybinds::buttons(&keybinds.secondary_attack)),
         ),
         (
             Action::<PickUp>::new(),
             Press::default(),
-            bindings![KeyCode::KeyF, KeyCode::Enter, GamepadButton::North],
+            Bindings::spawn(Keybinds::buttons(&keybinds.pick_up)),
         ),
-    ]));
-    Ok(())
+        (
+            Action::<Deflect>::new(),
+            Press::default(),
+            Bindings::spawn(Keybinds::buttons(&keybinds.deflect)),
+        ),
+        (
+            Action::<Dash>::new(),
+            Press::default(),
+            Bindings::spawn(Keybinds::buttons(&keybinds.dash)),
+        ),
+        (
+            Action::<SlowMotion>::new(),
+            Bindings::spawn(Keybinds::buttons(&keybinds.slow_motion)),
+        ),
+        (
+            Action::<CycleWeapon>::new(),
+            Press::default(),
+            bindings![
+                (Binding::mouse_wheel(), SwizzleAxis::YXZ),
+                GamepadButton::RightTrigger,
+                (GamepadButton::LeftTrigger, Negate::all()),
+            ],
+        ),
+    ])
 }
 
 #[derive(InputAction)]
 #[action_output(Vec2)]
 pub struct Move;
 
-#[derive(Default, Component)]
+#[derive(Default, Clone, Copy, Component)]
 pub struct MoveVector(pub Vec2);
 
-fn handle_movement(movement: On<Fire<Move>>, mut player: Single<&mut MoveVector, With<Player>>) {
-    player.0 = movement.value;
+fn handle_movement(movement: On<Fire<Move>>, mut players: Query<&mut MoveVector, With<Player>>) {
+    if let Ok(mut move_vector) = players.get_mut(movement.context) {
+        move_vector.0 = movement.value;
+    }
 }
 
-fn stop_movement(_movement: On<Complete<Move>>, mut player: Single<&mut MoveVector, With<Player>>) {
-    player.0 = Vec2::ZERO;
+fn stop_movement(movement: On<Complete<Move>>, mut players: Query<&mut MoveVector, With<Player>>) {
+    if let Ok(mut move_vector) = players.get_mut(movement.context) {
+        move_vector.0 = Vec2::ZERO;
+    }
 }
 
 fn apply_movement(
     time: Res<Time>,
-    player: Single<
+    gravity: Res<Gravity>,
+    mut players: Query<
         (
             &mut LinearVelocity,
             &mut WeaponVelocity,
             &InputVelocity,
             &WeaponVelocityDamp,
+            &mut ExternalVelocity,
             &MoveVector,
+            &GroundNormal,
+            Has<MoveLockout>,
+            Has<Grounded>,
+            Has<Jumping>,
         ),
         With<Player>,
     >,
 ) {
+    let _span = info_span!("apply_movement").entered();
     let dt = time.delta_secs();
-    let (mut velocity, mut weapon_velocity, input_velocity, damping, move_vector) =
-        player.into_inner();
+    let up = Vec2::Y * -gravity.0.y.signum();
+    for (
+        mut velocity,
+        mut weapon_velocity,
+        input_velocity,
+        damping,
+        mut external_velocity,
+        move_vector,
+        ground_normal,
+        locked,
+        grounded,
+        jumping,
+    ) in players.iter_mut()
+    {
+        weapon_velocity.0 *= 1.0 / (1.0 + damping.0 * dt);
+        external_velocity.0 *= 1.0 / (1.0 + EXTERNAL_VELOCITY_DAMP * dt);
+        let move_x = if locked { 0.0 } else { move_vector.0.x };
+        let input_movement = input_velocity.0 * move_x;
 
-    weapon_velocity.0 *= 1.0 / (1.0 + damping.0 * dt);
-    let input_movement = input_velocity.0 * move_vector.0.x;
-    if weapon_velocity.0.x.abs() < input_velocity.0 && move_vector.0.x != 0.0 {
-        velocity.x = input_movement;
-    } else {
-        velocity.x = weapon_velocity.0.x;
-    }
-    if weapon_velocity.0.y.abs() > 200.0 {
-        velocity.y = weapon_velocity.0.y;
+        // Direction along sloped ground, pointing right.
+        let slope = (grounded && !jumping && ground_normal.0.dot(up) < 0.999).then(|| {
+            let tangent = ground_normal.0.perp();
+            if tangent.x < 0.0 { -tangent } else { tangent }
+        });
+
+        if weapon_velocity.0.x.abs() < input_velocity.0 && move_x != 0.0 {
+            match slope {
+                // Following the ground rather than walking into or off it.
+                Some(tangent) => velocity.0 = tangent * input_movement,
+                None => velocity.x = input_movement,
+            }
+        } else {
+            velocity.x = weapon_velocity.0.x;
+        }
+        velocity.x += external_velocity.0;
+        if weapon_velocity.0.y.abs() > 200.0 {
+            velocity.y = weapon_velocity.0.y;
+        }
+        // Cancels the pull of gravity along the slope, which would otherwise
+        // slide the player down it without friction.
+        if let Some(tangent) = slope {
+            velocity.0 -= tangent * gravity.0.dot(tangent) * dt;
+        }
     }
 }
 
@@ -244,56 +714,229 @@ fn apply_movement(
 #[action_output(bool)]
 pub struct Jump;
 
+#[derive(Clone, Copy, Component)]
+pub(crate) struct Jumping(f32);
+
+/// Seconds after leaving the ground that the player can still jump.
+#[derive(Component)]
+pub struct CoyoteTime(pub f32);
+
+/// Seconds before landing that a [`Jump`] press is remembered and performed
+/// on landing.
 #[derive(Component)]
-struct Jumping(f32);
+pub struct JumpBuffer(pub f32);
+
+/// Seconds since the player was last [`Grounded`] without jumping.
+#[derive(Default, Clone, Copy, Component)]
+pub(crate) struct AirTime(f32);
+
+/// Seconds left of a buffered [`Jump`] press.
+#[derive(Clone, Copy, Component)]
+pub(crate) struct BufferedJump(f32);
+
+fn jump_timers(
+    mut commands: Commands,
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    mut players: Query<(
+        Entity,
+        &mut AirTime,
+        &LinearVelocity,
+        &GroundNormal,
+        Has<Grounded>,
+        Option<&mut BufferedJump>,
+        Option<&mut MoveLockout>,
+    )>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut air_time, velocity, ground_normal, grounded, buffered, lockout) in
+        players.iter_mut()
+    {
+        // Still rising from a jump near the ground does not count as landed.
+        // Walking up a slope moves along the ground rather than away from it.
+        if grounded && velocity.dot(ground_normal.0) <= 0.0 {
+            air_time.0 = 0.0;
+        } else {
+            air_time.0 += dt;
+        }
+        if let Some(mut buffered) = buffered {
+            buffered.0 -= dt;
+            if buffered.0 <= 0.0 {
+                commands.entity(entity).remove::<BufferedJump>();
+            }
+        }
+        if let Some(mut lockout) = lockout {
+            lockout.0 -= dt;
+            if lockout.0 <= 0.0 {
+                commands.entity(entity).remove::<MoveLockout>();
+            }
+        }
+    }
+}
 
 fn start_jump(
-    _jump: On<Start<Jump>>,
+    jump_input: On<Start<Jump>>,
     mut commands: Commands,
-    player: Single<Entity, (With<Player>, With<Grounded>)>,
+    mut players: Query<
+        (
+            Has<Grounded>,
+            Has<OnWall>,
+            &mut AirTime,
+            &CoyoteTime,
+            &JumpBuffer,
+            Option<&mut AirJumps>,
+        ),
+        With<Player>,
+    >,
 ) {
-    commands.entity(*player).insert(Jumping(0.0));
+    let Ok((grounded, on_wall, mut air_time, coyote, buffer, air_jumps)) =
+        players.get_mut(jump_input.context)
+    else {
+        return;
+    };
+    if grounded || air_time.0 < coyote.0 {
+        // Only one jump per stay on the ground.
+        air_time.0 = f32::INFINITY;
+        commands.run_system_cached_with(jump, jump_input.context);
+    } else if on_wall {
+        commands.run_system_cached_with(wall_jump, jump_input.context);
+    } else if let Some(mut air_jumps) = air_jumps.filter(|air_jumps| air_jumps.0 > 0) {
+        air_jumps.0 -= 1;
+        commands.run_system_cached_with(jump, jump_input.context);
+    } else if buffer.0 > 0.0 {
+        commands
+            .entity(jump_input.context)
+            .insert(BufferedJump(buffer.0));
+    }
+}
+
+/// Jumps left before landing, refilled to [`MaxAirJumps`] on landing.
+#[derive(Clone, Copy, Component)]
+pub struct AirJumps(pub u8);
+
+/// Jumps the player can make in the air, granted by [`DoubleJumpPickup`]s.
+#[derive(Default, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct MaxAirJumps(pub u8);
+
+/// Grants an extra [`MaxAirJumps`] when picked up.
+#[derive(Default, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    SerializedColliderConstructor = rectangle(30.0, 30.0),
+    CollisionLayers = Layer::Pickups.collision_layers(),
+    DebugPickingColor::new(SKY_BLUE),
+)]
+#[reflect(Default, Component)]
+pub struct DoubleJumpPickup;
+
+fn double_jump_pickup(
+    pick_up: On<Fire<PickUp>>,
+    mut commands: Commands,
+    players: Query<(&GlobalTransform, Option<&MaxAirJumps>), With<Player>>,
+    pickups: Query<(Entity, &GlobalTransform), With<DoubleJumpPickup>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let radius = 100.0;
+    let Ok((player_transform, max)) = players.get(pick_up.context) else {
+        return;
+    };
+    let player_translation = player_transform.translation().xy();
+    let Some((pickup, transform)) = pickups.iter().find(|(_, transform)| {
+        transform
+            .translation()
+            .xy()
+            .distance_squared(player_translation)
+            < radius * radius
+    }) else {
+        return;
+    };
+    let max = max.map_or(0, |max| max.0).saturating_add(1);
+    commands
+        .entity(pick_up.context)
+        .insert((MaxAirJumps(max), AirJumps(max)));
+    commands.entity(pickup).despawn();
+    sfx.write(PlaySfx::at(Sfx::Click, transform.translation().xy()));
+}
+
+fn jump(
+    entity: In<Entity>,
+    mut commands: Commands,
+    mut players: Query<(&mut LinearVelocity, &JumpImpulse, Option<&InPhysicsVolume>), With<Player>>,
+    gravity: Res<Gravity>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let Ok((mut velocity, jump_impulse, volume)) = players.get_mut(*entity) else {
+        return;
+    };
+    let scale = volume.map_or(1.0, |volume| volume.jump_scale);
+    // A buffered jump may have been released before landing, so it gets at
+    // least the initial impulse.
+    velocity.0.y = jump_impulse.impulse_range.x * scale * gravity.0.signum().y * -1.0;
+    commands.entity(*entity).insert(Jumping(0.0));
+    commands.trigger(Juice::stretch(*entity, 0.3));
+    sfx.write(PlaySfx::new(Sfx::Jump));
 }
 
 fn handle_jump(
-    _jump: On<Ongoing<Jump>>,
-    player: Single<(&mut LinearVelocity, &JumpImpulse, &Jumping), (With<Player>, With<Jumping>)>,
+    jump: On<Ongoing<Jump>>,
+    mut players: Query<
+        (
+            &mut LinearVelocity,
+            &JumpImpulse,
+            &Jumping,
+            Option<&InPhysicsVolume>,
+        ),
+        With<Player>,
+    >,
     gravity: Res<Gravity>,
 ) {
-    let (mut velocity, jump_impulse, duration) = player.into_inner();
+    let Ok((mut velocity, jump_impulse, duration, volume)) = players.get_mut(jump.context) else {
+        return;
+    };
     let t = EaseKind::CubicInOut.sample(duration.0 / jump_impulse.duration);
-    let range = jump_impulse.impulse_range * gravity.0.signum().y * -1.0;
+    let scale = volume.map_or(1.0, |volume| volume.jump_scale);
+    let range = jump_impulse.impulse_range * scale * gravity.0.signum().y * -1.0;
     velocity.0.y = range.x.lerp(range.y, t);
 }
 
 fn cancel_jump(
-    _jump: On<Cancel<Jump>>,
+    jump: On<Cancel<Jump>>,
     mut commands: Commands,
-    player: Single<Entity, (With<Player>, With<Jumping>)>,
+    players: Query<(), (With<Player>, With<Jumping>)>,
 ) {
-    commands.entity(*player).remove::<Jumping>();
+    if players.contains(jump.context) {
+        commands.entity(jump.context).remove::<Jumping>();
+    }
 }
 
 fn end_jump(
-    _jump: On<Fire<Jump>>,
+    jump: On<Fire<Jump>>,
     mut commands: Commands,
-    player: Single<Entity, (With<Player>, With<Jumping>)>,
+    players: Query<(), (With<Player>, With<Jumping>)>,
 ) {
-    commands.entity(*player).remove::<Jumping>();
+    if players.contains(jump.context) {
+        commands.entity(jump.context).remove::<Jumping>();
+    }
 }
 
 #[derive(InputAction)]
 #[action_output(Vec2)]
 pub struct Aim;
 
-#[derive(Default, Component)]
+#[derive(Default, Clone, Copy, Component)]
 pub struct AimVector(pub Vec2);
 
 fn handle_aim(
     aim: On<Fire<Aim>>,
-    player: Single<(&mut AimVector, &mut OrientationMethod), With<Player>>,
+    mut players: Query<(&mut AimVector, &mut OrientationMethod), With<Player>>,
 ) {
-    let (mut aim_vector, mut method) = player.into_inner();
+    let Ok((mut aim_vector, mut method)) = players.get_mut(aim.context) else {
+        return;
+    };
     *method = OrientationMethod::Stick;
 
     let angle = aim.value.normalize_or_zero();
@@ -302,18 +945,329 @@ fn handle_aim(
     }
 }
 
+/// Fires each step while held. Most weapons fire on [`Start`], hold-to-charge
+/// ones on release, see [`Charge`](crate::weapon::Charge).
 #[derive(InputAction)]
 #[action_output(bool)]
 pub struct Attack;
 
-fn handle_attack(
-    _attack: On<Fire<Attack>>,
+/// Fires the [`SecondaryWeapon`](crate::weapon::SecondaryWeapon) like
+/// [`Attack`] fires the primary one.
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct SecondaryAttack;
+
+fn handle_attack<A: InputAction>(
+    attack: On<Start<A>>,
     mut commands: Commands,
-    player: Single<Entity, With<Player>>,
+    players: Query<(), With<Player>>,
 ) {
-    commands.entity(*player).remove::<Jumping>();
+    if players.contains(attack.context) {
+        commands.entity(attack.context).remove::<Jumping>();
+    }
 }
 
 #[derive(InputAction)]
 #[action_output(bool)]
 pub struct PickUp;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct Deflect;
+
+/// Selects the next owned weapon when positive and the previous when negative,
+/// see [`PrimaryWeapon`](crate::weapon::PrimaryWeapon).
+#[derive(InputAction)]
+#[action_output(f32)]
+pub struct CycleWeapon;
+
+/// Seconds the player reflects enemy bullets after pressing [`Deflect`].
+const DEFLECT_WINDOW: f32 = 0.2;
+
+/// Seconds before [`Deflect`] can be used again.
+const DEFLECT_COOLDOWN: f32 = 0.6;
+
+/// Reflects [`EnemyBullet`](crate::enemy::EnemyBullet)s for the remaining
+/// seconds.
+#[derive(Clone, Copy, Component)]
+pub struct Deflecting(pub f32);
+
+#[derive(Clone, Copy, Component)]
+pub(crate) struct DeflectCooldown(f32);
+
+fn start_deflect(
+    deflect: On<Fire<Deflect>>,
+    mut commands: Commands,
+    players: Query<(), (With<Player>, Without<DeflectCooldown>)>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    if !players.contains(deflect.context) {
+        return;
+    }
+    commands.entity(deflect.context).insert((
+        Deflecting(DEFLECT_WINDOW),
+        DeflectCooldown(DEFLECT_COOLDOWN),
+    ));
+    commands.trigger(Juice::stretch(deflect.context, 0.2));
+    sfx.write(PlaySfx::new(Sfx::Click));
+}
+
+fn deflect_timers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<
+        (
+            Entity,
+            Option<&mut Deflecting>,
+            Option<&mut DeflectCooldown>,
+        ),
+        With<Player>,
+    >,
+) {
+    let dt = time.delta_secs();
+    for (entity, deflecting, cooldown) in players.iter_mut() {
+        if let Some(mut deflecting) = deflecting {
+            deflecting.0 -= dt;
+            if deflecting.0 <= 0.0 {
+                commands.entity(entity).remove::<Deflecting>();
+            }
+        }
+        if let Some(mut cooldown) = cooldown {
+            cooldown.0 -= dt;
+            if cooldown.0 <= 0.0 {
+                commands.entity(entity).remove::<DeflectCooldown>();
+            }
+        }
+    }
+}
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct Dash;
+
+/// A [`Dash`] moves the player at `speed` for `duration` seconds, towards
+/// their [`MoveVector`] or else their [`AimVector`], and can be used again
+/// after `cooldown` seconds. `invulnerable` dashes ignore [`Hurt`].
+#[derive(Component)]
+pub struct DashImpulse {
+    pub speed: f32,
+    pub duration: f32,
+    pub cooldown: f32,
+    pub invulnerable: bool,
+}
+
+/// Seconds left of a [`Dash`] and its velocity.
+#[derive(Clone, Copy, Component)]
+pub struct Dashing {
+    pub remaining: f32,
+    pub velocity: Vec2,
+}
+
+#[derive(Clone, Copy, Component)]
+pub(crate) struct DashCooldown(f32);
+
+fn start_dash(
+    dash: On<Fire<Dash>>,
+    mut commands: Commands,
+    players: Query<
+        (&DashImpulse, &MoveVector, &AimVector, Option<&Invulnerable>),
+        (With<Player>, Without<DashCooldown>),
+    >,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let Ok((impulse, move_vector, aim_vector, invulnerable)) = players.get(dash.context) else {
+        return;
+    };
+    let direction = if move_vector.0 != Vec2::ZERO {
+        move_vector.0
+    } else {
+        aim_vector.0
+    };
+    let mut entity = commands.entity(dash.context);
+    entity.insert((
+        Dashing {
+            remaining: impulse.duration,
+            velocity: direction.normalize_or(Vec2::X) * impulse.speed,
+        },
+        DashCooldown(impulse.cooldown),
+    ));
+    entity.remove::<Jumping>();
+    if impulse.invulnerable && invulnerable.is_none_or(|left| left.0 < impulse.duration) {
+        entity.insert(Invulnerable(impulse.duration));
+    }
+    commands.trigger(Juice::stretch(dash.context, 0.3));
+    sfx.write(PlaySfx::new(Sfx::Jump));
+}
+
+/// Slows the game down while held, see [`crate::bullet_time`].
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct SlowMotion;
+
+/// Holds [`Dashing`] players at their dash velocity, through the
+/// [`WeaponVelocity`] so that movement input does not override it.
+fn dash_timers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<
+        (
+            Entity,
+            &mut LinearVelocity,
+            &mut WeaponVelocity,
+            Option<&mut Dashing>,
+            Option<&mut DashCooldown>,
+        ),
+        With<Player>,
+    >,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut velocity, mut weapon_velocity, dashing, cooldown) in players.iter_mut() {
+        if let Some(mut dashing) = dashing {
+            // The dash velocity is damped as usual once it ends.
+            weapon_velocity.0 = dashing.velocity;
+            velocity.0 = dashing.velocity;
+            dashing.remaining -= dt;
+            if dashing.remaining <= 0.0 {
+                commands.entity(entity).remove::<Dashing>();
+            }
+        }
+        if let Some(mut cooldown) = cooldown {
+            cooldown.0 -= dt;
+            if cooldown.0 <= 0.0 {
+                commands.entity(entity).remove::<DashCooldown>();
+            }
+        }
+    }
+}
+
+/// [`Health`] of a player when they spawn or respawn.
+pub const PLAYER_HEALTH: f32 = 3.0;
+
+/// Seconds a [`Hurt`] player ignores further damage.
+const INVULNERABILITY: f32 = 1.0;
+
+/// Speed a [`Hurt`] player is knocked away from the source of the damage.
+const HURT_KNOCKBACK: f32 = 600.0;
+
+/// Triggered on a player to remove `amount` of their [`Health`], knocking
+/// them away from `source`. They are [`Killed`] when it runs out.
+#[derive(EntityEvent)]
+pub struct Hurt {
+    pub entity: Entity,
+    pub amount: f32,
+    pub source: Vec2,
+}
+
+/// Seconds left in which the player ignores [`Hurt`].
+#[derive(Clone, Copy, Component)]
+pub struct Invulnerable(pub f32);
+
+fn hurt(
+    hurt: On<Hurt>,
+    mut commands: Commands,
+    mut players: Query<
+        (&mut Health, &mut WeaponVelocity, &GlobalTransform),
+        (With<Player>, Without<Downed>, Without<Invulnerable>),
+    >,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let Ok((mut health, mut weapon_velocity, transform)) = players.get_mut(hurt.entity) else {
+        return;
+    };
+    health.0 -= hurt.amount;
+    if health.0 <= 0.0 {
+        commands.trigger(Killed {
+            entity: hurt.entity,
+        });
+        return;
+    }
+
+    sfx.write(PlaySfx::new(Sfx::Death));
+    commands.trigger(Damaged {
+        entity: hurt.entity,
+        amount: hurt.amount,
+    });
+    // Weapon velocity overrides movement input until it is damped.
+    let away = (transform.translation().xy() - hurt.source).normalize_or(Vec2::Y);
+    weapon_velocity.0 = away * HURT_KNOCKBACK;
+    commands
+        .entity(hurt.entity)
+        .insert(Invulnerable(INVULNERABILITY));
+}
+
+/// Blinks players while they are [`Invulnerable`].
+fn invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(Entity, &mut Invulnerable, Option<&mut Sprite>), With<Player>>,
+) {
+    for (entity, mut invulnerable, sprite) in players.iter_mut() {
+        invulnerable.0 -= time.delta_secs();
+        let done = invulnerable.0 <= 0.0;
+        if done {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+        if let Some(mut sprite) = sprite {
+            let visible = done || (invulnerable.0 * 10.0) as i32 % 2 == 0;
+            sprite.color.set_alpha(if visible { 1.0 } else { 0.3 });
+        }
+    }
+}
+
+/// Triggered on a player when a hazard kills them, or their [`Health`] runs
+/// out.
+///
+/// The player is [`Downed`] while a partner is still standing or in
+/// [`Versus`] mode, otherwise the level resets.
+#[derive(EntityEvent)]
+pub struct Killed {
+    pub entity: Entity,
+}
+
+fn killed(
+    killed: On<Killed>,
+    mut commands: Commands,
+    standing: Query<Entity, (With<Player>, Without<Downed>)>,
+    versus: Option<Res<Versus>>,
+    checkpoint: Res<ActiveCheckpoint>,
+    mut health: Query<&mut Health>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    if !standing.contains(killed.entity) {
+        return;
+    }
+    sfx.write(PlaySfx::new(Sfx::Death));
+    // Respawned players start over, a reset level spawns them anew.
+    if let Ok(mut health) = health.get_mut(killed.entity) {
+        health.0 = PLAYER_HEALTH;
+    }
+    commands.trigger(Damaged {
+        entity: killed.entity,
+        amount: f32::INFINITY,
+    });
+    if versus.is_some() || standing.iter().any(|entity| entity != killed.entity) {
+        commands.entity(killed.entity).insert((
+            Downed(RESPAWN_DELAY),
+            Visibility::Hidden,
+            ContextActivity::<Player>::INACTIVE,
+        ));
+    } else if checkpoint.0.is_some() {
+        commands.run_system_cached_with(respawn_at_checkpoint, killed.entity);
+    } else {
+        commands.run_system_cached(reset_level);
+    }
+}
+
+/// The player in `players` closest to `position`.
+pub fn nearest_player<'a>(
+    players: impl IntoIterator<Item = (Entity, &'a GlobalTransform)>,
+    position: Vec2,
+) -> Option<(Entity, Vec2)> {
+    players
+        .into_iter()
+        .map(|(entity, transform)| (entity, transform.translation().xy()))
+        .min_by(|(_, a), (_, b)| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+}
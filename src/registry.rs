@@ -0,0 +1,164 @@
+//! Registration API for weapons, level entities and achievements.
+//!
+//! Built-in content goes through the same functions as mods. A mod is a crate
+//! that depends on `shplat` and adds a plugin next to [`crate::game_plugin`]:
+//!
+//! ```ignore
+//! fn my_mod(app: &mut App) {
+//!     shplat::register_weapon::<Railgun>(app);
+//!     shplat::register_level_entity::<Spikes>(app);
+//! }
+//! ```
+//!
+//! With the `mods` feature, mods built as Rust `dylib`s that export their
+//! plugin with [`export_mod!`](crate::export_mod) are also loaded from the
+//! `mods` folder next to the assets at startup, see [`load_mods`]. Rust has no
+//! stable ABI, so they must be built by the same compiler against the same
+//! `shplat`, and share Bevy through `dynamic_linking`, which the feature
+//! enables. WASM modules are not supported.
+
+use bevy::{
+    ecs::component::ComponentId, prelude::*, reflect::GetTypeRegistration, scene::SceneFilter,
+};
+#[cfg(feature = "mods")]
+use std::{fs, path::Path};
+
+/// Everything registered through this module.
+#[derive(Default, Resource)]
+pub struct Registry {
+    /// Components written into level scenes by [`crate::level::serialize_level`].
+    pub components: SceneFilter,
+    /// Weapons in registration order, selectable with the number keys in the editor.
+    pub weapons: Vec<RegisteredWeapon>,
+    /// Entities placeable in the editor.
+    pub level_entities: Vec<String>,
+    pub achievements: Vec<Achievement>,
+}
+
+pub struct RegisteredWeapon {
+    pub name: String,
+    /// The weapon marker component.
+    pub component: ComponentId,
+    pub insert: fn(&mut EntityCommands),
+}
+
+/// Unlocked by triggering [`Unlock`](crate::achievements::Unlock) with its id.
+#[derive(Clone, Copy)]
+pub struct Achievement {
+    /// Stored in the [`SaveData`](crate::save::SaveData), so it must not change.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+fn short_name<T>() -> String {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+/// Writes `C` into level scenes.
+pub fn register_serialized<C: Component + GetTypeRegistration>(app: &mut App) {
+    app.register_type::<C>();
+    let mut registry = app.world_mut().get_resource_or_init::<Registry>();
+    let components = std::mem::take(&mut registry.components);
+    registry.components = components.allow::<C>();
+}
+
+/// Registers a weapon marker component, usually one that requires a
+/// [`WeaponDef`](crate::weapon::WeaponDef).
+pub fn register_weapon<W: Component + Default + GetTypeRegistration>(app: &mut App) {
+    register_serialized::<W>(app);
+    let component = app.world_mut().register_component::<W>();
+    app.world_mut()
+        .get_resource_or_init::<Registry>()
+        .weapons
+        .push(RegisteredWeapon {
+            name: short_name::<W>(),
+            component,
+            insert: |entity| {
+                entity.insert(W::default());
+            },
+        });
+}
+
+/// Registers a component that is placed in levels and edited in the inspector.
+pub fn register_level_entity<C: Component + GetTypeRegistration>(app: &mut App) {
+    register_serialized::<C>(app);
+    #[cfg(feature = "debug")]
+    crate::inspector::editable::<C>(app);
+    app.world_mut()
+        .get_resource_or_init::<Registry>()
+        .level_entities
+        .push(short_name::<C>());
+}
+
+/// Registers an achievement. Its condition is a system or observer that
+/// triggers [`Unlock`](crate::achievements::Unlock).
+pub fn register_achievement(app: &mut App, achievement: Achievement) {
+    app.world_mut()
+        .get_resource_or_init::<Registry>()
+        .achievements
+        .push(achievement);
+}
+
+/// Identifies the build of the game a mod was built against, see
+/// [`export_mod!`](crate::export_mod).
+pub const MOD_VERSION: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+
+/// Exports a plugin as the entry point of a mod loaded by [`load_mods`].
+///
+/// ```ignore
+/// shplat::export_mod!(my_mod);
+/// ```
+#[macro_export]
+macro_rules! export_mod {
+    ($plugin:path) => {
+        #[unsafe(no_mangle)]
+        pub fn shplat_mod_version() -> &'static str {
+            $crate::registry::MOD_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        pub fn shplat_mod(app: &mut ::bevy::app::App) {
+            $plugin(app);
+        }
+    };
+}
+
+/// Adds the plugin of every mod in the `mods` folder, in file name order.
+#[cfg(feature = "mods")]
+pub fn load_mods(app: &mut App) {
+    let dir = crate::assets::root().with_file_name("mods");
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let mut paths = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension() == Some(std::env::consts::DLL_EXTENSION.as_ref()))
+        .collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        match load_mod(&path, app) {
+            Ok(()) => info!("loaded mod {}", path.display()),
+            Err(err) => error!("failed to load mod {}: {err}", path.display()),
+        }
+    }
+}
+
+#[cfg(feature = "mods")]
+fn load_mod(path: &Path, app: &mut App) -> Result<(), String> {
+    // SAFETY: libraries in the mods folder are trusted to be mods, built as
+    // described in the module documentation.
+    let library = unsafe { libloading::Library::new(path) }.map_err(|err| err.to_string())?;
+    let version = *unsafe { library.get::<fn() -> &'static str>(b"shplat_mod_version") }
+        .map_err(|err| err.to_string())?;
+    if version() != MOD_VERSION {
+        return Err(format!("built for {}, expected {MOD_VERSION}", version()));
+    }
+    let plugin =
+        *unsafe { library.get::<fn(&mut App)>(b"shplat_mod") }.map_err(|err| err.to_string())?;
+    plugin(app);
+    // Components, systems and observers of the mod run until the game exits.
+    std::mem::forget(library);
+    Ok(())
+}
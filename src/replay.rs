@@ -0,0 +1,441 @@
+use crate::{
+    Seed,
+    coop::Partner,
+    level::{Level, LevelComplete, LevelGeometry},
+    player::{
+        Aim, AimVector, Attack, CycleWeapon, Dash, Deflect, Jump, Move, PickUp, Player,
+        SecondaryAttack, SlowMotion,
+    },
+};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedFirst,
+        (
+            start_segment,
+            (record_aim, save_segment).run_if(resource_exists::<Recording>),
+            (
+                playback_aim,
+                playback::<Move>,
+                playback::<Aim>,
+                playback::<Jump>,
+                playback::<Attack>,
+                playback::<SecondaryAttack>,
+                playback::<PickUp>,
+                playback::<Deflect>,
+                playback::<CycleWeapon>,
+                playback::<Dash>,
+                playback::<SlowMotion>,
+            )
+                .run_if(resource_exists::<Playback>),
+        )
+            .chain(),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            record::<Move>,
+            record::<Aim>,
+            record::<Jump>,
+            record::<Attack>,
+            record::<SecondaryAttack>,
+            record::<PickUp>,
+            record::<Deflect>,
+            record::<CycleWeapon>,
+            record::<Dash>,
+            record::<SlowMotion>,
+        )
+            .run_if(resource_exists::<Recording>),
+    )
+    .add_systems(
+        FixedLast,
+        (
+            record_position.run_if(resource_exists::<Recording>),
+            advance,
+        )
+            .chain(),
+    )
+    .add_systems(Last, save_on_exit.run_if(resource_exists::<Recording>))
+    .add_observer(save_best_run)
+    .add_observer(playback_next_level);
+}
+
+/// Where the [`Recording`] is written.
+pub const PATH: &str = "replays/last.ron";
+
+/// The player's input over a run.
+///
+/// Replaying it from the same [`Seed`] and starting level reproduces the run,
+/// see [`crate::determinism`].
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub level: String,
+    /// One segment per level load, since loading takes a variable number of steps.
+    pub segments: Vec<Segment>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        ron::from_str(&contents).map_err(|err| format!("{path}: {err}"))
+    }
+}
+
+/// Input from a level load until the next.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub level: String,
+    /// Fixed steps simulated in this segment.
+    pub steps: u32,
+    /// [`AimVector`] at the start of each step, which includes mouse aim.
+    pub aim: Track,
+    /// Input action tracks by action name.
+    pub actions: BTreeMap<String, Track>,
+    /// Player position at the end of each step, for ghosts.
+    #[serde(default)]
+    pub positions: Vec<[f32; 2]>,
+}
+
+/// Recorded values, stored only when they change.
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Track(Vec<(u32, Frame)>);
+
+impl Track {
+    fn push(&mut self, step: u32, frame: Frame) {
+        if self.0.last().map(|(_, last)| *last).unwrap_or_default() != frame {
+            self.0.push((step, frame));
+        }
+    }
+
+    fn get(&self, step: u32) -> Frame {
+        let index = self.0.partition_point(|(changed, _)| *changed <= step);
+        index
+            .checked_sub(1)
+            .map(|index| self.0[index].1)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    state: RecordedState,
+    value: [f32; 2],
+}
+
+impl Frame {
+    pub(crate) fn new(state: ActionState, value: ActionValue) -> Self {
+        Self {
+            state: state.into(),
+            value: value.as_axis2d().to_array(),
+        }
+    }
+
+    pub(crate) fn aim(aim: Vec2) -> Self {
+        Self {
+            state: RecordedState::None,
+            value: aim.to_array(),
+        }
+    }
+
+    pub(crate) fn value(self) -> Vec2 {
+        Vec2::from_array(self.value)
+    }
+
+    /// Mocks the next evaluation of an action whose value is `current`.
+    pub(crate) fn mock(self, current: &ActionValue) -> ActionMock {
+        let value = ActionValue::Axis2D(self.value()).convert(current.dim());
+        ActionMock::once(self.state.into(), value)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum RecordedState {
+    #[default]
+    None,
+    Ongoing,
+    Fired,
+}
+
+impl From<ActionState> for RecordedState {
+    fn from(state: ActionState) -> Self {
+        match state {
+            ActionState::None => Self::None,
+            ActionState::Ongoing => Self::Ongoing,
+            ActionState::Fired => Self::Fired,
+        }
+    }
+}
+
+impl From<RecordedState> for ActionState {
+    fn from(state: RecordedState) -> Self {
+        match state {
+            RecordedState::None => Self::None,
+            RecordedState::Ongoing => Self::Ongoing,
+            RecordedState::Fired => Self::Fired,
+        }
+    }
+}
+
+fn action_name<A: InputAction>() -> String {
+    let name = std::any::type_name::<A>();
+    name.rsplit("::").next().unwrap_or(name).to_string()
+}
+
+/// Records the player's input for this session into [`PATH`].
+#[derive(Default, Resource)]
+pub struct Recording {
+    replay: Replay,
+    step: u32,
+}
+
+impl Recording {
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    fn segment(&mut self) -> Option<&mut Segment> {
+        self.replay.segments.last_mut()
+    }
+}
+
+/// Feeds a [`Replay`] through the player's input context instead of the
+/// player's own input.
+#[derive(Resource)]
+pub struct Playback {
+    replay: Replay,
+    segment: Option<usize>,
+    step: u32,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            segment: None,
+            step: 0,
+        }
+    }
+
+    fn segment(&self) -> Option<&Segment> {
+        self.segment
+            .and_then(|index| self.replay.segments.get(index))
+    }
+}
+
+fn start_segment(
+    mut commands: Commands,
+    new_level: Query<(), Added<LevelGeometry>>,
+    level: Res<Level>,
+    seed: Res<Seed>,
+    recording: Option<ResMut<Recording>>,
+    playback: Option<ResMut<Playback>>,
+) {
+    if new_level.is_empty() {
+        return;
+    }
+
+    if let Some(mut recording) = recording {
+        if recording.replay.segments.is_empty() {
+            recording.replay.seed = seed.0;
+            recording.replay.level = level.0.clone();
+        }
+        recording.replay.segments.push(Segment {
+            level: level.0.clone(),
+            ..Default::default()
+        });
+        recording.step = 0;
+    }
+
+    if let Some(mut playback) = playback {
+        let index = playback.segment.map_or(0, |index| index + 1);
+        playback.segment = Some(index);
+        playback.step = 0;
+        match playback.segment() {
+            None => {
+                info!("replay finished");
+                commands.remove_resource::<Playback>();
+            }
+            Some(segment) if segment.level != level.0 => {
+                warn!(
+                    "replay desynced: expected {}, loaded {}",
+                    segment.level, level.0
+                );
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+fn advance(recording: Option<ResMut<Recording>>, playback: Option<ResMut<Playback>>) {
+    if let Some(mut recording) = recording {
+        recording.step += 1;
+        let step = recording.step;
+        if let Some(segment) = recording.segment() {
+            segment.steps = step;
+        }
+    }
+    if let Some(mut playback) = playback {
+        playback.step += 1;
+    }
+}
+
+/// The first player, the only one recorded and played back.
+type First = (With<Player>, Without<Partner>);
+
+fn record_aim(mut recording: ResMut<Recording>, aim: Single<&AimVector, First>) {
+    let step = recording.step;
+    if let Some(segment) = recording.segment() {
+        segment.aim.push(step, Frame::aim(aim.0));
+    }
+}
+
+fn record_position(mut recording: ResMut<Recording>, player: Single<&Transform, First>) {
+    if let Some(segment) = recording.segment() {
+        segment.positions.push(player.translation.xy().to_array());
+    }
+}
+
+fn record<A: InputAction>(
+    mut recording: ResMut<Recording>,
+    player: Single<&Actions<Player>, First>,
+    actions: Query<(&ActionState, &ActionValue), With<Action<A>>>,
+) {
+    let Some((state, value)) = player.iter().find_map(|action| actions.get(action).ok()) else {
+        return;
+    };
+    let step = recording.step;
+    if let Some(segment) = recording.segment() {
+        segment
+            .actions
+            .entry(action_name::<A>())
+            .or_default()
+            .push(step, Frame::new(*state, *value));
+    }
+}
+
+fn playback_aim(playback: Res<Playback>, mut aim: Single<&mut AimVector, First>) {
+    if let Some(segment) = playback.segment() {
+        aim.0 = segment.aim.get(playback.step).value();
+    }
+}
+
+fn playback<A: InputAction>(
+    mut commands: Commands,
+    playback: Res<Playback>,
+    player: Single<&Actions<Player>, First>,
+    actions: Query<&ActionValue, With<Action<A>>>,
+) {
+    let Some(segment) = playback.segment() else {
+        return;
+    };
+    let Some((entity, value)) = player
+        .iter()
+        .find_map(|action| Some((action, actions.get(action).ok()?)))
+    else {
+        return;
+    };
+    let frame = segment
+        .actions
+        .get(&action_name::<A>())
+        .map(|track| track.get(playback.step))
+        .unwrap_or_default();
+    commands.entity(entity).insert(frame.mock(value));
+}
+
+/// Follows the recorded level order rather than the door, which may have been
+/// redirected, for example by [`crate::daily`].
+fn playback_next_level(
+    _: On<LevelComplete>,
+    playback: Option<Res<Playback>>,
+    mut level: ResMut<Level>,
+) {
+    if let Some(next) = playback
+        .as_ref()
+        .and_then(|playback| playback.replay.segments.get(playback.segment? + 1))
+    {
+        level.0 = next.level.clone();
+    }
+}
+
+/// Writes the recording whenever a segment completes.
+fn save_segment(recording: Res<Recording>, new_level: Query<(), Added<LevelGeometry>>) {
+    if new_level.is_empty() || recording.replay.segments.len() < 2 {
+        return;
+    }
+    let Some(serialized) = serialize(&recording.replay) else {
+        return;
+    };
+    IoTaskPool::get()
+        .spawn(async move { write(serialized) })
+        .detach();
+}
+
+fn save_on_exit(recording: Res<Recording>, mut exit: MessageReader<AppExit>) {
+    if exit.read().last().is_some()
+        && let Some(serialized) = serialize(&recording.replay)
+    {
+        write(serialized);
+    }
+}
+
+fn serialize(replay: &Replay) -> Option<String> {
+    ron::to_string(replay)
+        .inspect_err(|err| error!("failed to serialize replay: {err}"))
+        .ok()
+}
+
+fn write(serialized: String) {
+    if let Err(err) = fs::create_dir_all("replays").and_then(|_| fs::write(PATH, serialized)) {
+        error!("failed to write {PATH}: {err}");
+    }
+}
+
+fn best_run_path(level: &str) -> String {
+    format!("replays/best/{level}.ron")
+}
+
+/// The fastest completed run through `level`, if any.
+pub fn best_run(level: &str) -> Option<Segment> {
+    let contents = fs::read_to_string(best_run_path(level)).ok()?;
+    ron::from_str(&contents)
+        .inspect_err(|err| warn!("failed to parse best run for {level}: {err}"))
+        .ok()
+}
+
+fn save_best_run(complete: On<LevelComplete>, recording: Option<Res<Recording>>) {
+    let Some(segment) = recording
+        .as_ref()
+        .and_then(|recording| recording.replay.segments.last())
+        .filter(|segment| segment.level == complete.level)
+    else {
+        return;
+    };
+    if best_run(&complete.level).is_some_and(|best| best.steps <= segment.steps) {
+        return;
+    }
+
+    info!(
+        "new best run through {}: {:.2}s",
+        complete.level, complete.time
+    );
+    let Ok(serialized) =
+        ron::to_string(segment).inspect_err(|err| error!("failed to serialize best run: {err}"))
+    else {
+        return;
+    };
+    let path = best_run_path(&complete.level);
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(err) =
+                fs::create_dir_all("replays/best").and_then(|_| fs::write(&path, serialized))
+            {
+                error!("failed to write {path}: {err}");
+            }
+        })
+        .detach();
+}
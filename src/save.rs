@@ -0,0 +1,118 @@
+use crate::{
+    assets,
+    daily::{Daily, DailyResult},
+    level::{Level, LevelComplete, level_path},
+    replay::Playback,
+    stats::Stats,
+    weapon::WeaponPickedUp,
+};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
+
+pub fn plugin(app: &mut App) {
+    let save = SaveData::load();
+    // `--level`, daily runs and replays insert their own level afterwards.
+    if let Some(level) = &save.last_level
+        && fs::exists(assets::path(level_path(level))).unwrap_or(false)
+    {
+        info!("continuing from {level}");
+        app.insert_resource(Level(level.clone()));
+    }
+    app.insert_resource(save)
+        .add_systems(Update, record_level.run_if(resource_changed::<Level>))
+        .add_systems(Last, save_progress)
+        .add_observer(record_completion)
+        .add_observer(record_weapon);
+}
+
+const PATH: &str = "save.ron";
+
+/// Player progress.
+///
+/// Loaded from and saved to `save.ron` in the working directory.
+#[derive(Default, Resource, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    /// Ids of unlocked [`Achievement`](crate::registry::Achievement)s.
+    pub achievements: BTreeSet<String>,
+    pub stats: Stats,
+    /// Daily challenge attempts by date.
+    pub daily: BTreeMap<String, DailyResult>,
+    /// Best completion time in seconds by level ident.
+    pub completed: BTreeMap<String, f64>,
+    /// Names of the weapons that have been picked up.
+    pub weapons: BTreeSet<String>,
+    /// Ident of the level the game starts in.
+    pub last_level: Option<String>,
+}
+
+impl SaveData {
+    fn load() -> Self {
+        match fs::read_to_string(PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("failed to parse {PATH}: {err}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+fn record_completion(complete: On<LevelComplete>, mut save: ResMut<SaveData>) {
+    let best = save
+        .completed
+        .entry(complete.level.clone())
+        .or_insert(f64::INFINITY);
+    *best = best.min(complete.time);
+}
+
+fn record_weapon(picked_up: On<WeaponPickedUp>, mut save: ResMut<SaveData>) {
+    if !save.weapons.contains(&picked_up.weapon) {
+        save.weapons.insert(picked_up.weapon.clone());
+    }
+}
+
+fn record_level(
+    level: Res<Level>,
+    mut save: ResMut<SaveData>,
+    daily: Option<Res<Daily>>,
+    playback: Option<Res<Playback>>,
+) {
+    // Daily runs and replays do not choose where the game continues.
+    if daily.is_some() || playback.is_some() {
+        return;
+    }
+    if save.last_level.as_ref() != Some(&level.0) {
+        save.last_level = Some(level.0.clone());
+    }
+}
+
+pub fn save_progress(save: Res<SaveData>, mut exit: MessageReader<AppExit>) {
+    if !save.is_changed() || save.is_added() {
+        return;
+    }
+    let serialized = match ron::ser::to_string_pretty(&*save, default()) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            error!("failed to serialize save data: {err}");
+            return;
+        }
+    };
+    // The app would not wait for the task.
+    if exit.read().last().is_some() {
+        write(serialized);
+        return;
+    }
+    IoTaskPool::get()
+        .spawn(async move { write(serialized) })
+        .detach();
+}
+
+fn write(serialized: String) {
+    if let Err(err) = fs::write(PATH, serialized) {
+        error!("failed to write {PATH}: {err}");
+    }
+}
@@ -0,0 +1,323 @@
+//! Level scripting with [Rhai](https://rhai.rs).
+//!
+//! Scripts are stored with the level in a [`Script`] component, compiled once
+//! when it is inserted, and run when the player enters the [`TriggerZone`] on
+//! the same entity. They can not touch the
+//! world directly, only queue [`ScriptCommand`]s through this API:
+//! - `show_text(text)`
+//! - `lock(door_name)` / `unlock(door_name)`
+//! - `move_to(name, x, y)`
+//! - `despawn(name)`
+//! - `spawn(kind, x, y)` where `kind` is `wall` or `killbox`
+//! - `flip_gravity()`
+
+use crate::{
+    collision::Layer,
+    level::{
+        DebugPickingColor, DespawnOnLevelUnload, Door, KillBox, LevelGeometry, Locked,
+        NeedsSerializedCollider, Serialize, Wall, rectangle,
+    },
+    player::{Player, orient_ground_casters},
+    registry::{register_level_entity, register_serialized},
+};
+use avian2d::prelude::{
+    CollisionEventsEnabled, CollisionLayers, CollisionStart, Gravity, RigidBody, Sensor,
+};
+use bevy::{color::palettes::css::TEAL, prelude::*};
+use rhai::{AST, Dynamic, Engine, module_resolvers::DummyModuleResolver};
+use std::sync::{Arc, Mutex};
+
+pub fn plugin(app: &mut App) {
+    let queue = ScriptQueue::default();
+    app.insert_resource(ScriptEngine(engine(&queue)))
+        .insert_resource(queue)
+        .add_systems(Update, script_text)
+        .add_observer(compile_script)
+        .add_observer(trigger_zone);
+
+    register_serialized::<Script>(app);
+    register_level_entity::<TriggerZone>(app);
+}
+
+/// Rhai source run by a [`TriggerZone`].
+#[derive(Default, Clone, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct Script(pub String);
+
+/// The compiled [`Script`] on the same entity.
+#[derive(Component)]
+struct CompiledScript(AST);
+
+/// Runs the [`Script`] on this entity when the player enters it.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Script,
+    RigidBody::Static,
+    Sensor,
+    CollisionEventsEnabled,
+    CollisionLayers = Layer::Trigger.collision_layers(),
+    DebugPickingColor::new(TEAL),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct TriggerZone {
+    /// Despawn the zone after its script runs.
+    pub once: bool,
+}
+
+/// A world change requested by a script.
+#[derive(Debug)]
+pub enum ScriptCommand {
+    ShowText(String),
+    SetLocked { door: String, locked: bool },
+    MoveTo { name: String, position: Vec2 },
+    Despawn(String),
+    Spawn { kind: String, position: Vec2 },
+    FlipGravity,
+}
+
+/// Upper bound on the work a single script run may do.
+const MAX_OPERATIONS: u64 = 10_000;
+/// Upper bound on the length of any string a script builds.
+const MAX_STRING_SIZE: usize = 1_024;
+/// Upper bound on the length of any array or map a script builds.
+const MAX_COLLECTION_SIZE: usize = 256;
+/// Upper bound on how deep script functions may recurse.
+const MAX_CALL_LEVELS: usize = 16;
+
+#[derive(Resource)]
+struct ScriptEngine(Engine);
+
+/// Commands queued by the script that is currently running.
+#[derive(Default, Clone, Resource)]
+struct ScriptQueue(Arc<Mutex<Vec<ScriptCommand>>>);
+
+impl ScriptQueue {
+    fn push(&self, command: ScriptCommand) {
+        self.0.lock().unwrap().push(command);
+    }
+
+    fn drain(&self) -> Vec<ScriptCommand> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+/// Accepts both integer and float script arguments.
+fn number(value: Dynamic) -> f32 {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|int| int as f64))
+        .unwrap_or_default() as f32
+}
+
+fn engine(queue: &ScriptQueue) -> Engine {
+    let mut engine = Engine::new();
+    // Scripts ship inside level files, so they may not `import` files or
+    // grow without bound.
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_string_size(MAX_STRING_SIZE)
+        .set_max_array_size(MAX_COLLECTION_SIZE)
+        .set_max_map_size(MAX_COLLECTION_SIZE)
+        .set_max_call_levels(MAX_CALL_LEVELS);
+
+    let q = queue.clone();
+    engine.register_fn("show_text", move |text: &str| {
+        q.push(ScriptCommand::ShowText(text.to_string()));
+    });
+    let q = queue.clone();
+    engine.register_fn("lock", move |door: &str| {
+        q.push(ScriptCommand::SetLocked {
+            door: door.to_string(),
+            locked: true,
+        });
+    });
+    let q = queue.clone();
+    engine.register_fn("unlock", move |door: &str| {
+        q.push(ScriptCommand::SetLocked {
+            door: door.to_string(),
+            locked: false,
+        });
+    });
+    let q = queue.clone();
+    engine.register_fn("move_to", move |name: &str, x: Dynamic, y: Dynamic| {
+        q.push(ScriptCommand::MoveTo {
+            name: name.to_string(),
+            position: Vec2::new(number(x), number(y)),
+        });
+    });
+    let q = queue.clone();
+    engine.register_fn("despawn", move |name: &str| {
+        q.push(ScriptCommand::Despawn(name.to_string()));
+    });
+    let q = queue.clone();
+    engine.register_fn("spawn", move |kind: &str, x: Dynamic, y: Dynamic| {
+        q.push(ScriptCommand::Spawn {
+            kind: kind.to_string(),
+            position: Vec2::new(number(x), number(y)),
+        });
+    });
+    let q = queue.clone();
+    engine.register_fn("flip_gravity", move || {
+        q.push(ScriptCommand::FlipGravity);
+    });
+
+    engine
+}
+
+fn compile_script(
+    insert: On<Insert, Script>,
+    mut commands: Commands,
+    scripts: Query<&Script>,
+    engine: Res<ScriptEngine>,
+) {
+    let Ok(script) = scripts.get(insert.entity) else {
+        return;
+    };
+    match engine.0.compile(&script.0) {
+        Ok(ast) => {
+            commands.entity(insert.entity).insert(CompiledScript(ast));
+        }
+        Err(err) => {
+            error!("script error: {err}");
+            commands.entity(insert.entity).remove::<CompiledScript>();
+        }
+    }
+}
+
+fn trigger_zone(
+    start: On<CollisionStart>,
+    mut commands: Commands,
+    players: Query<(), With<Player>>,
+    zones: Query<(&TriggerZone, Option<&CompiledScript>)>,
+    engine: Res<ScriptEngine>,
+    queue: Res<ScriptQueue>,
+) {
+    if !players.contains(start.collider2) {
+        return;
+    }
+    let Ok((zone, script)) = zones.get(start.collider1) else {
+        return;
+    };
+
+    if let Some(script) = script
+        && let Err(err) = engine.0.run_ast(&script.0)
+    {
+        error!("script error: {err}");
+    }
+    let queued = queue.drain();
+    commands.queue(move |world: &mut World| {
+        for command in queued {
+            apply(world, command);
+        }
+    });
+
+    if zone.once {
+        commands.entity(start.collider1).despawn();
+    }
+}
+
+fn named(world: &mut World, name: &str) -> Option<Entity> {
+    world
+        .query::<(Entity, &Name)>()
+        .iter(world)
+        .find(|(_, entity_name)| entity_name.as_str() == name)
+        .map(|(entity, _)| entity)
+}
+
+fn apply(world: &mut World, command: ScriptCommand) {
+    match command {
+        ScriptCommand::ShowText(text) => {
+            world.spawn((
+                DespawnOnLevelUnload,
+                ScriptText(Timer::from_seconds(3.0, TimerMode::Once)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                children![(
+                    Text::new(text),
+                    TextLayout::new_with_justify(Justify::Center)
+                )],
+            ));
+        }
+        ScriptCommand::SetLocked { door, locked } => {
+            let Some(entity) =
+                named(world, &door).filter(|entity| world.get::<Door>(*entity).is_some())
+            else {
+                warn!("script: no door named {door}");
+                return;
+            };
+            if locked {
+                world.entity_mut(entity).insert(Locked);
+            } else {
+                world.entity_mut(entity).remove::<Locked>();
+            }
+        }
+        ScriptCommand::MoveTo { name, position } => {
+            let Some(entity) = named(world, &name) else {
+                warn!("script: no entity named {name}");
+                return;
+            };
+            if let Some(mut transform) = world.get_mut::<Transform>(entity) {
+                transform.translation = position.extend(transform.translation.z);
+            }
+        }
+        ScriptCommand::Despawn(name) => match named(world, &name) {
+            Some(entity) => {
+                world.despawn(entity);
+            }
+            None => warn!("script: no entity named {name}"),
+        },
+        ScriptCommand::Spawn { kind, position } => {
+            let Some(level_geometry) = world
+                .query_filtered::<Entity, With<LevelGeometry>>()
+                .iter(world)
+                .next()
+            else {
+                return;
+            };
+            let bundle = (
+                ChildOf(level_geometry),
+                Transform::from_translation(position.extend(0.0)),
+                rectangle(200.0, 25.0),
+            );
+            match kind.as_str() {
+                "wall" => world.spawn((bundle, Name::new("Script Wall"), Wall)),
+                "killbox" => world.spawn((bundle, Name::new("Script Kill Box"), KillBox)),
+                _ => {
+                    warn!("script: can not spawn {kind}");
+                    return;
+                }
+            };
+        }
+        ScriptCommand::FlipGravity => {
+            let mut gravity = world.resource_mut::<Gravity>();
+            gravity.0.y = -gravity.0.y;
+            if let Err(err) = world.run_system_cached(orient_ground_casters) {
+                error!("script: {err}");
+            }
+        }
+    }
+}
+
+/// Text shown by `show_text`, despawned when the timer finishes.
+#[derive(Component)]
+struct ScriptText(Timer);
+
+fn script_text(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut texts: Query<(Entity, &mut ScriptText)>,
+) {
+    for (entity, mut text) in texts.iter_mut() {
+        if text.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
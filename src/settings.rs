@@ -0,0 +1,86 @@
+use crate::{difficulty::Difficulty, transition::TransitionStyle};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use std::fs;
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(Settings::load())
+        .add_systems(Last, save_settings);
+}
+
+const PATH: &str = "settings.ron";
+
+/// User facing options.
+///
+/// Loaded from and saved to `settings.ron` in the working directory.
+#[derive(Resource, Reflect, serde::Serialize, serde::Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Glow around bright sprites such as bullets.
+    pub bloom: bool,
+    /// Red vignette and chromatic aberration pulse when the player is hurt.
+    pub damage_effects: bool,
+    /// Camera shake on shotgun blasts, explosions, deaths and gravity flips.
+    pub screen_shake: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub ui_volume: f32,
+    /// Silence all audio while the window is not focused.
+    pub mute_on_focus_loss: bool,
+    /// Translucent replay of the best run through the current level.
+    pub ghost: bool,
+    pub difficulty: Difficulty,
+    /// How the screen is covered while going through a door.
+    pub transition: TransitionStyle,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bloom: true,
+            damage_effects: true,
+            screen_shake: true,
+            master_volume: 1.0,
+            music_volume: 0.6,
+            sfx_volume: 1.0,
+            ui_volume: 1.0,
+            mute_on_focus_loss: true,
+            ghost: true,
+            difficulty: Difficulty::Normal,
+            transition: TransitionStyle::Fade,
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        match fs::read_to_string(PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("failed to parse {PATH}: {err}");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    let serialized = match ron::ser::to_string_pretty(&*settings, default()) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            error!("failed to serialize settings: {err}");
+            return;
+        }
+    };
+    IoTaskPool::get()
+        .spawn(async move {
+            if let Err(err) = fs::write(PATH, serialized) {
+                error!("failed to write {PATH}: {err}");
+            }
+        })
+        .detach();
+}
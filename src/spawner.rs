@@ -0,0 +1,196 @@
+//! Arena rooms that spawn enemies in waves.
+//!
+//! A [`Spawner`] activates when a player enters it and runs through its
+//! [`Wave`]s, then despawns once the last wave is cleared. Relate the exit
+//! [`Door`](crate::level::Door) to it with [`KeyOf`](crate::level::KeyOf) to
+//! keep the door shut until then.
+
+use crate::{
+    collision::Layer,
+    effects::ParticleBurst,
+    enemy::{Enemy, EnemySet, Flyer, Grunt, Turret},
+    level::{DebugPickingColor, NeedsSerializedCollider, Serialize},
+    player::Player,
+    registry::register_level_entity,
+};
+use avian2d::prelude::*;
+use bevy::{color::palettes::css::ORCHID, prelude::*};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, spawn_waves.in_set(EnemySet))
+        .add_observer(activate_spawner);
+
+    register_level_entity::<Spawner>(app);
+}
+
+/// Spawns its [`Wave`]s in order once a player enters it.
+#[derive(Default, Clone, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollisionEventsEnabled,
+    CollisionLayers = Layer::Trigger.collision_layers(),
+    DebugPickingColor::new(ORCHID),
+    NeedsSerializedCollider
+)]
+#[reflect(Default, Component)]
+pub struct Spawner {
+    pub waves: Vec<Wave>,
+    /// World positions enemies spawn at in turn, the spawner's own position if
+    /// empty.
+    pub points: Vec<Vec2>,
+}
+
+#[derive(Clone, Reflect)]
+#[reflect(Default)]
+pub struct Wave {
+    pub enemy: WaveEnemy,
+    pub count: u32,
+    /// Seconds between spawns.
+    pub interval: f32,
+    pub start: WaveStart,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self {
+            enemy: WaveEnemy::default(),
+            count: 3,
+            interval: 1.0,
+            start: WaveStart::default(),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Reflect)]
+pub enum WaveEnemy {
+    #[default]
+    Grunt,
+    Turret,
+    Flyer,
+}
+
+impl WaveEnemy {
+    fn insert(self, entity: &mut EntityCommands) {
+        match self {
+            Self::Grunt => entity.insert(Grunt),
+            Self::Turret => entity.insert(Turret::default()),
+            Self::Flyer => entity.insert(Flyer::default()),
+        };
+    }
+}
+
+/// When a [`Wave`] starts spawning.
+#[derive(Default, Clone, Copy, Reflect)]
+pub enum WaveStart {
+    /// Once every enemy spawned so far is dead.
+    #[default]
+    Cleared,
+    /// Seconds after the previous wave finished spawning, or after activation
+    /// for the first wave.
+    Delay(f32),
+}
+
+/// Progress of an active [`Spawner`].
+#[derive(Default, Component)]
+struct SpawnerState {
+    wave: usize,
+    /// Enemies spawned in the current wave.
+    spawned: u32,
+    /// Seconds until the next spawn.
+    cooldown: f32,
+    alive: Vec<Entity>,
+    next_point: usize,
+}
+
+impl SpawnerState {
+    /// Starts the wave at `index`, with its delay if it has one.
+    fn start_wave(&mut self, spawner: &Spawner, index: usize) {
+        self.wave = index;
+        self.spawned = 0;
+        if let Some(Wave {
+            start: WaveStart::Delay(delay),
+            ..
+        }) = spawner.waves.get(index)
+        {
+            self.cooldown = *delay;
+        }
+    }
+}
+
+fn activate_spawner(
+    start: On<CollisionStart>,
+    mut commands: Commands,
+    players: Query<(), With<Player>>,
+    spawners: Query<&Spawner, Without<SpawnerState>>,
+) {
+    if !players.contains(start.collider2) {
+        return;
+    }
+    let Ok(spawner) = spawners.get(start.collider1) else {
+        return;
+    };
+    let mut state = SpawnerState::default();
+    state.start_wave(spawner, 0);
+    commands.entity(start.collider1).insert(state);
+}
+
+fn spawn_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spawners: Query<(Entity, &Spawner, &mut SpawnerState, &GlobalTransform)>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    for (entity, spawner, mut state, transform) in spawners.iter_mut() {
+        state.alive.retain(|enemy| enemies.contains(*enemy));
+        state.cooldown -= time.delta_secs();
+
+        let Some(wave) = spawner.waves.get(state.wave) else {
+            if state.alive.is_empty() {
+                info!("spawner cleared");
+                commands.entity(entity).despawn();
+            }
+            continue;
+        };
+        let ready = if state.spawned == 0 {
+            match wave.start {
+                WaveStart::Cleared => state.alive.is_empty(),
+                WaveStart::Delay(_) => state.cooldown <= 0.0,
+            }
+        } else {
+            state.cooldown <= 0.0
+        };
+        if !ready {
+            continue;
+        }
+
+        if state.spawned < wave.count {
+            let position = spawner
+                .points
+                .get(state.next_point % spawner.points.len().max(1))
+                .copied()
+                .unwrap_or(transform.translation().xy());
+            state.next_point += 1;
+
+            let mut enemy = commands.spawn(Transform::from_translation(position.extend(0.0)));
+            wave.enemy.insert(&mut enemy);
+            // Spawned enemies belong to the wave rather than the level.
+            enemy.remove::<Serialize>();
+            state.alive.push(enemy.id());
+            state.spawned += 1;
+            state.cooldown = wave.interval;
+
+            commands.trigger(ParticleBurst {
+                position,
+                color: ORCHID.into(),
+                count: 12,
+            });
+        }
+        if state.spawned >= wave.count {
+            let next = state.wave + 1;
+            state.start_wave(spawner, next);
+        }
+    }
+}
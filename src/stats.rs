@@ -0,0 +1,338 @@
+//! Lifetime gameplay statistics, kept in the [`SaveData`].
+//!
+//! Counted into a [`SessionStats`] and merged into the save whenever a level
+//! loads or the app exits, so that the save file is not rewritten every step.
+//! `F2` toggles the stats screen and completing a level shows its results,
+//! compared with the best time and the [`ParTimes`].
+
+use crate::{
+    assets,
+    coop::Downed,
+    damage::Died,
+    difficulty::Difficulty,
+    level::{Key, Level, LevelComplete, LevelGeometry, LevelLoading, MustKeep},
+    player::{Killed, Player},
+    save::{self, SaveData},
+    settings::Settings,
+    weapon::{GravityGun, WeaponFired},
+};
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write, fs};
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(ParTimes::load())
+        .init_resource::<SessionStats>()
+        .init_resource::<LevelStats>()
+        .add_systems(FixedUpdate, (track_movement, track_level_time))
+        .add_systems(Update, (toggle_stats_screen, results))
+        .add_systems(Last, flush.before(save::save_progress))
+        .add_observer(count_shots)
+        .add_observer(count_deaths)
+        .add_observer(count_keys_destroyed)
+        .add_observer(level_complete);
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    /// Shots fired by weapon name.
+    pub shots: BTreeMap<String, u64>,
+    pub deaths: u64,
+    /// Seconds played by level ident.
+    pub level_time: BTreeMap<String, f64>,
+    pub distance: f64,
+    pub max_speed: f32,
+    /// Flips with the [`GravityGun`].
+    pub gravity_flips: u64,
+    pub keys_destroyed: u64,
+    /// [`MustKeep`] keys intact when leaving a level.
+    pub keys_kept: u64,
+}
+
+impl Stats {
+    pub fn merge(&mut self, other: &Stats) {
+        for (weapon, shots) in &other.shots {
+            *self.shots.entry(weapon.clone()).or_default() += shots;
+        }
+        self.deaths += other.deaths;
+        for (level, time) in &other.level_time {
+            *self.level_time.entry(level.clone()).or_default() += time;
+        }
+        self.distance += other.distance;
+        self.max_speed = self.max_speed.max(other.max_speed);
+        self.gravity_flips += other.gravity_flips;
+        self.keys_destroyed += other.keys_destroyed;
+        self.keys_kept += other.keys_kept;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.shots.is_empty()
+            && self.deaths == 0
+            && self.level_time.is_empty()
+            && self.distance == 0.0
+            && self.max_speed == 0.0
+            && self.gravity_flips == 0
+            && self.keys_destroyed == 0
+            && self.keys_kept == 0
+    }
+}
+
+/// [`Stats`] counted since they were last merged into the [`SaveData`].
+#[derive(Default, Resource)]
+pub struct SessionStats(pub Stats);
+
+/// Counts for the level being played, shown when it is completed.
+///
+/// Kept across deaths, reset when a different level loads.
+#[derive(Default, Resource)]
+pub struct LevelStats {
+    pub level: String,
+    pub shots: u64,
+    pub deaths: u64,
+    pub keys_destroyed: u64,
+}
+
+fn count_shots(
+    fired: On<WeaponFired>,
+    weapons: Query<(&Name, Option<&GravityGun>)>,
+    mut session: ResMut<SessionStats>,
+    mut level: ResMut<LevelStats>,
+) {
+    let Ok((name, gravity_gun)) = weapons.get(fired.entity) else {
+        return;
+    };
+    *session.0.shots.entry(name.to_string()).or_default() += 1;
+    if gravity_gun.is_some_and(|gun| gun.flip) {
+        session.0.gravity_flips += 1;
+    }
+    level.shots += 1;
+}
+
+fn count_deaths(
+    killed: On<Killed>,
+    standing: Query<(), (With<Player>, Without<Downed>)>,
+    mut session: ResMut<SessionStats>,
+    mut level: ResMut<LevelStats>,
+) {
+    if standing.contains(killed.entity) {
+        session.0.deaths += 1;
+        level.deaths += 1;
+    }
+}
+
+fn count_keys_destroyed(
+    died: On<Died>,
+    keys: Query<(), With<Key>>,
+    mut session: ResMut<SessionStats>,
+    mut level: ResMut<LevelStats>,
+) {
+    if keys.contains(died.entity) {
+        session.0.keys_destroyed += 1;
+        level.keys_destroyed += 1;
+    }
+}
+
+fn track_movement(
+    time: Res<Time>,
+    loading: Res<LevelLoading>,
+    players: Query<&LinearVelocity, (With<Player>, Without<Downed>)>,
+    mut session: ResMut<SessionStats>,
+) {
+    if loading.0 {
+        return;
+    }
+    for velocity in players.iter() {
+        let speed = velocity.length();
+        session.0.distance += (speed * time.delta_secs()) as f64;
+        session.0.max_speed = session.0.max_speed.max(speed);
+    }
+}
+
+fn track_level_time(
+    time: Res<Time>,
+    loading: Res<LevelLoading>,
+    level: Res<Level>,
+    mut session: ResMut<SessionStats>,
+) {
+    if !loading.0 {
+        *session.0.level_time.entry(level.0.clone()).or_default() += time.delta_secs_f64();
+    }
+}
+
+/// Merges the [`SessionStats`] into the [`SaveData`].
+fn flush(
+    new_level: Query<(), Added<LevelGeometry>>,
+    mut exit: MessageReader<AppExit>,
+    level: Res<Level>,
+    mut session: ResMut<SessionStats>,
+    mut level_stats: ResMut<LevelStats>,
+    mut save: ResMut<SaveData>,
+) {
+    if new_level.is_empty() && exit.read().last().is_none() {
+        return;
+    }
+    if level_stats.level != level.0 {
+        *level_stats = LevelStats {
+            level: level.0.clone(),
+            ..Default::default()
+        };
+    }
+    if !session.0.is_empty() {
+        save.stats.merge(&std::mem::take(&mut session.0));
+    }
+}
+
+const PAR_TIMES_PATH: &str = "par_times.ron";
+
+/// Par completion time in seconds by level ident, on [`Difficulty::Normal`].
+///
+/// Loaded from `assets/par_times.ron`. Levels without one have no par.
+#[derive(Default, Resource)]
+pub struct ParTimes(BTreeMap<String, f64>);
+
+impl ParTimes {
+    fn load() -> Self {
+        let path = assets::path(PAR_TIMES_PATH);
+        match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).map(Self).unwrap_or_else(|err| {
+                warn!("failed to parse {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Par time of `level` scaled by the
+    /// [`DifficultyTable::par_time`](crate::difficulty::DifficultyTable::par_time)
+    /// of `difficulty`.
+    pub fn get(&self, level: &str, difficulty: Difficulty) -> Option<f64> {
+        self.0
+            .get(level)
+            .map(|par| par * difficulty.table().par_time as f64)
+    }
+}
+
+/// Results of a completed level, despawned when the timer finishes.
+#[derive(Component)]
+struct Results(Timer);
+
+/// Seconds the results of a completed level stay on screen.
+const RESULTS_DURATION: f32 = 4.0;
+
+fn level_complete(
+    complete: On<LevelComplete>,
+    mut commands: Commands,
+    kept: Query<(), With<MustKeep>>,
+    mut session: ResMut<SessionStats>,
+    level: Res<LevelStats>,
+    save: Res<SaveData>,
+    par_times: Res<ParTimes>,
+    settings: Res<Settings>,
+) {
+    let kept = kept.iter().count() as u64;
+    session.0.keys_kept += kept;
+
+    let mut text = format!("{} complete\n\n", complete.level);
+    _ = writeln!(text, "Time: {:.2}s", complete.time);
+    // The save may already hold this run, so a tie is a new best too.
+    let best = save
+        .completed
+        .get(&complete.level)
+        .copied()
+        .unwrap_or(f64::INFINITY);
+    if complete.time <= best {
+        _ = writeln!(text, "New best");
+    } else {
+        _ = writeln!(text, "Best: {best:.2}s");
+    }
+    if let Some(par) = par_times.get(&complete.level, settings.difficulty) {
+        let verdict = if complete.time <= par {
+            "beaten"
+        } else {
+            "missed"
+        };
+        _ = writeln!(text, "Par: {par:.2}s, {verdict}");
+    }
+    _ = writeln!(text, "Shots: {}", level.shots);
+    _ = writeln!(text, "Deaths: {}", level.deaths);
+    _ = writeln!(text, "Keys destroyed: {}", level.keys_destroyed);
+    _ = write!(text, "Keys kept: {kept}");
+
+    commands.spawn((
+        Results(Timer::from_seconds(RESULTS_DURATION, TimerMode::Once)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(15.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        children![(
+            Text::new(text),
+            TextLayout::new_with_justify(Justify::Center)
+        )],
+    ));
+}
+
+fn results(mut commands: Commands, time: Res<Time>, mut results: Query<(Entity, &mut Results)>) {
+    for (entity, mut results) in results.iter_mut() {
+        if results.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[derive(Component)]
+struct StatsScreen;
+
+fn toggle_stats_screen(
+    mut commands: Commands,
+    input: Res<ButtonInput<KeyCode>>,
+    screen: Query<Entity, With<StatsScreen>>,
+    save: Res<SaveData>,
+    session: Res<SessionStats>,
+) {
+    if !input.just_pressed(KeyCode::F2) {
+        return;
+    }
+    if let Ok(screen) = screen.single() {
+        commands.entity(screen).despawn();
+        return;
+    }
+
+    let mut stats = save.stats.clone();
+    stats.merge(&session.0);
+
+    let mut text = String::from("Stats\n\n");
+    _ = writeln!(text, "Deaths: {}", stats.deaths);
+    _ = writeln!(text, "Distance: {:.0}", stats.distance);
+    _ = writeln!(text, "Max speed: {:.0}", stats.max_speed);
+    _ = writeln!(text, "Gravity flips: {}", stats.gravity_flips);
+    _ = writeln!(text, "Keys destroyed: {}", stats.keys_destroyed);
+    _ = writeln!(text, "Keys kept: {}", stats.keys_kept);
+    text.push_str("\nShots\n");
+    for (weapon, shots) in &stats.shots {
+        _ = writeln!(text, "{weapon}: {shots}");
+    }
+    text.push_str("\nTime played\n");
+    for (level, time) in &stats.level_time {
+        _ = writeln!(text, "{level}: {time:.0}s");
+    }
+
+    commands.spawn((
+        StatsScreen,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.8)),
+        GlobalZIndex(5),
+        children![Text::new(text)],
+    ));
+}
@@ -0,0 +1,213 @@
+use crate::level::DespawnOnLevelUnload;
+use avian2d::prelude::LinearVelocity;
+use bevy::{
+    asset::RenderAssetUsages, mesh::PrimitiveTopology, prelude::*, transform::TransformSystems,
+};
+use std::collections::VecDeque;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, trail_material)
+        .add_observer(detach_trail)
+        .add_systems(
+            PostUpdate,
+            (spawn_trails, sample_trails, build_trail_meshes)
+                .chain()
+                .after(TransformSystems::Propagate),
+        )
+        .add_systems(
+            PostUpdate,
+            (spawn_tracers, stretch_tracers)
+                .chain()
+                .before(TransformSystems::Propagate),
+        );
+}
+
+/// Renders a fading ribbon along the recent positions of this entity.
+///
+/// The ribbon lives on its own entity so that it can fade out after the
+/// trailed entity is despawned.
+#[derive(Clone, Copy, Component)]
+pub struct Trail {
+    /// Seconds a sampled position remains in the ribbon.
+    pub lifetime: f32,
+    pub width: f32,
+    pub color: Color,
+}
+
+impl Trail {
+    pub fn new(lifetime: f32, width: f32, color: impl Into<Color>) -> Self {
+        Self {
+            lifetime,
+            width,
+            color: color.into(),
+        }
+    }
+}
+
+#[derive(Component)]
+#[relationship_target(relationship = TrailOf)]
+struct Trails(Vec<Entity>);
+
+#[derive(Component)]
+#[relationship(relationship_target = Trails)]
+struct TrailOf(Entity);
+
+/// Time-sampled world positions, oldest first.
+#[derive(Component)]
+struct TrailPoints {
+    trail: Trail,
+    points: VecDeque<(Vec2, f32)>,
+}
+
+#[derive(Resource)]
+struct TrailMaterial(Handle<ColorMaterial>);
+
+fn trail_material(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.insert_resource(TrailMaterial(materials.add(ColorMaterial::default())));
+}
+
+fn spawn_trails(
+    mut commands: Commands,
+    trails: Query<(Entity, &Trail), Added<Trail>>,
+    material: Res<TrailMaterial>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, trail) in trails.iter() {
+        commands.spawn((
+            TrailOf(entity),
+            TrailPoints {
+                trail: *trail,
+                points: VecDeque::new(),
+            },
+            DespawnOnLevelUnload,
+            Transform::from_xyz(0.0, 0.0, -1.0),
+            Mesh2d(meshes.add(Mesh::new(
+                PrimitiveTopology::TriangleStrip,
+                RenderAssetUsages::default(),
+            ))),
+            MeshMaterial2d(material.0.clone()),
+        ));
+    }
+}
+
+/// Lets the ribbon fade out when [`Trail`] is removed from a live entity,
+/// such as a pooled bullet, see [`BulletPool`](crate::weapon::BulletPool).
+fn detach_trail(remove: On<Remove, Trail>, mut commands: Commands) {
+    commands.entity(remove.entity).try_remove::<Trails>();
+}
+
+fn sample_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trails: Query<(Entity, &mut TrailPoints, Option<&TrailOf>)>,
+    sources: Query<&GlobalTransform, Without<TrailPoints>>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, mut trail, trail_of) in trails.iter_mut() {
+        let lifetime = trail.trail.lifetime;
+        while trail
+            .points
+            .front()
+            .is_some_and(|(_, sampled)| now - sampled > lifetime)
+        {
+            trail.points.pop_front();
+        }
+
+        match trail_of.and_then(|trail_of| sources.get(trail_of.0).ok()) {
+            Some(transform) => trail.points.push_back((transform.translation().xy(), now)),
+            None if trail.points.is_empty() => commands.entity(entity).despawn(),
+            None => {}
+        }
+    }
+}
+
+fn build_trail_meshes(
+    time: Res<Time>,
+    trails: Query<(&TrailPoints, &Mesh2d)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let now = time.elapsed_secs();
+    for (TrailPoints { trail, points }, mesh) in trails.iter() {
+        let Some(mesh) = meshes.get_mut(&mesh.0) else {
+            continue;
+        };
+
+        let mut positions = Vec::with_capacity(points.len() * 2);
+        let mut colors = Vec::with_capacity(points.len() * 2);
+        if points.len() > 1 {
+            let linear = trail.color.to_linear();
+            for (i, (point, sampled)) in points.iter().enumerate() {
+                let previous = points[i.saturating_sub(1)].0;
+                let next = points[(i + 1).min(points.len() - 1)].0;
+                let normal = (next - previous).normalize_or_zero().perp();
+
+                // Younger samples are wider and more opaque.
+                let t = 1.0 - ((now - sampled) / trail.lifetime).clamp(0.0, 1.0);
+                let offset = normal * trail.width * 0.5 * t;
+                positions.push((point + offset).extend(0.0).to_array());
+                positions.push((point - offset).extend(0.0).to_array());
+
+                let color = linear.with_alpha(linear.alpha * t).to_f32_array();
+                colors.push(color);
+                colors.push(color);
+            }
+        }
+
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+}
+
+/// Stretches a streak behind this entity along its [`LinearVelocity`], so that
+/// fast projectiles read as lines rather than dots.
+///
+/// The streak is a child sprite, so it despawns with the entity. Its length
+/// follows the speed, shrinking as the entity slows down.
+#[derive(Clone, Copy, Component)]
+pub struct Tracer {
+    /// Seconds of travel the streak covers.
+    pub duration: f32,
+    pub width: f32,
+    pub color: Color,
+}
+
+impl Tracer {
+    pub fn new(duration: f32, width: f32, color: impl Into<Color>) -> Self {
+        Self {
+            duration,
+            width,
+            color: color.into(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct TracerStreak;
+
+fn spawn_tracers(mut commands: Commands, tracers: Query<(Entity, &Tracer), Added<Tracer>>) {
+    for (entity, tracer) in tracers.iter() {
+        commands.entity(entity).with_child((
+            TracerStreak,
+            Sprite::from_color(tracer.color, Vec2::ZERO),
+            Transform::from_xyz(0.0, 0.0, -0.5),
+        ));
+    }
+}
+
+fn stretch_tracers(
+    tracers: Query<(&Tracer, &LinearVelocity, &Transform, &Children)>,
+    mut streaks: Query<(&mut Sprite, &mut Transform), (With<TracerStreak>, Without<Tracer>)>,
+) {
+    for (tracer, velocity, transform, children) in tracers.iter() {
+        let length = velocity.length() * tracer.duration;
+        let direction = velocity.normalize_or_zero();
+        // The streak is placed in world space, whatever the parent's rotation.
+        let inverse = transform.rotation.inverse();
+        let mut streaks = streaks.iter_many_mut(children.iter());
+        while let Some((mut sprite, mut streak)) = streaks.fetch_next() {
+            sprite.custom_size = Some(Vec2::new(length, tracer.width));
+            streak.rotation = inverse * Quat::from_rotation_z(direction.to_angle());
+            streak.translation = (inverse * (-direction * length * 0.5).extend(0.0)).with_z(-0.5);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Screen transitions that cover the level change through a
+//! [`Door`](crate::level::Door).
+//!
+//! The screen is covered over [`TRANSITION_OUT`], then the level is reset
+//! into the door's destination. Once it is no longer [`LevelLoading`] the
+//! screen is uncovered over [`TRANSITION_IN`]. Players' input is held the
+//! whole time. The [`TransitionStyle`] is part of the
+//! [`Settings`](crate::settings::Settings), see [`crate::effects`] for how each
+//! is drawn.
+//!
+//! The phases advance in the fixed schedule, so that a replay changes level on
+//! the same step.
+
+use crate::{
+    coop::Downed,
+    level::{LevelLoading, LevelSet, reset_level},
+    player::Player,
+};
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::ContextActivity;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        advance_transition
+            .run_if(resource_exists::<Transition>)
+            .in_set(LevelSet),
+    );
+}
+
+/// Seconds the screen takes to cover before the level changes.
+pub const TRANSITION_OUT: f32 = 0.35;
+
+/// Seconds the screen takes to uncover once the new level has loaded.
+pub const TRANSITION_IN: f32 = 0.35;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub enum TransitionStyle {
+    /// Fades to black.
+    #[default]
+    Fade,
+    /// Sweeps black across the screen from left to right.
+    Wipe,
+    /// Closes a circle on the middle of the screen.
+    Iris,
+}
+
+impl FromStr for TransitionStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fade" => Ok(Self::Fade),
+            "wipe" => Ok(Self::Wipe),
+            "iris" => Ok(Self::Iris),
+            _ => Err(format!(
+                "unknown transition `{s}`, expected fade, wipe or iris"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionPhase {
+    /// Covering the screen.
+    Out,
+    /// Waiting for the level to load.
+    Loading,
+    /// Uncovering the screen.
+    In,
+}
+
+/// A level change in progress, removed once the screen is uncovered.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct Transition {
+    pub phase: TransitionPhase,
+    /// Seconds into the phase.
+    pub elapsed: f32,
+}
+
+impl Transition {
+    /// How much of the screen is covered, from `0.0` to `1.0`.
+    pub fn coverage(&self) -> f32 {
+        match self.phase {
+            TransitionPhase::Out => (self.elapsed / TRANSITION_OUT).min(1.0),
+            TransitionPhase::Loading => 1.0,
+            TransitionPhase::In => 1.0 - (self.elapsed / TRANSITION_IN).min(1.0),
+        }
+    }
+}
+
+/// Starts covering the screen, unless a transition is already running.
+///
+/// The [`Level`](crate::level::Level) should already name the destination.
+pub fn start_transition(mut commands: Commands, transition: Option<Res<Transition>>) {
+    if transition.is_none() {
+        commands.insert_resource(Transition {
+            phase: TransitionPhase::Out,
+            elapsed: 0.0,
+        });
+    }
+}
+
+fn advance_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    loading: Res<LevelLoading>,
+    mut transition: ResMut<Transition>,
+    players: Query<(Entity, &ContextActivity<Player>), Without<Downed>>,
+) {
+    transition.elapsed += time.delta_secs();
+    match transition.phase {
+        TransitionPhase::Out if transition.elapsed >= TRANSITION_OUT => {
+            commands.run_system_cached(reset_level);
+            *transition = Transition {
+                phase: TransitionPhase::Loading,
+                elapsed: 0.0,
+            };
+        }
+        // The reset is applied at the end of the step that queued it, so the
+        // level is loading by the next.
+        TransitionPhase::Loading if !loading.0 => {
+            *transition = Transition {
+                phase: TransitionPhase::In,
+                elapsed: 0.0,
+            };
+        }
+        TransitionPhase::In if transition.elapsed >= TRANSITION_IN => {
+            commands.remove_resource::<Transition>();
+            for (entity, _) in players.iter() {
+                commands
+                    .entity(entity)
+                    .insert(ContextActivity::<Player>::ACTIVE);
+            }
+            return;
+        }
+        _ => {}
+    }
+    // Players spawned by the new level start with their input active.
+    for (entity, activity) in players.iter() {
+        if **activity {
+            commands
+                .entity(entity)
+                .insert(ContextActivity::<Player>::INACTIVE);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    assets,
+    registry::Registry,
+    weapon::{FireMode, Weapon, WeaponDef},
+};
+use avian2d::prelude::Gravity;
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::component::ComponentId,
+    prelude::*,
+};
+use serde::Deserialize;
+
+pub fn plugin(app: &mut App) {
+    app.init_asset::<Tuning>()
+        .init_asset_loader::<TuningLoader>()
+        .init_resource::<Tuning>()
+        .init_asset::<WeaponStats>()
+        .init_asset_loader::<WeaponStatsLoader>()
+        .add_systems(Startup, (load_tuning, load_weapon_stats))
+        .add_systems(
+            Update,
+            (
+                (
+                    reload_tuning,
+                    apply_gravity.run_if(resource_changed::<Tuning>),
+                )
+                    .chain(),
+                reload_weapon_stats,
+            ),
+        )
+        .add_observer(insert_weapon_stats);
+}
+
+const PATH: &str = "tuning.ron";
+
+/// Gameplay feel parameters.
+///
+/// Loaded from `assets/tuning.ron` and copied into this resource whenever the
+/// file changes, so systems read it like any other resource.
+#[derive(Debug, Clone, Asset, Resource, Reflect, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Tuning {
+    pub gravity: f32,
+    /// X-axis speed of the player from movement input.
+    pub input_velocity: f32,
+    /// Jump velocity eased from the first to the second value while the jump is held.
+    pub jump_impulse_range: [f32; 2],
+    /// Seconds a jump can be held.
+    pub jump_duration: f32,
+    /// Seconds after leaving the ground that a jump is still allowed.
+    pub coyote_time: f32,
+    /// Seconds before landing that a jump press is remembered.
+    pub jump_buffer: f32,
+    /// Steepest ground in degrees that the player walks on rather than slides down.
+    pub max_slope: f32,
+    /// Fall speed while pushing against a wall.
+    pub wall_slide_speed: f32,
+    /// X-axis speed of a jump off a wall.
+    pub wall_jump_impulse: f32,
+    /// Seconds after a wall jump that movement input is ignored.
+    pub wall_jump_lockout: f32,
+    pub dash_speed: f32,
+    /// Seconds a dash lasts.
+    pub dash_duration: f32,
+    /// Seconds before the player can dash again.
+    pub dash_cooldown: f32,
+    pub weapon_velocity_damp: f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            gravity: 2_000.0,
+            input_velocity: 300.0,
+            jump_impulse_range: [500.0, 700.0],
+            jump_duration: 0.2,
+            coyote_time: 0.1,
+            jump_buffer: 0.1,
+            max_slope: 50.0,
+            wall_slide_speed: 150.0,
+            wall_jump_impulse: 400.0,
+            wall_jump_lockout: 0.15,
+            dash_speed: 900.0,
+            dash_duration: 0.15,
+            dash_cooldown: 0.6,
+            weapon_velocity_damp: 10.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    type Asset = Tuning;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &(),
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+struct TuningHandle(Handle<Tuning>);
+
+fn load_tuning(mut commands: Commands, server: Res<AssetServer>) {
+    commands.insert_resource(TuningHandle(server.load(PATH)));
+}
+
+fn reload_tuning(
+    mut commands: Commands,
+    mut events: MessageReader<AssetEvent<Tuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<Tuning>>,
+) {
+    for event in events.read() {
+        if (event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0))
+            && let Some(tuning) = assets.get(&handle.0)
+        {
+            info!("loaded {PATH}");
+            commands.insert_resource(tuning.clone());
+        }
+    }
+}
+
+fn apply_gravity(tuning: Res<Tuning>, mut gravity: ResMut<Gravity>) {
+    let sign = if gravity.0.y > 0.0 { 1.0 } else { -1.0 };
+    let target = Vec2::Y * sign * tuning.gravity;
+    if gravity.0 != target {
+        gravity.0 = target;
+    }
+}
+
+/// Overrides of a weapon's [`WeaponDef`] and [`MaxAmmo`](crate::weapon::MaxAmmo).
+///
+/// Loaded from `assets/weapons/<name>.weapon.ron`, where `<name>` is the
+/// registered weapon name in snake case, and applied again whenever the file
+/// changes. Fields left out keep the built-in value, as do weapons without a
+/// file.
+#[derive(Debug, Default, Clone, Asset, Reflect, Deserialize)]
+#[serde(default)]
+pub struct WeaponStats {
+    /// Projectiles per shot.
+    pub count: Option<usize>,
+    /// Full width in radians of the arc projectiles are spread across.
+    pub spread: Option<f32>,
+    /// Slowest and fastest projectile speed.
+    pub speed: Option<[f32; 2]>,
+    pub recoil: Option<f32>,
+    /// Seconds before projectiles slow down and despawn, or just despawn.
+    pub lifetime: Option<f32>,
+    pub ammo: Option<usize>,
+    /// Seconds between shots.
+    pub cooldown: Option<f32>,
+    pub mode: Option<FireMode>,
+}
+
+impl WeaponStats {
+    pub fn apply(&self, def: &mut WeaponDef) {
+        if let Some(count) = self.count {
+            def.count = count;
+        }
+        if let Some(spread) = self.spread {
+            def.spread = spread;
+        }
+        if let Some(recoil) = self.recoil {
+            def.recoil = recoil;
+        }
+        if let Some(cooldown) = self.cooldown {
+            def.cooldown = cooldown;
+        }
+        if let Some(mode) = self.mode {
+            def.mode = mode;
+        }
+        let Some(projectile) = &mut def.projectile else {
+            return;
+        };
+        if let Some([min, max]) = self.speed {
+            projectile.speed = min;
+            projectile.speed_jitter = (max - min).max(0.0);
+        }
+        if let Some(lifetime) = self.lifetime {
+            match &mut projectile.slowdown {
+                Some((_, duration)) => *duration = lifetime,
+                None => projectile.lifetime = Some(lifetime),
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct WeaponStatsLoader;
+
+impl AssetLoader for WeaponStatsLoader {
+    type Asset = WeaponStats;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &(),
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapon.ron"]
+    }
+}
+
+/// [`WeaponStats`] of each registered weapon that has a file.
+#[derive(Resource)]
+struct WeaponStatsHandles(Vec<(ComponentId, Handle<WeaponStats>)>);
+
+/// The [`WeaponStats`] of a [`Weapon`].
+#[derive(Component)]
+pub struct WeaponStatsHandle(pub Handle<WeaponStats>);
+
+fn load_weapon_stats(mut commands: Commands, server: Res<AssetServer>, registry: Res<Registry>) {
+    let handles = registry
+        .weapons
+        .iter()
+        .filter_map(|weapon| {
+            let path = format!("weapons/{}.weapon.ron", snake_case(&weapon.name));
+            assets::path(&path)
+                .is_file()
+                .then(|| (weapon.component, server.load(path)))
+        })
+        .collect();
+    commands.insert_resource(WeaponStatsHandles(handles));
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.char_indices() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn insert_weapon_stats(
+    add: On<Add, Weapon>,
+    mut commands: Commands,
+    handles: Option<Res<WeaponStatsHandles>>,
+    weapons: Query<EntityRef>,
+) {
+    let (Some(handles), Ok(weapon)) = (handles, weapons.get(add.entity)) else {
+        return;
+    };
+    if let Some((_, handle)) = handles.0.iter().find(|(id, _)| weapon.contains_id(*id)) {
+        commands
+            .entity(add.entity)
+            .insert(WeaponStatsHandle(handle.clone()));
+    }
+}
+
+/// Marks the weapons whose [`WeaponStats`] loaded or changed, so that
+/// `apply_weapon_stats` applies them on the next fixed step.
+fn reload_weapon_stats(
+    mut events: MessageReader<AssetEvent<WeaponStats>>,
+    mut weapons: Query<&mut WeaponStatsHandle>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        for mut handle in weapons.iter_mut() {
+            if handle.0.id() == *id {
+                handle.set_changed();
+            }
+        }
+    }
+}
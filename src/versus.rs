@@ -0,0 +1,296 @@
+//! Versus mode, where players shoot each other.
+//!
+//! Enabled by inserting the [`Versus`] resource, see `--versus`. Player
+//! bullets gain the [`VERSUS`](crate::collision::VERSUS) collision layers and a hit kills the other
+//! player, scoring for the [`Owner`] of the bullet. Killed players are
+//! [`Downed`] and respawn at the [`RespawnPoint`] furthest from everyone else.
+//!
+//! A round lasts [`Versus::round_seconds`] or until someone reaches
+//! [`Versus::score_limit`], then the results are shown until the level resets.
+
+use crate::{
+    collision::Layer,
+    coop::Downed,
+    level::{
+        DebugPickingColor, DespawnOnLevelUnload, LevelGeometry, NeedsSerializedCollider, Serialize,
+    },
+    player::{Killed, Player, PlayerSet},
+    registry::register_level_entity,
+    weapon::{Bullet, BulletImpact, ImpactSet, Owner, WeaponSet, despawn_bullet},
+};
+use avian2d::prelude::*;
+use bevy::{color::palettes::css::LIME, prelude::*};
+use bevy_enhanced_input::prelude::ContextActivity;
+use std::fmt::Write;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Round>()
+        .register_required_components::<Player, Score>()
+        .add_systems(
+            FixedUpdate,
+            (
+                (start_round, respawn_downed).in_set(PlayerSet),
+                (versus_layers, end_round).after(WeaponSet),
+            )
+                .run_if(resource_exists::<Versus>),
+        )
+        .add_systems(FixedUpdate, versus_hit.in_set(ImpactSet::Enemies))
+        .add_systems(
+            Update,
+            (spawn_hud, hud, results)
+                .chain()
+                .run_if(resource_exists::<Versus>),
+        );
+
+    register_level_entity::<RespawnPoint>(app);
+}
+
+/// Enables versus mode.
+#[derive(Clone, Copy, Resource)]
+pub struct Versus {
+    pub score_limit: u32,
+    pub round_seconds: f32,
+}
+
+impl Default for Versus {
+    fn default() -> Self {
+        Self {
+            score_limit: 10,
+            round_seconds: 180.0,
+        }
+    }
+}
+
+/// The round in progress, restarted when a level loads.
+#[derive(Default, Clone, Resource)]
+pub struct Round {
+    pub remaining: f32,
+    pub over: bool,
+}
+
+/// Kills scored this round.
+#[derive(Default, Clone, Copy, Component)]
+pub struct Score(pub u32);
+
+/// Distance from the shooter at which versus bullets spawn, so that they
+/// clear the shooter's own collider.
+const MUZZLE_OFFSET: f32 = 25.0;
+
+/// Where a [`Downed`] player respawns in versus mode.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    Sensor,
+    CollisionLayers = CollisionLayers::NONE,
+    DebugPickingColor::new(LIME),
+    NeedsSerializedCollider(Vec2::new(25.0, 40.0))
+)]
+#[reflect(Default, Component)]
+pub struct RespawnPoint;
+
+fn start_round(
+    versus: Res<Versus>,
+    mut round: ResMut<Round>,
+    new_level: Query<(), Added<LevelGeometry>>,
+) {
+    if !new_level.is_empty() {
+        round.remaining = versus.round_seconds;
+        round.over = false;
+    }
+}
+
+fn versus_layers(
+    mut commands: Commands,
+    mut players: Query<&mut CollisionLayers, Added<Player>>,
+    mut bullets: Query<(Entity, &mut Transform, &LinearVelocity), (With<Bullet>, Added<Owner>)>,
+) {
+    for mut layers in players.iter_mut() {
+        *layers = Layer::Player.versus_collision_layers();
+    }
+    for (bullet, mut transform, velocity) in bullets.iter_mut() {
+        transform.translation += (velocity.normalize_or_zero() * MUZZLE_OFFSET).extend(0.0);
+        commands
+            .entity(bullet)
+            .insert(Layer::Bullet.versus_collision_layers());
+    }
+}
+
+fn versus_hit(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    round: Option<Res<Round>>,
+    bullets: Query<&Owner, With<Bullet>>,
+    players: Query<(), (With<Player>, Without<Downed>)>,
+    mut scores: Query<&mut Score>,
+) {
+    for impact in impacts.read() {
+        let Ok(owner) = bullets.get(impact.bullet) else {
+            continue;
+        };
+        if owner.0 == impact.target || !players.contains(impact.target) {
+            continue;
+        }
+        commands
+            .entity(impact.bullet)
+            .queue_silenced(despawn_bullet);
+        if round.as_ref().is_some_and(|round| round.over) {
+            continue;
+        }
+        commands.trigger(Killed {
+            entity: impact.target,
+        });
+        if let Ok(mut score) = scores.get_mut(owner.0) {
+            score.0 += 1;
+        }
+    }
+}
+
+fn respawn_downed(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut downed: Query<(Entity, &mut Downed, &mut Transform, &mut LinearVelocity)>,
+    standing: Query<&GlobalTransform, (With<Player>, Without<Downed>)>,
+    points: Query<&GlobalTransform, With<RespawnPoint>>,
+) {
+    for (entity, mut timer, mut transform, mut velocity) in downed.iter_mut() {
+        timer.0 -= time.delta_secs();
+        if timer.0 > 0.0 {
+            continue;
+        }
+        // The point whose closest standing player is furthest away.
+        let point = points
+            .iter()
+            .map(|point| point.translation())
+            .max_by(|a, b| {
+                let distance = |point: &Vec3| {
+                    standing
+                        .iter()
+                        .map(|player| player.translation().distance_squared(*point))
+                        .fold(f32::MAX, f32::min)
+                };
+                distance(a).total_cmp(&distance(b))
+            });
+        if let Some(point) = point {
+            transform.translation = point;
+        }
+        velocity.0 = Vec2::ZERO;
+        commands
+            .entity(entity)
+            .remove::<(Downed, RigidBodyDisabled, ColliderDisabled)>()
+            .insert((Visibility::Inherited, ContextActivity::<Player>::ACTIVE));
+    }
+}
+
+fn end_round(
+    mut commands: Commands,
+    time: Res<Time>,
+    versus: Res<Versus>,
+    mut round: ResMut<Round>,
+    players: Query<(Entity, &Score), With<Player>>,
+) {
+    if round.over {
+        return;
+    }
+    round.remaining = (round.remaining - time.delta_secs()).max(0.0);
+    let limit = players
+        .iter()
+        .any(|(_, score)| score.0 >= versus.score_limit);
+    if round.remaining > 0.0 && !limit {
+        return;
+    }
+    round.over = true;
+    for (player, _) in players.iter() {
+        commands
+            .entity(player)
+            .insert(ContextActivity::<Player>::INACTIVE);
+    }
+}
+
+/// Round timer and scores shown during a round.
+#[derive(Component)]
+struct VersusHud;
+
+/// Final scores shown once the [`Round`] is over.
+#[derive(Component)]
+struct Results;
+
+fn spawn_hud(mut commands: Commands, hud: Query<(), With<VersusHud>>) {
+    if !hud.is_empty() {
+        return;
+    }
+    commands.spawn((
+        VersusHud,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..Default::default()
+        },
+        children![(
+            Text::default(),
+            TextLayout::new_with_justify(Justify::Center)
+        )],
+    ));
+}
+
+fn hud(
+    round: Res<Round>,
+    players: Query<(&Score, Option<&Name>), With<Player>>,
+    hud: Single<&Children, With<VersusHud>>,
+    mut texts: Query<&mut Text>,
+) {
+    let Some(mut text) = hud.first().and_then(|child| texts.get_mut(*child).ok()) else {
+        return;
+    };
+    let seconds = round.remaining.ceil() as u32;
+    text.0.clear();
+    _ = write!(text.0, "{}:{:02}", seconds / 60, seconds % 60);
+    for (score, name) in players.iter() {
+        let name = name.map_or("Player", Name::as_str);
+        _ = write!(text.0, "   {name} {}", score.0);
+    }
+}
+
+fn results(
+    mut commands: Commands,
+    round: Res<Round>,
+    players: Query<(&Score, Option<&Name>), With<Player>>,
+    results: Query<(), With<Results>>,
+) {
+    if !round.over || !results.is_empty() {
+        return;
+    }
+
+    let mut scores = players
+        .iter()
+        .map(|(score, name)| (score.0, name.map_or("Player", Name::as_str)))
+        .collect::<Vec<_>>();
+    scores.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut text = String::from("Round over\n\n");
+    for (score, name) in scores {
+        _ = writeln!(text, "{name}: {score}");
+    }
+    text.push_str("\nPress R to play again");
+
+    commands.spawn((
+        Results,
+        DespawnOnLevelUnload,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.7)),
+        GlobalZIndex(5),
+        children![(
+            Text::new(text),
+            TextLayout::new_with_justify(Justify::Center)
+        )],
+    ));
+}
@@ -1,36 +1,126 @@
 use crate::{
+    audio::{PlaySfx, Sfx},
+    collision::Layer,
+    coop::Partner,
+    damage::{Damage, Died, Health, apply_damage},
+    effects::{GLOW, ParticleBurst},
+    enemy::{Enemy, EnemyBullet},
+    juice::Juice,
+    keybinds::Keybinds,
     level::{
-        DebugPickingColor, Key, Layer, Serialize, SerializedColliderConstructor, Transient,
-        rectangle,
+        DebugPickingColor, DespawnOnLevelUnload, InPhysicsVolume, Key, MustDestroy, PhysicsVolume,
+        Serialize, SerializedColliderConstructor, WindZone, rectangle,
     },
-    player::{AimVector, Attack, Grounded, PickUp, Player, WeaponVelocity},
+    player::{
+        AimVector, Attack, CycleWeapon, Deflecting, ExternalVelocity, Grounded, PickUp, Player,
+        SecondaryAttack, WeaponVelocity, orient_ground_casters,
+    },
+    registry::{Registry, register_level_entity, register_serialized, register_weapon},
+    settings::Settings,
+    trail::{Tracer, Trail},
+    tuning::{WeaponStats, WeaponStatsHandle},
 };
 use avian2d::prelude::*;
 use bevy::{
-    color::palettes::css::PURPLE,
-    ecs::{lifecycle::HookContext, world::DeferredWorld},
+    color::palettes::css::{ORANGE, PURPLE},
+    ecs::{
+        archetype::Archetypes, entity::Entities, entity_disabling::Disabled,
+        lifecycle::HookContext, world::DeferredWorld,
+    },
     prelude::*,
 };
-use bevy_enhanced_input::prelude::Fire;
+use bevy_enhanced_input::prelude::{Complete, Fire, InputAction, Start};
 use bevy_rand::{global::GlobalRng, prelude::WyRand};
-use bevy_tween::{
-    bevy_time_runner::TimeRunnerEnded, component_tween_system, prelude::*, tween::AnimationTarget,
-};
+use bevy_tween::{prelude::*, tween::AnimationTarget};
 use rand::Rng;
+use serde::Deserialize;
 use std::f32::consts::PI;
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, (despawn_bullets, laser, reload))
-        .add_tween_systems(component_tween_system::<BulletVelocityLength>())
-        .add_observer(weapon_pickup)
-        .add_observer(insert_fire)
-        .add_observer(remove_fire)
-        .add_observer(shotgun)
-        .add_observer(assault_rifle)
-        .add_observer(gravity_gun)
-        .add_observer(rocket);
+    app.add_systems(
+        FixedUpdate,
+        (
+            apply_weapon_stats,
+            cool_down,
+            burst_fire,
+            slow_bullets,
+            expire_bullets,
+            (open_gravity_wells, pull_gravity_wells).chain(),
+            sticky_bombs,
+            laser,
+            explosion_flash,
+            reload,
+            collect_ammo,
+            reload_from_reserve,
+        )
+            .chain()
+            .in_set(WeaponSet)
+            .after(ImpactSet::Despawn),
+    )
+    .configure_sets(
+        FixedUpdate,
+        (
+            ImpactSet::Keys,
+            ImpactSet::Destructibles,
+            ImpactSet::Enemies,
+            ImpactSet::Effects,
+            ImpactSet::Despawn,
+        )
+            .chain()
+            .in_set(WeaponSet),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
+            (
+                impact_sfx,
+                rocket_impact,
+                gravity_well_shot_impact,
+                stick_bomb,
+            )
+                .in_set(ImpactSet::Effects),
+            despawn_on_hit.in_set(ImpactSet::Despawn),
+        ),
+    )
+    .add_message::<BulletImpact>()
+    .init_resource::<BulletPool>()
+    .add_systems(Update, (hotbar, charge_bar, pickup_prompt))
+    .add_observer(weapon_pickup)
+    .add_observer(cycle_weapon)
+    .add_observer(remove_fire)
+    .add_observer(fire_weapon)
+    .add_observer(explosion)
+    .add_observer(drop_ammo)
+    .add_observer(bullet_impact)
+    .add_observer(gravity_gun);
+
+    weapon_slot::<Attack, PrimaryWeapon>(app);
+    weapon_slot::<SecondaryAttack, SecondaryWeapon>(app);
+
+    register_serialized::<PrimaryWeapon>(app);
+    register_serialized::<SecondaryWeapon>(app);
+    register_serialized::<MaxAmmo>(app);
+    register_serialized::<DropsAmmo>(app);
+    register_serialized::<ReserveAmmo>(app);
+    register_level_entity::<AmmoPickup>(app);
+    register_level_entity::<WeaponPickup>(app);
+    register_weapon::<Shotgun>(app);
+    register_weapon::<AssaultRifle>(app);
+    register_weapon::<RicochetRifle>(app);
+    register_weapon::<GravityGun>(app);
+    register_weapon::<Rocket>(app);
+    register_weapon::<Laser>(app);
+    register_weapon::<Railgun>(app);
+    register_weapon::<StickyBomb>(app);
 }
 
+/// Weapon and bullet logic, after [`PlayerSet`](crate::player::PlayerSet) and
+/// before [`EnemySet`](crate::enemy::EnemySet).
+///
+/// See [`crate::game_plugin`] for the full ordering.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct WeaponSet;
+
 #[derive(Component, Reflect)]
 #[component(on_insert = Self::insert)]
 #[reflect(Component)]
@@ -43,34 +133,373 @@ impl MaxAmmo {
     }
 }
 
-#[derive(Component)]
+#[derive(Clone, Copy, Component)]
 pub struct Ammo(pub usize);
 
+/// Drops an [`AmmoPickup`] when it [`Died`].
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct DropsAmmo;
+
+/// Refills a weapon of the player that touches it, see
+/// [`DifficultyTable::ammo_refill`](crate::difficulty::DifficultyTable::ammo_refill).
+///
+/// Placed in levels, or dropped by [`DropsAmmo`] enemies for the current run.
+#[derive(Default, Clone, Component, Reflect)]
+#[require(
+    Serialize,
+    Transform,
+    RigidBody::Static,
+    SerializedColliderConstructor = rectangle(12.0, 12.0),
+    CollisionLayers = Layer::Pickups.collision_layers(),
+    DebugPickingColor::new(ORANGE),
+    Sprite::from_color(Color::srgb(1.0, 0.85, 0.2), Vec2::splat(12.0))
+)]
+#[reflect(Default, Component)]
+pub struct AmmoPickup {
+    /// Name the refilled weapon was registered with, see [`register_weapon`],
+    /// or the selected weapon when `None`.
+    pub weapon: Option<String>,
+    /// Ammo added to the weapon's [`ReserveAmmo`] on top of the refill.
+    pub reserve: usize,
+}
+
+/// Distance within which the player collects an [`AmmoPickup`].
+const AMMO_PICKUP_RADIUS: f32 = 30.0;
+
+fn drop_ammo(died: On<Died>, mut commands: Commands, drops: Query<(), With<DropsAmmo>>) {
+    if drops.contains(died.entity) {
+        commands
+            .spawn((
+                AmmoPickup::default(),
+                Transform::from_translation(died.position.extend(0.0)),
+            ))
+            .remove::<Serialize>();
+    }
+}
+
+fn collect_ammo(
+    mut commands: Commands,
+    players: Query<(&GlobalTransform, &Children), With<Player>>,
+    mut weapons: Query<(
+        Entity,
+        &mut Ammo,
+        &MaxAmmo,
+        &mut ReserveAmmo,
+        Has<PrimaryWeapon>,
+    )>,
+    pickups: Query<(Entity, &GlobalTransform, &AmmoPickup)>,
+    registry: Res<Registry>,
+    entities: &Entities,
+    archetypes: &Archetypes,
+    settings: Res<Settings>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    let refill = settings.difficulty.table().ammo_refill;
+    for (entity, transform, pickup) in pickups.iter() {
+        let position = transform.translation().xy();
+        let Some((_, children)) = players.iter().find(|(player, _)| {
+            player.translation().xy().distance_squared(position)
+                < AMMO_PICKUP_RADIUS * AMMO_PICKUP_RADIUS
+        }) else {
+            continue;
+        };
+        let component = pickup.weapon.as_ref().map(|name| {
+            registry
+                .weapons
+                .iter()
+                .find(|weapon| weapon.name == *name)
+                .map(|weapon| weapon.component)
+        });
+        let mut weapons = weapons.iter_many_mut(children);
+        while let Some((weapon, mut ammo, max_ammo, mut reserve, selected)) = weapons.fetch_next() {
+            let matches = match component {
+                None => selected,
+                Some(component) => component.is_some_and(|component| {
+                    entities
+                        .get(weapon)
+                        .and_then(|location| archetypes.get(location.archetype_id))
+                        .is_some_and(|archetype| archetype.contains(component))
+                }),
+            };
+            if !matches {
+                continue;
+            }
+            let amount = (max_ammo.0 as f32 * refill).ceil() as usize;
+            ammo.0 = (ammo.0 + amount).min(max_ammo.0);
+            reserve.0 += pickup.reserve;
+            sfx.write(PlaySfx::at(Sfx::Click, position));
+            commands.entity(entity).despawn();
+            break;
+        }
+    }
+}
+
+/// Ammo a [`Weapon`] reloads from once it runs out in the air.
+///
+/// Only filled by [`AmmoPickup`]s and the level, since landing reloads for
+/// free.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[reflect(Default, Component)]
+pub struct ReserveAmmo(pub usize);
+
+fn reload_from_reserve(mut weapons: Query<(&mut Ammo, &MaxAmmo, &mut ReserveAmmo)>) {
+    for (mut ammo, max_ammo, mut reserve) in weapons.iter_mut() {
+        if ammo.0 == 0 && reserve.0 > 0 {
+            let amount = max_ammo.0.min(reserve.0);
+            ammo.0 = amount;
+            reserve.0 -= amount;
+        }
+    }
+}
+
 fn reload(
-    _player: Single<&Player, Or<(Added<Grounded>, (Changed<Children>, With<Grounded>))>>,
-    ammo: Single<(&mut Ammo, &MaxAmmo), With<SelectedWeapon>>,
+    players: Query<
+        &Children,
+        (
+            With<Player>,
+            Or<(Added<Grounded>, (Changed<Children>, With<Grounded>))>,
+        ),
+    >,
+    mut weapons: Query<(&mut Ammo, &MaxAmmo), With<Weapon>>,
 ) {
-    let (mut ammo, max_ammo) = ammo.into_inner();
-    ammo.0 = max_ammo.0;
+    for children in players.iter() {
+        let mut weapons = weapons.iter_many_mut(children);
+        while let Some((mut ammo, max_ammo)) = weapons.fetch_next() {
+            ammo.0 = max_ammo.0;
+        }
+    }
 }
 
 #[derive(Component)]
 struct FireWeapon;
 
-fn insert_fire(
-    _attack: On<Fire<Attack>>,
+/// Fires the weapon in slot `S` with the action `A`.
+fn weapon_slot<A: InputAction, S: Component>(app: &mut App) {
+    app.add_observer(insert_fire::<A, S>)
+        .add_observer(auto_fire::<A, S>)
+        .add_observer(charge_weapon::<A, S>)
+        .add_observer(hold_laser::<A, S>)
+        .add_observer(release_charge::<A, S>);
+}
+
+fn insert_fire<A: InputAction, S: Component>(
+    attack: On<Start<A>>,
     mut commands: Commands,
-    weapon: Single<(Entity, &mut Ammo), With<SelectedWeapon>>,
-    is_grounded: Single<Has<Grounded>, With<Player>>,
+    mut weapons: Query<
+        (
+            Entity,
+            &mut Ammo,
+            &Cooldown,
+            Option<&WeaponDef>,
+            Option<&mut Charge>,
+            Has<StickyBomb>,
+            Has<Beam>,
+        ),
+        With<S>,
+    >,
+    players: Query<(&Children, Has<Grounded>), With<Player>>,
+    mut bombs: Query<(&mut StickyBombShot, &Owner)>,
 ) {
-    let (entity, mut ammo) = weapon.into_inner();
-    if !*is_grounded && ammo.0 == 0 {
+    let player = attack.context;
+    let Ok((children, is_grounded)) = players.get(player) else {
+        return;
+    };
+    let mut weapons = weapons.iter_many_mut(children);
+    let Some((entity, mut ammo, cooldown, def, charge, sticky, beam)) = weapons.fetch_next() else {
+        return;
+    };
+    // A second press detonates the bombs already out instead of firing.
+    if sticky && bombs.iter().any(|(_, owner)| owner.0 == player) {
+        for (mut bomb, owner) in bombs.iter_mut() {
+            if owner.0 == player {
+                bomb.fuse = 0.0;
+            }
+        }
         return;
     }
-    commands.entity(entity).insert(FireWeapon);
-    if !*is_grounded {
+    // Charged weapons fire on release, see `release_charge`.
+    if let Some(mut charge) = charge {
+        charge.amount = 0.0;
+        return;
+    }
+    // Beams and automatic weapons fire while held, see `laser` and `auto_fire`.
+    if beam || def.is_some_and(|def| def.mode == FireMode::Auto) {
+        return;
+    }
+    fire(
+        &mut commands,
+        player,
+        entity,
+        &mut ammo,
+        cooldown,
+        is_grounded,
+    );
+}
+
+/// Fires `weapon` if it is not cooling down and has [`Ammo`] left or the
+/// player is grounded.
+///
+/// Returns whether it fired.
+fn fire(
+    commands: &mut Commands,
+    player: Entity,
+    weapon: Entity,
+    ammo: &mut Ammo,
+    cooldown: &Cooldown,
+    is_grounded: bool,
+) -> bool {
+    if cooldown.0 > 0.0 || (!is_grounded && ammo.0 == 0) {
+        return false;
+    }
+    commands.entity(weapon).insert(FireWeapon);
+    commands.trigger(Juice::kick(player, 0.15));
+    if !is_grounded {
         ammo.0 -= 1;
     }
+    true
+}
+
+fn auto_fire<A: InputAction, S: Component>(
+    attack: On<Fire<A>>,
+    mut commands: Commands,
+    mut weapons: Query<(Entity, &WeaponDef, &mut Ammo, &Cooldown), With<S>>,
+    players: Query<(&Children, Has<Grounded>), With<Player>>,
+) {
+    let player = attack.context;
+    let Ok((children, is_grounded)) = players.get(player) else {
+        return;
+    };
+    let mut weapons = weapons.iter_many_mut(children);
+    if let Some((entity, def, mut ammo, cooldown)) = weapons.fetch_next()
+        && def.mode == FireMode::Auto
+    {
+        fire(
+            &mut commands,
+            player,
+            entity,
+            &mut ammo,
+            cooldown,
+            is_grounded,
+        );
+    }
+}
+
+/// The shots left in a [`FireMode::Burst`] and seconds until the next.
+#[derive(Clone, Copy, Component)]
+pub(crate) struct Burst {
+    remaining: usize,
+    interval: f32,
+    timer: f32,
+}
+
+/// Fires the rest of each [`Burst`] without using [`Ammo`].
+fn burst_fire(mut commands: Commands, time: Res<Time>, mut bursts: Query<(Entity, &mut Burst)>) {
+    for (entity, mut burst) in bursts.iter_mut() {
+        burst.timer -= time.delta_secs();
+        if burst.timer > 0.0 {
+            continue;
+        }
+        burst.timer += burst.interval;
+        burst.remaining = burst.remaining.saturating_sub(1);
+        commands.entity(entity).insert(FireWeapon);
+        if burst.remaining == 0 {
+            commands.entity(entity).remove::<Burst>();
+        }
+    }
+}
+
+/// Seconds until a [`Weapon`] can fire again, set from
+/// [`WeaponDef::cooldown`] when it fires.
+#[derive(Default, Clone, Copy, Component)]
+pub struct Cooldown(pub f32);
+
+fn cool_down(time: Res<Time>, mut weapons: Query<&mut Cooldown>) {
+    for mut cooldown in weapons.iter_mut() {
+        if cooldown.0 > 0.0 {
+            cooldown.0 = (cooldown.0 - time.delta_secs()).max(0.0);
+        }
+    }
+}
+
+/// Charge of a hold-to-charge [`Weapon`].
+///
+/// Builds up each step [`Attack`] is held and fires on release, with the
+/// projectile speed and recoil scaled up and the spread narrowed by the charge.
+#[derive(Clone, Copy, Component)]
+pub struct Charge {
+    /// Seconds to charge fully.
+    pub duration: f32,
+    /// Fraction of the projectile speed and recoil at no charge.
+    pub min_scale: f32,
+    /// In `[0, 1]`.
+    pub amount: f32,
+}
+
+impl Charge {
+    pub fn new(duration: f32, min_scale: f32) -> Self {
+        Self {
+            duration,
+            min_scale,
+            amount: 0.0,
+        }
+    }
+
+    /// Scales `def` by the current charge.
+    pub fn apply(&self, def: &mut WeaponDef) {
+        let scale = self.min_scale.lerp(1.0, self.amount);
+        if let Some(projectile) = &mut def.projectile {
+            projectile.speed *= scale;
+            projectile.speed_jitter *= scale;
+        }
+        def.recoil *= scale;
+        def.spread *= 1.0 - self.amount;
+    }
+}
+
+fn charge_weapon<A: InputAction, S: Component>(
+    attack: On<Fire<A>>,
+    time: Res<Time>,
+    players: Query<&Children, With<Player>>,
+    mut weapons: Query<&mut Charge, With<S>>,
+) {
+    let Ok(children) = players.get(attack.context) else {
+        return;
+    };
+    let mut weapons = weapons.iter_many_mut(children);
+    if let Some(mut charge) = weapons.fetch_next() {
+        charge.amount = (charge.amount + time.delta_secs() / charge.duration).min(1.0);
+    }
+}
+
+fn release_charge<A: InputAction, S: Component>(
+    attack: On<Complete<A>>,
+    mut commands: Commands,
+    mut weapons: Query<(Entity, &mut Ammo, &Cooldown, &mut Charge), With<S>>,
+    players: Query<(&Children, Has<Grounded>), With<Player>>,
+) {
+    let player = attack.context;
+    let Ok((children, is_grounded)) = players.get(player) else {
+        return;
+    };
+    let mut weapons = weapons.iter_many_mut(children);
+    let Some((entity, mut ammo, cooldown, mut charge)) = weapons.fetch_next() else {
+        return;
+    };
+    if charge.amount == 0.0 {
+        return;
+    }
+    // `fire_weapon` resets the charge after scaling the shot by it.
+    if !fire(
+        &mut commands,
+        player,
+        entity,
+        &mut ammo,
+        cooldown,
+        is_grounded,
+    ) {
+        charge.amount = 0.0;
+    }
 }
 
 fn remove_fire(insert: On<Insert, FireWeapon>, mut commands: Commands) {
@@ -78,242 +507,1409 @@ fn remove_fire(insert: On<Insert, FireWeapon>, mut commands: Commands) {
 }
 
 #[derive(Default, Component, Reflect)]
-#[require(Serialize)]
+#[require(Serialize, Cooldown, ReserveAmmo)]
 #[reflect(Component)]
 pub struct Weapon;
 
-#[derive(Default, Component, Reflect)]
+/// The [`Weapon`] among the player's children fired with [`Attack`].
+///
+/// The children without a slot are the player's inventory, cycled through
+/// with [`CycleWeapon`].
+#[derive(Default, Clone, Copy, Component, Reflect)]
 #[reflect(Component)]
-pub struct SelectedWeapon;
+pub struct PrimaryWeapon;
 
+/// The [`Weapon`] among the player's children fired with [`SecondaryAttack`].
+///
+/// Filled by the previous [`PrimaryWeapon`] when a [`WeaponPickup`] replaces
+/// it.
 #[derive(Default, Clone, Copy, Component, Reflect)]
-#[require(Weapon, MaxAmmo(1), Name::new("Shotgun"))]
-#[reflect(Default, Component)]
-pub struct Shotgun;
+#[reflect(Component)]
+pub struct SecondaryWeapon;
+
+/// A [`Weapon`] in either slot.
+pub type Equipped = Or<(With<PrimaryWeapon>, With<SecondaryWeapon>)>;
 
-fn shotgun(
-    _fire: On<Insert, FireWeapon>,
+fn cycle_weapon(
+    cycle: On<Fire<CycleWeapon>>,
     mut commands: Commands,
-    player: Single<(&mut WeaponVelocity, &GlobalTransform, &AimVector), With<Player>>,
-    _shotgun: Single<(), (With<Shotgun>, With<SelectedWeapon>)>,
-    mut rng: Single<&mut WyRand, With<GlobalRng>>,
+    players: Query<(&GlobalTransform, &Children), With<Player>>,
+    weapons: Query<Has<PrimaryWeapon>, (With<Weapon>, Without<SecondaryWeapon>)>,
+    mut sfx: MessageWriter<PlaySfx>,
 ) {
-    let (mut player_velocity, player_transform, aim_vector) = player.into_inner();
+    let Ok((transform, children)) = players.get(cycle.context) else {
+        return;
+    };
+    let owned = children
+        .iter()
+        .filter(|child| weapons.contains(*child))
+        .collect::<Vec<_>>();
+    if owned.len() < 2 {
+        return;
+    }
+    let selected = owned
+        .iter()
+        .position(|weapon| weapons.get(*weapon).unwrap_or_default())
+        .unwrap_or_default();
+    let next = if cycle.value > 0.0 {
+        (selected + 1) % owned.len()
+    } else {
+        (selected + owned.len() - 1) % owned.len()
+    };
+    commands.entity(owned[selected]).remove::<PrimaryWeapon>();
+    commands.entity(owned[next]).insert(PrimaryWeapon);
+    sfx.write(PlaySfx::at(Sfx::Click, transform.translation().xy()));
+}
 
-    let dir = -aim_vector.0;
-    let force = dir * 2_000.0;
-    player_velocity.0 += force;
+/// Shows the highest [`Charge`] of the first player's [`Equipped`] weapons
+/// above the [`Hotbar`].
+#[derive(Component)]
+struct ChargeBar;
 
-    for _ in 0..12 {
-        let velocity = random_direction_in_arc(aim_vector.0, 0.9, &mut rng);
-        let starting_velocity = rng.random_range(1_000.0..1_300.0);
+const CHARGE_BAR_WIDTH: f32 = 120.0;
 
-        let target = AnimationTarget.into_target();
-        commands
+fn charge_bar(
+    mut commands: Commands,
+    bar: Option<Single<(&mut Node, &mut BackgroundColor), With<ChargeBar>>>,
+    player: Option<Single<&Children, (With<Player>, Without<Partner>)>>,
+    weapons: Query<&Charge, Equipped>,
+) {
+    let charge = player
+        .map(|children| {
+            weapons
+                .iter_many(*children)
+                .map(|charge| charge.amount)
+                .fold(0.0, f32::max)
+        })
+        .unwrap_or_default();
+    let Some(bar) = bar else {
+        commands.spawn((
+            ChargeBar,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(72.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-CHARGE_BAR_WIDTH / 2.0)),
+                width: Val::Px(0.0),
+                height: Val::Px(6.0),
+                ..Default::default()
+            },
+            BackgroundColor(Color::WHITE),
+            Pickable::IGNORE,
+        ));
+        return;
+    };
+    let (mut node, mut color) = bar.into_inner();
+    node.width = Val::Px(CHARGE_BAR_WIDTH * charge);
+    color.0 = if charge >= 1.0 { GLOW } else { Color::WHITE };
+}
+
+/// Lists the first player's weapons along the bottom of the screen, with the
+/// [`PrimaryWeapon`] outlined in white and the [`SecondaryWeapon`] in yellow.
+#[derive(Component)]
+struct Hotbar;
+
+fn hotbar(
+    mut commands: Commands,
+    hotbar: Option<Single<Entity, With<Hotbar>>>,
+    player: Option<Single<&Children, (With<Player>, Without<Partner>)>>,
+    weapons: Query<
+        (
+            Entity,
+            &Name,
+            &Ammo,
+            &ReserveAmmo,
+            Has<PrimaryWeapon>,
+            Has<SecondaryWeapon>,
+        ),
+        With<Weapon>,
+    >,
+    mut shown: Local<Vec<(Entity, usize, usize, bool, bool)>>,
+) {
+    let owned = player
+        .map(|children| weapons.iter_many(*children).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let current = owned
+        .iter()
+        .map(|(entity, _, ammo, reserve, primary, secondary)| {
+            (*entity, ammo.0, reserve.0, *primary, *secondary)
+        })
+        .collect::<Vec<_>>();
+    if hotbar.is_some() && *shown == current {
+        return;
+    }
+    *shown = current;
+
+    let hotbar = match hotbar {
+        Some(hotbar) => {
+            commands.entity(*hotbar).despawn_children();
+            *hotbar
+        }
+        None => commands
             .spawn((
-                Bullet,
-                AnimationTarget,
-                LinearVelocity(velocity),
-                Transform::from_translation(player_transform.translation().xy().extend(0.0)),
-                Collider::circle(5.0),
-                Sprite::from_color(Color::WHITE, Vec2::splat(10.0)),
-                GravityScale(0.0),
+                Hotbar,
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(16.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(8.0),
+                    ..Default::default()
+                },
+                Pickable::IGNORE,
             ))
-            .animation()
-            .insert_tween_here(
-                Duration::from_secs_f32(0.8),
-                EaseKind::QuadraticOut,
-                target.with(bullet_velocity(starting_velocity, 100.0)),
-            );
+            .id(),
+    };
+    for (i, (_, name, ammo, reserve, primary, secondary)) in owned.into_iter().enumerate() {
+        let border = if primary {
+            Color::WHITE
+        } else if secondary {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            Color::WHITE.with_alpha(0.2)
+        };
+        commands.spawn((
+            ChildOf(hotbar),
+            Node {
+                padding: UiRect::all(Val::Px(6.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                ..Default::default()
+            },
+            BorderColor::all(border),
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            Pickable::IGNORE,
+            children![(
+                Text::new(if reserve.0 > 0 {
+                    format!("{} {name}\n{} +{}", i + 1, ammo.0, reserve.0)
+                } else {
+                    format!("{} {name}\n{}", i + 1, ammo.0)
+                }),
+                TextFont::from_font_size(14.0),
+                TextLayout::new_with_justify(Justify::Center),
+                Pickable::IGNORE,
+            )],
+        ));
     }
 }
 
-#[derive(Default, Clone, Copy, Component, Reflect)]
-#[require(Weapon, MaxAmmo(3), Name::new("Assault Rifle"))]
-#[reflect(Default, Component)]
-pub struct AssaultRifle;
+/// Describes how a [`Weapon`] fires, interpreted by [`fire_weapon`].
+///
+/// Most weapons are a marker component that requires a [`WeaponDef`]. Special
+/// behavior hooks into [`Projectile::insert`] or observes [`WeaponFired`].
+#[derive(Clone, Copy, Component)]
+pub struct WeaponDef {
+    pub projectile: Option<Projectile>,
+    /// Projectiles spawned per shot.
+    pub count: usize,
+    /// Full width in radians of the arc projectiles are spread across.
+    pub spread: f32,
+    /// Velocity applied to the player against the aim direction.
+    pub recoil: f32,
+    /// Seconds after firing before the weapon fires again.
+    pub cooldown: f32,
+    pub mode: FireMode,
+    pub sfx: Option<Sfx>,
+}
 
-fn assault_rifle(
-    _fire: On<Insert, FireWeapon>,
+/// How a [`WeaponDef`] fires while [`Attack`] is pressed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect, Deserialize)]
+pub enum FireMode {
+    /// Once per press.
+    #[default]
+    Semi,
+    /// Repeatedly while held, once per [`WeaponDef::cooldown`].
+    Auto,
+    /// `count` shots `interval` seconds apart per press.
+    Burst { count: usize, interval: f32 },
+}
+
+impl Default for WeaponDef {
+    fn default() -> Self {
+        Self {
+            projectile: None,
+            count: 1,
+            spread: 0.0,
+            recoil: 0.0,
+            cooldown: 0.0,
+            mode: FireMode::Semi,
+            sfx: None,
+        }
+    }
+}
+
+impl WeaponDef {
+    /// Spawns [`Bullet`]s from `origin` toward `aim`, passing each to `insert`.
+    ///
+    /// Projectiles without a [`Projectile::insert`] are taken from the
+    /// [`BulletPool`] when it has any.
+    pub fn spawn_projectiles(
+        &self,
+        commands: &mut Commands,
+        pool: &mut BulletPool,
+        rng: &mut impl Rng,
+        origin: Vec2,
+        aim: Vec2,
+        mut insert: impl FnMut(&mut EntityCommands),
+    ) {
+        let Some(projectile) = self.projectile else {
+            return;
+        };
+        for _ in 0..self.count {
+            // Only sample what varies, so that fixed values do not shift the RNG stream.
+            let direction = if self.spread > 0.0 {
+                random_direction_in_arc(aim, self.spread, rng)
+            } else {
+                aim
+            };
+            let speed = if projectile.speed_jitter > 0.0 {
+                rng.random_range(projectile.speed..projectile.speed + projectile.speed_jitter)
+            } else {
+                projectile.speed
+            };
+
+            let mut bullet = match projectile.insert {
+                Some(_) => commands.spawn_empty(),
+                None => pool.acquire(commands),
+            };
+            bullet.insert((
+                Bullet,
+                Layer::Bullet.collision_layers(),
+                LinearVelocity(direction * speed),
+                Transform::from_translation(origin.extend(0.0)),
+                Collider::circle(projectile.radius),
+                Sprite::from_color(GLOW, Vec2::splat(projectile.radius * 2.0)),
+                GravityScale(projectile.gravity_scale),
+                Damage(projectile.damage),
+            ));
+            if let Some((end, duration)) = projectile.slowdown {
+                bullet.insert(BulletSlowdown::new(speed, end, duration));
+            }
+            if let Some(trail) = projectile.trail {
+                bullet.insert(trail);
+            }
+            if let Some(tracer) = projectile.tracer {
+                bullet.insert(tracer);
+            }
+            if projectile.despawn_on_hit {
+                bullet.insert(DespawnOnHit);
+            }
+            if let Some(lifetime) = projectile.lifetime {
+                bullet.insert(BulletLifetime(lifetime));
+            }
+            if let Some(insert) = projectile.insert {
+                insert(&mut bullet);
+            }
+            insert(&mut bullet);
+        }
+    }
+}
+
+/// Prototype for the [`Bullet`]s spawned by a [`WeaponDef`].
+#[derive(Clone, Copy)]
+pub struct Projectile {
+    pub speed: f32,
+    /// Random extra speed in `[0, speed_jitter)`.
+    pub speed_jitter: f32,
+    /// Eases to `.0` speed over `.1` seconds, then despawns.
+    pub slowdown: Option<(f32, f32)>,
+    pub radius: f32,
+    pub gravity_scale: f32,
+    /// [`Damage`] dealt on hit.
+    pub damage: f32,
+    pub despawn_on_hit: bool,
+    /// Seconds before the projectile despawns.
+    pub lifetime: Option<f32>,
+    pub trail: Option<Trail>,
+    pub tracer: Option<Tracer>,
+    /// Adds weapon specific components to each projectile, which react to
+    /// its [`BulletImpact`]s.
+    pub insert: Option<fn(&mut EntityCommands)>,
+}
+
+impl Default for Projectile {
+    fn default() -> Self {
+        Self {
+            speed: 1_000.0,
+            speed_jitter: 0.0,
+            slowdown: None,
+            radius: 5.0,
+            gravity_scale: 0.0,
+            damage: 1.0,
+            despawn_on_hit: false,
+            lifetime: None,
+            trail: None,
+            tracer: None,
+            insert: None,
+        }
+    }
+}
+
+/// The player that fired a [`Bullet`].
+#[derive(Clone, Copy, Component)]
+pub struct Owner(pub Entity);
+
+/// The [`Weapon`] or turret that fired a [`Bullet`].
+#[derive(Clone, Copy, Component)]
+pub struct FiredBy(pub Entity);
+
+/// Triggered on a [`Weapon`] after [`fire_weapon`] fires it.
+#[derive(EntityEvent)]
+pub struct WeaponFired {
+    pub entity: Entity,
+}
+
+fn fire_weapon(
+    fire: On<Insert, FireWeapon>,
     mut commands: Commands,
-    player: Single<(&mut WeaponVelocity, &GlobalTransform, &AimVector), With<Player>>,
-    _assault_rifle: Single<(), (With<AssaultRifle>, With<SelectedWeapon>)>,
+    mut players: Query<(&mut WeaponVelocity, &GlobalTransform, &AimVector), With<Player>>,
+    mut weapons: Query<
+        (
+            &WeaponDef,
+            &ChildOf,
+            &mut Cooldown,
+            Option<&mut Charge>,
+            Has<Burst>,
+        ),
+        Equipped,
+    >,
+    mut pool: ResMut<BulletPool>,
     mut rng: Single<&mut WyRand, With<GlobalRng>>,
+    mut sfx: MessageWriter<PlaySfx>,
 ) {
-    let (mut player_velocity, player_transform, aim_vector) = player.into_inner();
+    let Ok((def, child_of, mut cooldown, charge, burst)) = weapons.get_mut(fire.entity) else {
+        return;
+    };
+    cooldown.0 = def.cooldown;
+    if let FireMode::Burst { count, interval } = def.mode
+        && count > 1
+        && !burst
+    {
+        commands.entity(fire.entity).insert(Burst {
+            remaining: count - 1,
+            interval,
+            timer: interval,
+        });
+    }
+    let mut def = *def;
+    if let Some(mut charge) = charge {
+        charge.apply(&mut def);
+        charge.amount = 0.0;
+    }
+    let Ok((mut player_velocity, player_transform, aim_vector)) =
+        players.get_mut(child_of.parent())
+    else {
+        return;
+    };
+    let _span = info_span!("fire_weapon", count = def.count).entered();
+    if let Some(effect) = def.sfx {
+        sfx.write(PlaySfx::new(effect));
+    }
+    player_velocity.0 -= aim_vector.0 * def.recoil;
 
-    let dir = -aim_vector.0;
-    let force = dir * 500.0;
-    player_velocity.0 += force;
+    def.spawn_projectiles(
+        &mut commands,
+        &mut pool,
+        &mut **rng,
+        player_transform.translation().xy(),
+        aim_vector.0,
+        |bullet| {
+            bullet.insert((Owner(child_of.parent()), FiredBy(fire.entity)));
+        },
+    );
 
-    let velocity = random_direction_in_arc(aim_vector.0, PI * 0.1, &mut rng);
-    let starting_velocity = rng.random_range(1_000.0..1_300.0);
+    commands.trigger(WeaponFired {
+        entity: fire.entity,
+    });
+}
 
-    commands
-        .spawn((
-            Bullet,
-            LinearVelocity(velocity * starting_velocity),
-            Transform::from_translation(player_transform.translation().xy().extend(0.0)),
-            Collider::circle(5.0),
-            Sprite::from_color(Color::WHITE, Vec2::splat(10.0)),
-            GravityScale(0.0),
-            CollisionEventsEnabled,
-        ))
-        .observe(|target: On<CollisionStart>, mut commands: Commands| {
-            commands.entity(target.collider1).despawn();
-        });
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(Weapon, MaxAmmo(1), Name::new("Shotgun"), WeaponDef = Self::def())]
+#[reflect(Default, Component)]
+pub struct Shotgun;
+
+impl Shotgun {
+    fn def() -> WeaponDef {
+        WeaponDef {
+            projectile: Some(Projectile {
+                speed_jitter: 300.0,
+                slowdown: Some((100.0, 0.8)),
+                trail: Some(Trail::new(0.08, 6.0, Color::WHITE.with_alpha(0.5))),
+                tracer: Some(Tracer::new(0.02, 3.0, Color::WHITE.with_alpha(0.8))),
+                ..Default::default()
+            }),
+            count: 12,
+            spread: 0.9,
+            recoil: 2_000.0,
+            sfx: Some(Sfx::Shotgun),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(Weapon, MaxAmmo(3), Name::new("Assault Rifle"), WeaponDef = Self::def())]
+#[reflect(Default, Component)]
+pub struct AssaultRifle;
+
+impl AssaultRifle {
+    fn def() -> WeaponDef {
+        WeaponDef {
+            projectile: Some(Projectile {
+                speed_jitter: 300.0,
+                despawn_on_hit: true,
+                tracer: Some(Tracer::new(0.03, 2.0, Color::WHITE.with_alpha(0.6))),
+                ..Default::default()
+            }),
+            spread: PI * 0.1,
+            recoil: 500.0,
+            cooldown: 0.1,
+            mode: FireMode::Auto,
+            sfx: Some(Sfx::AssaultRifle),
+            ..Default::default()
+        }
+    }
 }
 
+/// An [`AssaultRifle`] whose bullets [`Ricochet`] off walls.
 #[derive(Default, Clone, Copy, Component, Reflect)]
-#[require(Weapon, MaxAmmo(2), Name::new("Gravity Gun"))]
+#[require(Weapon, MaxAmmo(3), Name::new("Ricochet Rifle"), WeaponDef = Self::def())]
 #[reflect(Default, Component)]
-pub struct GravityGun;
+pub struct RicochetRifle;
+
+impl RicochetRifle {
+    fn def() -> WeaponDef {
+        let mut def = AssaultRifle::def();
+        if let Some(projectile) = &mut def.projectile {
+            projectile.lifetime = Some(2.0);
+            projectile.insert = Some(|bullet| {
+                bullet.insert(Ricochet(3));
+            });
+        }
+        def.cooldown = 0.3;
+        def.mode = FireMode::Burst {
+            count: 3,
+            interval: 0.06,
+        };
+        def
+    }
+}
 
+/// Applies the [`WeaponStats`] of weapons when they are added or their file
+/// changes.
+fn apply_weapon_stats(
+    stats: Res<Assets<WeaponStats>>,
+    mut weapons: Query<(
+        Ref<WeaponStatsHandle>,
+        &mut WeaponDef,
+        &mut MaxAmmo,
+        &mut Ammo,
+    )>,
+) {
+    for (handle, mut def, mut max_ammo, mut ammo) in weapons.iter_mut() {
+        if !handle.is_changed() {
+            continue;
+        }
+        let Some(stats) = stats.get(&handle.0) else {
+            continue;
+        };
+        stats.apply(&mut def);
+        if let Some(max) = stats.ammo {
+            max_ammo.0 = max;
+            ammo.0 = ammo.0.min(max);
+        }
+    }
+}
+
+/// Fires a slow shot that opens a [`GravityWell`] where it hits or runs out.
+///
+/// With `flip`, it flips the global [`Gravity`] instead, which also turns
+/// bullets and every other body upside down.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(Weapon, MaxAmmo(2), Name::new("Gravity Gun"), WeaponDef)]
+#[component(on_insert = GravityGun::insert)]
+#[reflect(Default, Component)]
+pub struct GravityGun {
+    pub flip: bool,
+}
+
+impl GravityGun {
+    fn insert(mut world: DeferredWorld, ctx: HookContext) {
+        let flip = world.get::<GravityGun>(ctx.entity).unwrap().flip;
+        if let Some(mut def) = world.get_mut::<WeaponDef>(ctx.entity) {
+            *def = Self::def(flip);
+        }
+    }
+
+    fn def(flip: bool) -> WeaponDef {
+        if flip {
+            return WeaponDef::default();
+        }
+        WeaponDef {
+            projectile: Some(Projectile {
+                speed: 600.0,
+                radius: 8.0,
+                insert: Some(|bullet| {
+                    bullet.insert(GravityWellShot(GRAVITY_WELL_SHOT_LIFETIME));
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Seconds a [`GravityGun`] shot flies before opening its well.
+const GRAVITY_WELL_SHOT_LIFETIME: f32 = 0.5;
+
+/// A [`GravityGun`] shot, with the seconds left before it opens a
+/// [`GravityWell`].
+#[derive(Clone, Copy, Component)]
+pub(crate) struct GravityWellShot(f32);
+
+/// Pulls dynamic bodies and players within `radius` toward it, harder closer
+/// in, until `remaining` runs out.
+#[derive(Clone, Copy, Component)]
+#[require(Transform, DespawnOnLevelUnload)]
+pub struct GravityWell {
+    /// Acceleration at the center in pixels per second squared.
+    pub strength: f32,
+    pub radius: f32,
+    pub remaining: f32,
+}
+
+impl Default for GravityWell {
+    fn default() -> Self {
+        Self {
+            strength: 4_000.0,
+            radius: 250.0,
+            remaining: 3.0,
+        }
+    }
+}
+
+fn open_gravity_well(commands: &mut Commands, shot: Entity, position: Vec2) {
+    commands.entity(shot).try_despawn();
+    commands.spawn((
+        GravityWell::default(),
+        Transform::from_translation(position.extend(0.0)),
+    ));
+}
+
+fn gravity_well_shot_impact(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    shots: Query<(), With<GravityWellShot>>,
+) {
+    for impact in impacts.read() {
+        if shots.contains(impact.bullet) {
+            open_gravity_well(&mut commands, impact.bullet, impact.position);
+        }
+    }
+}
+
+fn open_gravity_wells(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shots: Query<(Entity, &mut GravityWellShot, &GlobalTransform)>,
+) {
+    for (entity, mut shot, transform) in shots.iter_mut() {
+        shot.0 -= time.delta_secs();
+        if shot.0 <= 0.0 {
+            open_gravity_well(&mut commands, entity, transform.translation().xy());
+        }
+    }
+}
+
+fn pull_gravity_wells(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut wells: Query<(Entity, &mut GravityWell, &GlobalTransform)>,
+    mut players: Query<(&Position, &mut ExternalVelocity, &mut LinearVelocity), With<Player>>,
+    mut bodies: Query<(&RigidBody, &Position, &mut LinearVelocity), Without<Player>>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut well, transform) in wells.iter_mut() {
+        well.remaining -= dt;
+        if well.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let center = transform.translation().xy();
+        let pull = |position: Vec2| {
+            let offset = center - position;
+            let distance = offset.length();
+            // Nothing at the very center, where the direction flips every step.
+            if distance > well.radius || distance < 10.0 {
+                return Vec2::ZERO;
+            }
+            offset / distance * well.strength * (1.0 - distance / well.radius) * dt
+        };
+        for (position, mut external, mut velocity) in players.iter_mut() {
+            let pull = pull(position.0);
+            // Movement overrides the player's x velocity each step.
+            external.0 += pull.x;
+            velocity.y += pull.y;
+        }
+        for (body, position, mut velocity) in bodies.iter_mut() {
+            if body.is_dynamic() {
+                velocity.0 += pull(position.0);
+            }
+        }
+    }
+}
+
+/// Flips the global [`Gravity`] when a [`GravityGun`] with `flip` fires.
 fn gravity_gun(
-    _fire: On<Insert, FireWeapon>,
+    fired: On<WeaponFired>,
     mut commands: Commands,
-    player: Single<Entity, With<Player>>,
-    _gravity_gun: Single<&GravityGun, With<SelectedWeapon>>,
+    gravity_guns: Query<&GravityGun>,
     mut gravity: ResMut<Gravity>,
 ) {
-    gravity.0.y = -gravity.0.y;
-    if gravity.0.y > 0.0 {
-        commands.entity(*player).insert(Player::ceiling_caster());
-    } else {
-        commands.entity(*player).insert(Player::ground_caster());
+    if !gravity_guns.get(fired.entity).is_ok_and(|gun| gun.flip) {
+        return;
     }
+    let _span = info_span!("gravity_gun").entered();
+    gravity.0.y = -gravity.0.y;
+    commands.run_system_cached(orient_ground_casters);
 }
 
 #[derive(Default, Clone, Copy, Component, Reflect)]
-#[require(Weapon, MaxAmmo(1), Name::new("Rocket"))]
+#[require(Weapon, MaxAmmo(1), Name::new("Rocket"), WeaponDef = Self::def())]
 #[reflect(Default, Component)]
 pub struct Rocket;
 
-fn rocket(
-    _fire: On<Insert, FireWeapon>,
+impl Rocket {
+    fn def() -> WeaponDef {
+        WeaponDef {
+            projectile: Some(Projectile {
+                gravity_scale: 0.5,
+                damage: 3.0,
+                insert: Some(|bullet| {
+                    bullet.insert(RocketBullet);
+                }),
+                ..Default::default()
+            }),
+            sfx: Some(Sfx::Rocket),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Trail = Trail::new(0.4, 10.0, Color::srgb(0.8, 0.8, 0.8)))]
+pub struct RocketBullet;
+
+fn rocket_impact(
+    mut impacts: MessageReader<BulletImpact>,
     mut commands: Commands,
-    player: Single<(&GlobalTransform, &AimVector), With<Player>>,
-    _rocket: Single<(), (With<Rocket>, With<SelectedWeapon>)>,
+    rockets: Query<(), With<RocketBullet>>,
 ) {
-    let (player_transform, aim_vector) = player.into_inner();
-    let dir = aim_vector.0;
-    let velocity = dir * 1_000.0;
+    for impact in impacts.read() {
+        if !rockets.contains(impact.bullet) {
+            continue;
+        }
+        let _span = info_span!("rocket_impact").entered();
+        commands.trigger(Explosion {
+            position: impact.position,
+            force: 5_000.0,
+            radius: 200.0,
+            damage: 3.0,
+        });
+        commands.entity(impact.bullet).try_despawn();
+    }
+}
 
-    commands
-        .spawn((
-            Bullet,
-            RocketBullet,
-            LinearVelocity(velocity),
-            Transform::from_translation(player_transform.translation().xy().extend(0.0)),
-            Collider::circle(5.0),
-            Sprite::from_color(Color::WHITE, Vec2::splat(10.0)),
-            GravityScale(0.5),
-            CollisionEventsEnabled,
-        ))
-        .observe(rocket_bullet);
+/// Lobs a bomb that sticks to the first wall or key it touches and explodes
+/// on the next [`Attack`] press or after [`STICKY_BOMB_FUSE`] seconds.
+#[derive(Default, Clone, Copy, Component, Reflect)]
+#[require(Weapon, MaxAmmo(2), Name::new("Sticky Bomb"), WeaponDef = Self::def())]
+#[reflect(Default, Component)]
+pub struct StickyBomb;
+
+impl StickyBomb {
+    fn def() -> WeaponDef {
+        WeaponDef {
+            projectile: Some(Projectile {
+                speed: 700.0,
+                radius: 6.0,
+                gravity_scale: 1.0,
+                damage: 0.0,
+                insert: Some(|bullet| {
+                    bullet.insert(StickyBombShot::default());
+                }),
+                ..Default::default()
+            }),
+            recoil: 300.0,
+            sfx: Some(Sfx::Rocket),
+            ..Default::default()
+        }
+    }
 }
 
-#[derive(Component)]
-pub struct RocketBullet;
+/// Seconds from firing until a [`StickyBomb`] explodes on its own.
+pub const STICKY_BOMB_FUSE: f32 = 3.0;
 
-fn rocket_bullet(
-    start: On<CollisionStart>,
+#[derive(Clone, Copy, Component)]
+pub(crate) struct StickyBombShot {
+    fuse: f32,
+}
+
+impl Default for StickyBombShot {
+    fn default() -> Self {
+        Self {
+            fuse: STICKY_BOMB_FUSE,
+        }
+    }
+}
+
+/// A [`StickyBombShot`] attached to `target` at `offset` in its local space.
+///
+/// Follows the target rather than becoming its child, since the children of
+/// level entities are saved with the level.
+#[derive(Clone, Copy, Component)]
+pub(crate) struct Stuck {
+    target: Entity,
+    offset: Vec2,
+}
+
+fn stick_bomb(
+    mut impacts: MessageReader<BulletImpact>,
     mut commands: Commands,
-    player: Single<(&mut WeaponVelocity, &GlobalTransform), With<Player>>,
-    _rocket: Single<(), (With<Rocket>, With<SelectedWeapon>)>,
+    bombs: Query<(), (With<StickyBombShot>, Without<Stuck>)>,
+    layers: Query<&CollisionLayers>,
     transforms: Query<&GlobalTransform>,
-) -> Result {
-    let (mut velocity, player_transform) = player.into_inner();
-    let transform = transforms.get(start.collider1)?;
-    let diff = transform.translation().xy() - player_transform.translation().xy();
-    let dist = diff.length();
-    let angle = diff.normalize_or(Vec2::NEG_Y);
+) {
+    for impact in impacts.read() {
+        if !bombs.contains(impact.bullet)
+            || !layers.get(impact.target).is_ok_and(|layers| {
+                layers.memberships.has_all(Layer::Wall) || layers.memberships.has_all(Layer::Key)
+            })
+        {
+            continue;
+        }
+        let Ok(target) = transforms.get(impact.target) else {
+            continue;
+        };
+        let offset = target
+            .affine()
+            .inverse()
+            .transform_point3(impact.position.extend(0.0))
+            .xy();
+        commands.entity(impact.bullet).insert((
+            RigidBody::Kinematic,
+            LinearVelocity::ZERO,
+            Stuck {
+                target: impact.target,
+                offset,
+            },
+        ));
+    }
+}
 
-    let falloff_rate = 0.003;
-    let force = 5_000.0 * (-falloff_rate * (dist - 300.0).max(0.0)).exp();
-    velocity.0 = velocity.0.max(-angle * force);
+/// Moves stuck bombs with their targets and explodes those whose fuse ran out
+/// or whose target is gone.
+fn sticky_bombs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bombs: Query<(Entity, &mut StickyBombShot, &mut Position, Option<&Stuck>)>,
+    targets: Query<&GlobalTransform>,
+) {
+    for (entity, mut bomb, mut position, stuck) in bombs.iter_mut() {
+        if let Some(stuck) = stuck {
+            match targets.get(stuck.target) {
+                Ok(target) => {
+                    position.0 = target.transform_point(stuck.offset.extend(0.0)).xy();
+                }
+                Err(_) => bomb.fuse = 0.0,
+            }
+        }
+        bomb.fuse -= time.delta_secs();
+        if bomb.fuse > 0.0 {
+            continue;
+        }
+        commands.trigger(Explosion {
+            position: position.0,
+            force: 5_000.0,
+            radius: 200.0,
+            damage: 3.0,
+        });
+        commands.entity(entity).despawn();
+    }
+}
 
-    commands.entity(start.collider1).despawn();
-    Ok(())
+/// Knocks the players away from `position`, falling off with distance.
+///
+/// Within `radius`, keys, enemies and dynamic bodies are pushed away and take
+/// up to `damage`, falling off toward the edge. [`MustDestroy`] keys in range
+/// are destroyed outright when the explosion does damage.
+#[derive(Event)]
+pub struct Explosion {
+    pub position: Vec2,
+    pub force: f32,
+    pub radius: f32,
+    pub damage: f32,
 }
 
+/// Fraction of an [`Explosion`]'s force added to the velocity of dynamic
+/// bodies in its radius, which are lighter than they look.
+const EXPLOSION_BODY_IMPULSE: f32 = 0.2;
+
+fn explosion(
+    explosion: On<Explosion>,
+    mut commands: Commands,
+    spatial: SpatialQuery,
+    mut players: Query<(&mut WeaponVelocity, &GlobalTransform), With<Player>>,
+    mut targets: Query<
+        (
+            &GlobalTransform,
+            Option<&mut Health>,
+            Option<&RigidBody>,
+            Option<&mut LinearVelocity>,
+            Has<MustDestroy>,
+            Has<Enemy>,
+        ),
+        Without<Player>,
+    >,
+    settings: Res<Settings>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    sfx.write(PlaySfx::at(Sfx::Explosion, explosion.position));
+    for (mut velocity, player_transform) in players.iter_mut() {
+        let diff = explosion.position - player_transform.translation().xy();
+        let dist = diff.length();
+        let angle = diff.normalize_or(Vec2::NEG_Y);
+
+        let falloff_rate = 0.003;
+        let force = explosion.force * (-falloff_rate * (dist - 300.0).max(0.0)).exp();
+        velocity.0 = velocity.0.max(-angle * force);
+    }
+
+    if explosion.radius <= 0.0 {
+        return;
+    }
+    commands.run_system_cached_with(
+        spawn_explosion_flash,
+        (explosion.position, explosion.radius),
+    );
+    commands.trigger(ParticleBurst {
+        position: explosion.position,
+        color: GLOW,
+        count: 16,
+    });
+
+    let filter = SpatialQueryFilter::from_mask([Layer::Key, Layer::Enemy, Layer::Pickups]);
+    let hits = spatial.shape_intersections(
+        &Collider::circle(explosion.radius),
+        explosion.position,
+        0.0,
+        &filter,
+    );
+    let enemy_health = settings.difficulty.table().enemy_health;
+    for entity in hits {
+        let Ok((transform, health, body, velocity, must_destroy, enemy)) = targets.get_mut(entity)
+        else {
+            continue;
+        };
+        let position = transform.translation().xy();
+        let offset = position - explosion.position;
+        let falloff = (1.0 - offset.length() / explosion.radius).clamp(0.0, 1.0);
+
+        if body.is_some_and(|body| *body == RigidBody::Dynamic)
+            && let Some(mut velocity) = velocity
+        {
+            velocity.0 +=
+                offset.normalize_or(Vec2::Y) * explosion.force * EXPLOSION_BODY_IMPULSE * falloff;
+        }
+
+        if explosion.damage <= 0.0 {
+            continue;
+        }
+        let Some(mut health) = health else {
+            continue;
+        };
+        let amount = if must_destroy {
+            health.0
+        } else if enemy {
+            explosion.damage * falloff / enemy_health
+        } else {
+            explosion.damage * falloff
+        };
+        apply_damage(&mut commands, entity, &mut health, amount, position);
+    }
+}
+
+/// Seconds the [`Explosion`] flash takes to expand and fade.
+const EXPLOSION_FLASH: f32 = 0.3;
+
+/// Despawned once its tween finishes.
+#[derive(Component)]
+#[require(DespawnOnLevelUnload)]
+struct ExplosionFlash(f32);
+
+fn spawn_explosion_flash(explosion: In<(Vec2, f32)>, mut commands: Commands) {
+    let (position, radius) = *explosion;
+    let target = AnimationTarget.into_target();
+    commands
+        .spawn((
+            ExplosionFlash(EXPLOSION_FLASH),
+            AnimationTarget,
+            Sprite::from_color(GLOW, Vec2::splat(radius * 2.0)),
+            Transform::from_translation(position.extend(2.0)).with_scale(Vec3::splat(0.2)),
+        ))
+        .animation()
+        .insert_tween_here(
+            Duration::from_secs_f32(EXPLOSION_FLASH),
+            EaseKind::QuadraticOut,
+            (
+                target.with(interpolate::scale(Vec3::splat(0.2), Vec3::ONE)),
+                target.with(interpolate::sprite_color(GLOW, GLOW.with_alpha(0.0))),
+            ),
+        );
+}
+
+fn explosion_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut ExplosionFlash)>,
+) {
+    for (entity, mut flash) in flashes.iter_mut() {
+        flash.0 -= time.delta_secs();
+        if flash.0 <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Fires a beam through keys and enemies to the first wall while [`Attack`]
+/// is held, damaging everything along it and draining [`Ammo`] in the air.
 #[derive(Default, Clone, Copy, Component, Reflect)]
-#[require(Weapon, Name::new("Laser"))]
+#[require(Weapon, MaxAmmo(3), Name::new("Laser"), Beam)]
 #[component(on_insert = Laser::insert)]
 #[reflect(Default, Component)]
 pub struct Laser;
 
 impl Laser {
     fn insert(mut world: DeferredWorld, ctx: HookContext) {
-        let mut shape_caster = ShapeCaster::new(Collider::circle(0.5), Vec2::ZERO, 0.0, Dir2::X);
-        shape_caster.query_filter = shape_caster
-            .query_filter
-            .with_mask([Layer::Wall, Layer::Key]);
+        let mut shape_caster = ShapeCaster::new(Collider::circle(0.5), Vec2::ZERO, 0.0, Dir2::X)
+            .with_max_distance(LASER_WEAPON_RANGE)
+            .with_max_hits(16);
+        shape_caster.query_filter =
+            shape_caster
+                .query_filter
+                .with_mask([Layer::Wall, Layer::Key, Layer::Enemy]);
         world.commands().entity(ctx.entity).insert(shape_caster);
     }
 }
 
+/// The beam of a [`Laser`], updated by [`laser`] each step.
+#[derive(Default, Clone, Copy, Component)]
+pub struct Beam {
+    pub active: bool,
+    pub direction: Vec2,
+    /// Distance to the first wall.
+    pub length: f32,
+    /// Whether [`Attack`] was held this step.
+    held: bool,
+    /// Fraction of the next [`Ammo`] drained.
+    drain: f32,
+}
+
+const LASER_WEAPON_RANGE: f32 = 2_000.0;
+
+/// [`Damage`] per second to everything along a [`Beam`].
+const LASER_DAMAGE_PER_SECOND: f32 = 4.0;
+
+/// [`Ammo`] per second a [`Beam`] drains while the player is airborne.
+const LASER_AMMO_PER_SECOND: f32 = 2.0;
+
+fn hold_laser<A: InputAction, S: Component>(
+    attack: On<Fire<A>>,
+    players: Query<&Children, With<Player>>,
+    mut beams: Query<&mut Beam, With<S>>,
+) {
+    let Ok(children) = players.get(attack.context) else {
+        return;
+    };
+    let mut beams = beams.iter_many_mut(children);
+    if let Some(mut beam) = beams.fetch_next() {
+        beam.held = true;
+    }
+}
+
 fn laser(
     mut commands: Commands,
-    aim_vector: Single<&AimVector, With<Player>>,
-    laser: Single<(&mut ShapeCaster, &ShapeHits), (With<Laser>, With<SelectedWeapon>)>,
-    keys: Query<Entity, With<Key>>,
+    time: Res<Time>,
+    players: Query<(&AimVector, Has<Grounded>), With<Player>>,
+    mut lasers: Query<(&mut ShapeCaster, &ShapeHits, &mut Beam, &mut Ammo, &ChildOf), With<Laser>>,
+    layers: Query<&CollisionLayers>,
+    mut targets: Query<(&GlobalTransform, &mut Health, Has<Enemy>)>,
+    settings: Res<Settings>,
 ) {
-    let (mut caster, hits) = laser.into_inner();
-    for entity in keys.iter_many(hits.iter().map(|data| data.entity)) {
-        commands.entity(entity).despawn();
-    }
-    if let Ok(direction) = Dir2::new(aim_vector.0) {
-        caster.direction = direction;
+    let enemy_health = settings.difficulty.table().enemy_health;
+    for (mut caster, hits, mut beam, mut ammo, child_of) in lasers.iter_mut() {
+        let held = std::mem::take(&mut beam.held);
+        let Ok((aim_vector, is_grounded)) = players.get(child_of.parent()) else {
+            beam.active = false;
+            continue;
+        };
+        if let Ok(direction) = Dir2::new(aim_vector.0) {
+            caster.direction = direction;
+        }
+        beam.direction = *caster.direction;
+        beam.length = hits
+            .iter()
+            .filter(|hit| {
+                layers
+                    .get(hit.entity)
+                    .is_ok_and(|layers| layers.memberships.has_all(Layer::Wall))
+            })
+            .map(|hit| hit.distance)
+            .fold(LASER_WEAPON_RANGE, f32::min);
+        // Only set for equipped lasers, see `hold_laser`.
+        beam.active = held && (is_grounded || ammo.0 > 0);
+        if !beam.active {
+            continue;
+        }
+
+        if !is_grounded {
+            beam.drain += LASER_AMMO_PER_SECOND * time.delta_secs();
+            if beam.drain >= 1.0 {
+                beam.drain -= 1.0;
+                ammo.0 = ammo.0.saturating_sub(1);
+            }
+        }
+
+        let damage = LASER_DAMAGE_PER_SECOND * time.delta_secs();
+        for hit in hits.iter().filter(|hit| hit.distance < beam.length) {
+            let Ok((transform, mut health, enemy)) = targets.get_mut(hit.entity) else {
+                continue;
+            };
+            let amount = if enemy { damage / enemy_health } else { damage };
+            let position = transform.translation().xy();
+            apply_damage(&mut commands, hit.entity, &mut health, amount, position);
+        }
     }
 }
 
 #[derive(Component)]
 #[require(
-    Transient,
+    DespawnOnLevelUnload,
     RigidBody::Dynamic,
     LockedAxes::ROTATION_LOCKED,
+    TransformInterpolation,
+    // Pellets are fast enough to tunnel through thin walls.
+    SweptCcd,
     Restitution {
         coefficient: 0.1,
         combine_rule: CoefficientCombine::Average,
     },
-    CollisionLayers::new(Layer::Bullet, Layer::Default.to_bits() | Layer::Wall.to_bits() | Layer::Key.to_bits()),
+    CollisionLayers = Layer::Bullet.collision_layers(),
+    CollisionEventsEnabled,
 )]
 pub struct Bullet;
 
+/// Disabled [`Bullet`]s that [`WeaponDef::spawn_projectiles`] fires again
+/// instead of spawning new ones.
+///
+/// Shotgun blasts would otherwise spawn and despawn a dozen bodies at a time.
+/// Bullets with a [`Projectile::insert`] are never pooled, as their weapon
+/// specific components would not be reset.
+#[derive(Default, Clone, Resource)]
+pub struct BulletPool(Vec<Entity>);
+
+impl BulletPool {
+    /// The disabled bullets waiting to be fired again.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Enables a pooled bullet, or spawns a new one if the pool is empty.
+    fn acquire<'a>(&mut self, commands: &'a mut Commands) -> EntityCommands<'a> {
+        while let Some(entity) = self.0.pop() {
+            if commands.get_entity(entity).is_ok() {
+                let mut bullet = commands.entity(entity);
+                bullet.remove::<Disabled>();
+                return bullet;
+            }
+        }
+        commands.spawn(Pooled)
+    }
+}
+
+/// Returns this [`Bullet`] to the [`BulletPool`] rather than despawning it.
 #[derive(Component)]
-struct BulletVelocityLength {
+pub(crate) struct Pooled;
+
+/// Per shot components of a [`Bullet`], removed when it returns to the
+/// [`BulletPool`] so that it is fired again from a clean state.
+///
+/// The [`PhysicsVolume`] it was in is forgotten along with its damping, a
+/// recycled bullet would otherwise restore them when it next leaves a volume.
+type Shot = (
+    Owner,
+    FiredBy,
+    EnemyBullet,
+    DespawnOnHit,
+    BulletSlowdown,
+    BulletLifetime,
+    Trail,
+    Tracer,
+    InPhysicsVolume,
+    LinearDamping,
+    Spent,
+);
+
+/// Despawns a [`Bullet`], or disables it and returns it to the [`BulletPool`].
+///
+/// Queue it with [`EntityCommands::queue_silenced`], a bullet may hit several
+/// things in one step.
+pub fn despawn_bullet(mut entity: EntityWorldMut) {
+    if !entity.contains::<Pooled>() {
+        entity.despawn();
+        return;
+    }
+    if entity.contains::<Disabled>() {
+        return;
+    }
+    // Tracer streaks are the only children of a bullet, a new one is spawned
+    // when the bullet is fired again.
+    entity.despawn_children();
+    entity
+        .remove::<Shot>()
+        .insert((Disabled, LinearVelocity::ZERO));
+    let bullet = entity.id();
+    entity.resource_mut::<BulletPool>().0.push(bullet);
+}
+
+/// Eases the speed of a [`Bullet`] from `start` to `end`, then despawns it.
+///
+/// Stepped with the fixed timestep rather than tweened so that bullets are
+/// deterministic.
+#[derive(Clone, Copy, Component)]
+pub(crate) struct BulletSlowdown {
     start: f32,
     end: f32,
+    duration: f32,
+    elapsed: f32,
 }
 
-fn bullet_velocity(start: f32, end: f32) -> BulletVelocityLength {
-    BulletVelocityLength { start, end }
+impl BulletSlowdown {
+    fn new(start: f32, end: f32, duration: f32) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+            elapsed: 0.0,
+        }
+    }
 }
 
-impl Interpolator for BulletVelocityLength {
-    type Item = LinearVelocity;
-    fn interpolate(
-        &self,
-        item: &mut Self::Item,
-        value: interpolate::CurrentValue,
-        _: interpolate::PreviousValue,
-    ) {
-        if item.0 != Vec2::ZERO {
-            let new_length = self.start.lerp(self.end, value);
-            item.0 = item.0.normalize() * new_length;
+/// Stages that resolve each [`BulletImpact`] in order, at the start of
+/// [`WeaponSet`].
+///
+/// Weapons react to the impacts of their own projectiles in
+/// [`ImpactSet::Effects`] rather than observing collisions themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum ImpactSet {
+    /// Shot targets and other level switches.
+    Keys,
+    /// [`Damage`] to anything with [`Health`], keys included.
+    Destructibles,
+    /// Enemy bullets hitting players, and players hitting each other.
+    Enemies,
+    /// Sounds and weapon specific reactions, such as explosions.
+    Effects,
+    /// [`DespawnOnHit`] bullets are despawned or bounced.
+    Despawn,
+}
+
+/// Written when a [`Bullet`] starts touching something other than a
+/// [`PhysicsVolume`] or [`WindZone`], then resolved in [`ImpactSet`] order on
+/// the next step.
+///
+/// Contacts are found during physics in [`FixedPostUpdate`], so a bullet
+/// lives on for one more physics step after it hits. It may move through thin
+/// geometry and touch more things in that step, a [`DespawnOnHit`] bullet only
+/// reports the first thing that stops it.
+#[derive(Debug, Clone, Copy, Message)]
+pub struct BulletImpact {
+    pub bullet: Entity,
+    /// The entity that was hit.
+    pub target: Entity,
+    pub position: Vec2,
+    /// Surface normal of `target` at the contact, or against the velocity
+    /// when the target is a sensor.
+    pub normal: Vec2,
+    /// Velocity of the bullet when it hit.
+    pub velocity: Vec2,
+    /// The weapon that fired the bullet, see [`FiredBy`].
+    pub weapon: Option<Entity>,
+}
+
+fn bullet_impact(
+    start: On<CollisionStart>,
+    mut bullets: Query<
+        (
+            &GlobalTransform,
+            &LinearVelocity,
+            Option<&FiredBy>,
+            Option<&mut Spent>,
+            Option<&Ricochet>,
+        ),
+        With<Bullet>,
+    >,
+    volumes: Query<(), Or<(With<PhysicsVolume>, With<WindZone>)>>,
+    deflectors: Query<(), With<Deflecting>>,
+    layers: Query<&CollisionLayers>,
+    collisions: Collisions,
+    mut impacts: MessageWriter<BulletImpact>,
+) {
+    let Ok((transform, velocity, fired_by, spent, ricochet)) = bullets.get_mut(start.collider1)
+    else {
+        return;
+    };
+    if volumes.contains(start.collider2) {
+        return;
+    }
+    if let Some(mut spent) = spent {
+        if spent.0 {
+            return;
+        }
+        spent.0 = !deflectors.contains(start.collider2)
+            && !bounces(ricochet, layers.get(start.collider2).ok());
+    }
+    let normal = collisions
+        .get(start.collider1, start.collider2)
+        .and_then(|pair| {
+            let manifold = pair.manifolds.first()?;
+            // Manifold normals point from the first collider of the pair.
+            Some(if pair.collider1 == start.collider1 {
+                -manifold.normal
+            } else {
+                manifold.normal
+            })
+        })
+        .unwrap_or_else(|| -velocity.normalize_or_zero());
+    impacts.write(BulletImpact {
+        bullet: start.collider1,
+        target: start.collider2,
+        position: transform.translation().xy(),
+        normal,
+        velocity: velocity.0,
+        weapon: fired_by.map(|fired_by| fired_by.0),
+    });
+}
+
+/// Despawns a [`Bullet`] on its first impact, unless it was deflected or has
+/// a [`Ricochet`] left.
+#[derive(Component)]
+#[require(Spent)]
+pub struct DespawnOnHit;
+
+/// Whether a [`DespawnOnHit`] bullet has reported the impact that despawns
+/// it, its later contacts are ignored.
+#[derive(Default, Clone, Copy, Component)]
+pub(crate) struct Spent(bool);
+
+/// Bounces a [`Bullet`] off walls this many more times before it despawns.
+///
+/// The solver reflects the velocity off the contact normal, so the bullet
+/// bounces with full restitution and no friction.
+#[derive(Clone, Copy, Component)]
+#[require(DespawnOnHit)]
+#[component(on_add = Ricochet::add)]
+pub struct Ricochet(pub u8);
+
+impl Ricochet {
+    fn add(mut world: DeferredWorld, ctx: HookContext) {
+        world.commands().entity(ctx.entity).insert((
+            Restitution {
+                coefficient: 1.0,
+                combine_rule: CoefficientCombine::Max,
+            },
+            Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
+        ));
+    }
+}
+
+fn impact_sfx(
+    mut impacts: MessageReader<BulletImpact>,
+    bullets: Query<(), With<DespawnOnHit>>,
+    deflectors: Query<(), With<Deflecting>>,
+    mut sfx: MessageWriter<PlaySfx>,
+) {
+    for impact in impacts.read() {
+        if bullets.contains(impact.bullet) && !deflectors.contains(impact.target) {
+            sfx.write(PlaySfx::at(Sfx::Impact, impact.position));
+        }
+    }
+}
+
+fn despawn_on_hit(
+    mut impacts: MessageReader<BulletImpact>,
+    mut commands: Commands,
+    mut bullets: Query<Option<&mut Ricochet>, With<DespawnOnHit>>,
+    deflectors: Query<(), With<Deflecting>>,
+    layers: Query<&CollisionLayers>,
+) {
+    for impact in impacts.read() {
+        let Ok(ricochet) = bullets.get_mut(impact.bullet) else {
+            continue;
+        };
+        if deflectors.contains(impact.target) {
+            continue;
+        }
+        if let Some(mut ricochet) = ricochet
+            && bounces(Some(&*ricochet), layers.get(impact.target).ok())
+        {
+            ricochet.0 -= 1;
+            continue;
+        }
+        commands
+            .entity(impact.bullet)
+            .queue_silenced(despawn_bullet);
+    }
+}
+
+/// Whether a bullet with `ricochet` bounces off a target on `layers`.
+fn bounces(ricochet: Option<&Ricochet>, layers: Option<&CollisionLayers>) -> bool {
+    ricochet.is_some_and(|ricochet| ricochet.0 > 0)
+        && layers.is_some_and(|layers| layers.memberships.has_all(Layer::Wall))
+}
+
+/// Seconds until a [`Bullet`] despawns.
+#[derive(Clone, Copy, Component)]
+pub struct BulletLifetime(pub f32);
+
+fn expire_bullets(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bullets: Query<(Entity, &mut BulletLifetime)>,
+) {
+    for (entity, mut lifetime) in bullets.iter_mut() {
+        lifetime.0 -= time.delta_secs();
+        if lifetime.0 <= 0.0 {
+            commands.entity(entity).queue_silenced(despawn_bullet);
         }
     }
 }
 
-fn despawn_bullets(
+fn slow_bullets(
     mut commands: Commands,
-    mut reader: MessageReader<TimeRunnerEnded>,
-    bullets: Query<(), With<Bullet>>,
+    time: Res<Time>,
+    mut bullets: Query<(Entity, &mut BulletSlowdown, &mut LinearVelocity)>,
 ) {
-    for event in reader.read() {
-        if event.is_completed() && bullets.contains(event.entity) {
-            commands.entity(event.entity).despawn();
+    for (entity, mut slowdown, mut velocity) in bullets.iter_mut() {
+        slowdown.elapsed += time.delta_secs();
+        let t = (slowdown.elapsed / slowdown.duration).min(1.0);
+        if velocity.0 != Vec2::ZERO {
+            let length = slowdown
+                .start
+                .lerp(slowdown.end, EaseKind::QuadraticOut.sample(t));
+            velocity.0 = velocity.0.normalize() * length;
+        }
+        if t >= 1.0 {
+            commands.entity(entity).queue_silenced(despawn_bullet);
         }
     }
 }
@@ -344,55 +1940,163 @@ fn random_direction_in_arc(dir: Vec2, arc_radians: f32, rng: &mut impl Rng) -> V
     }
 }
 
-#[derive(Default, Clone, Copy, Component, Reflect)]
+/// A weapon lying in the level, picked up with [`PickUp`] within `radius`.
+#[derive(Clone, Copy, Component, Reflect)]
 #[require(
     Transform,
+    DespawnOnLevelUnload,
     RigidBody::Dynamic,
     SerializedColliderConstructor = rectangle(50.0, 50.0),
-    CollisionLayers::new(Layer::Pickups, LayerMask::ALL),
+    CollisionLayers = Layer::Pickups.collision_layers(),
     DebugPickingColor::new(PURPLE),
 )]
 #[reflect(Default, Component)]
-pub struct WeaponPickup;
+pub struct WeaponPickup {
+    pub radius: f32,
+}
+
+impl Default for WeaponPickup {
+    fn default() -> Self {
+        Self { radius: 100.0 }
+    }
+}
+
+/// The nearest [`WeaponPickup`] whose radius reaches `position`.
+fn reachable_pickup<'a, T>(
+    position: Vec2,
+    pickups: impl IntoIterator<Item = (T, &'a GlobalTransform, &'a WeaponPickup)>,
+) -> Option<(T, Vec2)> {
+    pickups
+        .into_iter()
+        .map(|(item, transform, pickup)| (item, transform.translation().xy(), pickup.radius))
+        .filter(|(_, pickup, radius)| pickup.distance_squared(position) < radius * radius)
+        .min_by(|(_, a, _), (_, b, _)| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+        .map(|(item, pickup, _)| (item, pickup))
+}
+
+/// Shows the [`PickUp`] binding above the [`WeaponPickup`] the first player
+/// would pick up.
+#[derive(Component)]
+struct PickupPrompt;
+
+fn pickup_prompt(
+    mut commands: Commands,
+    prompt: Option<Single<(&mut Text2d, &mut Transform, &mut Visibility), With<PickupPrompt>>>,
+    player: Option<Single<&GlobalTransform, (With<Player>, Without<Partner>)>>,
+    pickups: Query<(Option<&Name>, &GlobalTransform, &WeaponPickup)>,
+    keybinds: Res<Keybinds>,
+) {
+    let Some(prompt) = prompt else {
+        commands.spawn((
+            PickupPrompt,
+            Text2d::default(),
+            TextFont::from_font_size(14.0),
+            Transform::default(),
+            Visibility::Hidden,
+        ));
+        return;
+    };
+    let (mut text, mut transform, mut visibility) = prompt.into_inner();
+    let Some((name, position)) =
+        player.and_then(|player| reachable_pickup(player.translation().xy(), pickups.iter()))
+    else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let button = keybinds
+        .pick_up
+        .iter()
+        .find(|button| !button.is_gamepad())
+        .or(keybinds.pick_up.first())
+        .map(|button| button.label())
+        .unwrap_or_default();
+    let prompt = match name {
+        Some(name) => format!("Press {button} to pick up {name}"),
+        None => format!("Press {button} to pick up"),
+    };
+    if text.0 != prompt {
+        text.0 = prompt;
+    }
+    transform.translation = (position + Vec2::Y * 50.0).extend(10.0);
+    *visibility = Visibility::Inherited;
+}
+
+/// Turns a player's weapon back into a [`WeaponPickup`] at `position`.
+///
+/// [`Ammo`] is left as is, so picking the weapon up again does not refill it.
+fn drop_weapon(weapon: &mut EntityCommands, position: Vec2) {
+    weapon
+        .remove::<(ChildOf, PrimaryWeapon, SecondaryWeapon, Burst)>()
+        .insert((
+            WeaponPickup::default(),
+            Transform::from_translation(position.extend(0.0)),
+        ));
+}
+
+/// Triggered on a [`Player`] when they pick up a [`WeaponPickup`].
+#[derive(EntityEvent)]
+pub struct WeaponPickedUp {
+    pub entity: Entity,
+    /// Name the weapon was registered with, see [`register_weapon`].
+    pub weapon: String,
+}
 
 fn weapon_pickup(
-    _: On<Fire<PickUp>>,
+    pick_up: On<Fire<PickUp>>,
     mut commands: Commands,
-    player: Single<(Entity, &GlobalTransform), With<Player>>,
-    weapon: Query<Entity, With<SelectedWeapon>>,
-    pickups: Query<(Entity, &GlobalTransform), With<WeaponPickup>>,
+    players: Query<(Entity, &GlobalTransform, Option<&Children>), With<Player>>,
+    primary: Query<Entity, With<PrimaryWeapon>>,
+    secondary: Query<Entity, With<SecondaryWeapon>>,
+    pickups: Query<(Entity, &GlobalTransform, &WeaponPickup)>,
+    components: Query<EntityRef>,
+    registry: Res<Registry>,
 ) {
-    let radius = 100.0;
-    let (player, player_transform) = player.into_inner();
+    let Ok((player, player_transform, children)) = players.get(pick_up.context) else {
+        return;
+    };
     let player_translation = player_transform.translation().xy();
-    for (pickup, pickup_transform) in pickups.iter() {
-        if pickup_transform
-            .translation()
-            .xy()
-            .distance_squared(player_translation)
-            < radius * radius
-        {
-            commands
-                .entity(pickup)
-                .remove::<(
-                    Transform,
-                    WeaponPickup,
-                    Collider,
-                    SerializedColliderConstructor,
-                    ColliderConstructor,
-                    Sprite,
-                    DebugPickingColor,
-                )>()
-                .insert((SelectedWeapon, ChildOf(player)));
-            for entity in weapon.iter() {
-                commands
-                    .entity(entity)
-                    .remove::<(SelectedWeapon, ChildOf)>()
-                    .insert((
-                        WeaponPickup,
-                        Transform::from_translation(player_translation.extend(0.0)),
-                    ));
-            }
+    let Some((pickup, _)) = reachable_pickup(player_translation, pickups.iter()) else {
+        return;
+    };
+    commands
+        .entity(pickup)
+        .remove::<(
+            Transform,
+            WeaponPickup,
+            Collider,
+            SerializedColliderConstructor,
+            ColliderConstructor,
+            Sprite,
+            DebugPickingColor,
+        )>()
+        .insert((PrimaryWeapon, ChildOf(player)));
+    // The previous primary fills an empty secondary slot, or is
+    // dropped as a pickup that keeps its ammo.
+    let has_secondary = secondary
+        .iter_many(children.into_iter().flatten())
+        .next()
+        .is_some();
+    for entity in primary.iter_many(children.into_iter().flatten()) {
+        let mut entity = commands.entity(entity);
+        entity.remove::<PrimaryWeapon>();
+        if has_secondary {
+            drop_weapon(&mut entity, player_translation);
+        } else {
+            entity.insert(SecondaryWeapon);
         }
     }
+    if let Ok(components) = components.get(pickup)
+        && let Some(registered) = registry
+            .weapons
+            .iter()
+            .find(|weapon| components.contains_id(weapon.component))
+    {
+        commands.trigger(WeaponPickedUp {
+            entity: player,
+            weapon: registered.name.clone(),
+        });
+    }
 }
@@ -0,0 +1,147 @@
+use avian2d::prelude::{Collider, LinearDamping, RigidBody};
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use shplat::{
+    Seed,
+    collision::Layer,
+    damage::Health,
+    level::{InPhysicsVolume, Level, LevelBoundary, LevelLoading, PhysicsVolume, rectangle},
+    weapon::{Bullet, BulletPool, Projectile, WeaponDef, despawn_bullet},
+};
+
+/// Loads `shotgun_1` and returns the app along with a point well clear of its
+/// walls.
+fn loaded_level() -> (App, Vec2) {
+    let mut app = App::new();
+    app.insert_resource(Seed(7))
+        .add_plugins(shplat::headless_plugin)
+        .insert_resource(Level("shotgun_1".to_string()));
+    for _ in 0..1_000 {
+        app.update();
+        let world = app.world();
+        let boundary = world.resource::<LevelBoundary>().0;
+        if !world.resource::<LevelLoading>().0 && !boundary.is_empty() {
+            // The boundary is the walls inflated by hundreds of pixels.
+            return (app, boundary.max - Vec2::splat(200.0));
+        }
+    }
+    panic!("level did not load");
+}
+
+/// Fires a single standing `projectile` at `origin` through the
+/// [`BulletPool`] and returns the bullet.
+fn fire(app: &mut App, projectile: Projectile, origin: Vec2) -> Entity {
+    app.world_mut()
+        .run_system_once(
+            move |mut commands: Commands, mut pool: ResMut<BulletPool>| {
+                let weapon = WeaponDef {
+                    projectile: Some(projectile),
+                    ..default()
+                };
+                weapon.spawn_projectiles(
+                    &mut commands,
+                    &mut pool,
+                    &mut rand::rng(),
+                    origin,
+                    Vec2::X,
+                    |_| {},
+                );
+            },
+        )
+        .unwrap();
+    let world = app.world_mut();
+    world
+        .query_filtered::<(Entity, &Transform), With<Bullet>>()
+        .iter(world)
+        .find(|(_, transform)| transform.translation.xy() == origin)
+        .map(|(entity, _)| entity)
+        .expect("bullet was not fired")
+}
+
+fn standing() -> Projectile {
+    Projectile {
+        speed: 0.0,
+        ..default()
+    }
+}
+
+fn steps(app: &mut App, count: usize) {
+    for _ in 0..count {
+        app.update();
+    }
+}
+
+#[test]
+fn recycled_bullet_forgets_physics_volume() {
+    let (mut app, clear) = loaded_level();
+    app.world_mut().spawn((
+        PhysicsVolume {
+            damping: 5.0,
+            ..default()
+        },
+        Transform::from_translation(clear.extend(0.0)),
+        rectangle(100.0, 100.0),
+    ));
+    steps(&mut app, 2);
+
+    let bullet = fire(&mut app, standing(), clear);
+    steps(&mut app, 3);
+    let world = app.world();
+    assert!(world.get::<InPhysicsVolume>(bullet).is_some());
+    assert_eq!(
+        world.get::<LinearDamping>(bullet).map(|damping| damping.0),
+        Some(5.0)
+    );
+
+    despawn_bullet(app.world_mut().entity_mut(bullet));
+    let elsewhere = clear - Vec2::new(300.0, 0.0);
+    let recycled = fire(&mut app, standing(), elsewhere);
+    assert_eq!(recycled, bullet, "the bullet was not taken from the pool");
+
+    steps(&mut app, 3);
+    let world = app.world();
+    assert!(world.get::<InPhysicsVolume>(recycled).is_none());
+    assert!(
+        world
+            .get::<LinearDamping>(recycled)
+            .is_none_or(|damping| damping.0 == 0.0)
+    );
+}
+
+#[test]
+fn pooled_bullet_resolves_one_of_two_hits() {
+    let (mut app, clear) = loaded_level();
+    let targets = [Vec2::new(-4.0, 0.0), Vec2::new(4.0, 0.0)].map(|offset| {
+        app.world_mut()
+            .spawn((
+                Health(10.0),
+                RigidBody::Static,
+                Collider::circle(8.0),
+                Layer::Default.collision_layers(),
+                Transform::from_translation((clear + offset).extend(0.0)),
+            ))
+            .id()
+    });
+
+    let bullet = fire(
+        &mut app,
+        Projectile {
+            despawn_on_hit: true,
+            ..standing()
+        },
+        clear,
+    );
+    steps(&mut app, 5);
+
+    let world = app.world();
+    let pooled = world
+        .resource::<BulletPool>()
+        .iter()
+        .filter(|pooled| *pooled == bullet)
+        .count();
+    assert_eq!(pooled, 1, "the bullet was not returned to the pool once");
+    let damage: f32 = targets
+        .iter()
+        .map(|target| 10.0 - world.get::<Health>(*target).unwrap().0)
+        .sum();
+    assert_eq!(damage, Projectile::default().damage);
+}
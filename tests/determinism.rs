@@ -0,0 +1,75 @@
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use shplat::{Seed, level::Level, player::Player, weapon::Bullet};
+
+/// Keys held down during a range of fixed steps.
+const INPUTS: &[(KeyCode, std::ops::Range<usize>)] = &[
+    (KeyCode::KeyD, 0..90),
+    (KeyCode::Space, 20..35),
+    (KeyCode::ArrowUp, 40..60),
+    (KeyCode::KeyA, 120..200),
+    (KeyCode::Space, 150..160),
+];
+
+const STEPS: usize = 240;
+
+/// Runs [`INPUTS`] in `level` and returns the player and bullet state.
+fn simulate(level: &str, seed: u64) -> (Vec2, Vec2, Vec<Vec2>) {
+    let mut app = App::new();
+    app.insert_resource(Seed(seed))
+        .add_plugins(shplat::headless_plugin)
+        .insert_resource(Level(level.to_string()));
+
+    let mut loading = 0;
+    while app
+        .world_mut()
+        .query_filtered::<(), With<Player>>()
+        .iter(app.world())
+        .next()
+        .is_none()
+    {
+        loading += 1;
+        assert!(loading < 1_000, "level `{level}` did not load");
+        app.update();
+    }
+
+    for step in 0..STEPS {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        for (key, range) in INPUTS {
+            if range.start == step {
+                input.press(*key);
+            } else if range.end == step {
+                input.release(*key);
+            }
+        }
+        if step == 45 {
+            let mut mouse = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+            mouse.press(MouseButton::Left);
+        } else if step == 46 {
+            let mut mouse = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+            mouse.release(MouseButton::Left);
+        }
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let (transform, velocity) = world
+        .query_filtered::<(&Transform, &LinearVelocity), With<Player>>()
+        .single(world)
+        .unwrap();
+    let (position, velocity) = (transform.translation.xy(), velocity.0);
+    let mut bullets = world
+        .query_filtered::<&Transform, With<Bullet>>()
+        .iter(world)
+        .map(|transform| transform.translation.xy())
+        .collect::<Vec<_>>();
+    bullets.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    (position, velocity, bullets)
+}
+
+#[test]
+fn replayed_inputs_reach_identical_state() {
+    let first = simulate("shotgun_1", 7);
+    let second = simulate("shotgun_1", 7);
+    assert_eq!(first, second);
+}
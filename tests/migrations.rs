@@ -0,0 +1,274 @@
+use bevy::{
+    prelude::*,
+    reflect::FromReflect,
+    scene::{DynamicEntity, serde::SceneDeserializer},
+};
+use serde::de::DeserializeSeed;
+use shplat::{
+    level::{Door, LEVEL_VERSION, SpawnPoint, migrate},
+    weapon::{GravityGun, PrimaryWeapon, WeaponPickup},
+};
+
+fn transform(x: f32, y: f32) -> String {
+    format!(
+        "(
+          translation: ({x:?}, {y:?}, 0.0),
+          rotation: (0.0, 0.0, 0.0, 1.0),
+          scale: (1.0, 1.0, 1.0),
+        )"
+    )
+}
+
+fn entity(id: u64, components: &[(&str, &str)]) -> String {
+    let mut entity = format!("    {id}: (\n      components: {{\n");
+    for (type_path, value) in components {
+        entity.push_str(&format!("        \"{type_path}\": {value},\n"));
+    }
+    entity.push_str("      },\n    ),\n");
+    entity
+}
+
+/// A RON level of `entities`, with the header of `version`.
+fn level(version: u32, entities: &[String]) -> String {
+    let header = match version {
+        0 => String::new(),
+        version => format!("// version {version}\n"),
+    };
+    format!(
+        "{header}(\n  resources: {{}},\n  entities: {{\n{}  }},\n)\n",
+        entities.concat()
+    )
+}
+
+/// A level with a player, their weapons and a pickup, written as `version`
+/// would have.
+fn fixture(version: u32) -> String {
+    let gravity_gun = if version < 2 { "()" } else { "(flip: true)" };
+    let slot = if version < 3 {
+        "shplat::weapon::SelectedWeapon"
+    } else {
+        "shplat::weapon::PrimaryWeapon"
+    };
+    let pickup = if version < 4 { "()" } else { "(radius: 100.0)" };
+    let mut entities = vec![
+        entity(
+            4294967296,
+            &[
+                ("bevy_ecs::name::Name", "\"Player\""),
+                (
+                    "bevy_transform::components::transform::Transform",
+                    &transform(-40.0, 25.0),
+                ),
+                ("shplat::level::Serialize", "()"),
+                ("shplat::player::Player", "()"),
+            ],
+        ),
+        entity(
+            4294967297,
+            &[
+                ("shplat::level::Serialize", "()"),
+                ("shplat::weapon::GravityGun", gravity_gun),
+                (slot, "()"),
+            ],
+        ),
+        entity(
+            4294967298,
+            &[
+                (
+                    "bevy_transform::components::transform::Transform",
+                    &transform(300.0, 0.0),
+                ),
+                ("shplat::level::Serialize", "()"),
+                ("shplat::weapon::WeaponPickup", pickup),
+            ],
+        ),
+    ];
+    if version >= 5 {
+        entities.push(entity(
+            4294967299,
+            &[
+                ("bevy_ecs::name::Name", "\"Spawn Point\""),
+                (
+                    "bevy_transform::components::transform::Transform",
+                    &transform(-40.0, 25.0),
+                ),
+                ("shplat::level::Serialize", "()"),
+                ("shplat::level::SpawnPoint", "()"),
+            ],
+        ));
+    }
+    level(version, &entities)
+}
+
+/// Deserializes a migrated level with the game's types.
+fn deserialize(level: &str) -> DynamicScene {
+    let app = shplat::headless_app();
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    let mut deserializer = ron::Deserializer::from_str(level).unwrap();
+    SceneDeserializer {
+        type_registry: &type_registry,
+    }
+    .deserialize(&mut deserializer)
+    .unwrap_or_else(|err| panic!("{err}\n{level}"))
+}
+
+fn component<T: FromReflect + TypePath>(entity: &DynamicEntity) -> Option<T> {
+    entity
+        .components
+        .iter()
+        .filter(|component| {
+            component
+                .get_represented_type_info()
+                .is_some_and(|info| info.type_path() == T::type_path())
+        })
+        .find_map(|component| T::from_reflect(component.as_partial_reflect()))
+}
+
+/// Every `T` in the `scene`.
+fn components<T: FromReflect + TypePath>(scene: &DynamicScene) -> Vec<T> {
+    scene.entities.iter().filter_map(component::<T>).collect()
+}
+
+fn migrated(level: String) -> String {
+    let (_, migrated) = migrate(level).unwrap();
+    assert!(
+        migrated.starts_with(&format!("// version {LEVEL_VERSION}\n")),
+        "{migrated}"
+    );
+    migrated
+}
+
+#[test]
+fn every_version_migrates_to_current() {
+    for version in 0..LEVEL_VERSION {
+        let (from, level) = migrate(fixture(version)).unwrap();
+        assert_eq!(from, version);
+        assert_eq!(level, fixture(LEVEL_VERSION), "from version {version}");
+
+        let scene = deserialize(&level);
+        let gravity_guns = components::<GravityGun>(&scene);
+        assert_eq!(gravity_guns.len(), 1);
+        assert!(gravity_guns[0].flip);
+        assert_eq!(components::<PrimaryWeapon>(&scene).len(), 1);
+        let pickups = components::<WeaponPickup>(&scene);
+        assert_eq!(pickups.len(), 1);
+        assert_eq!(pickups[0].radius, 100.0);
+    }
+}
+
+#[test]
+fn current_level_is_left_as_is() {
+    let level = fixture(LEVEL_VERSION);
+    assert_eq!(migrate(level.clone()), Ok((LEVEL_VERSION, level)));
+}
+
+#[test]
+fn newer_level_is_refused() {
+    assert!(
+        migrate(fixture(LEVEL_VERSION).replace(
+            &format!("// version {LEVEL_VERSION}"),
+            &format!("// version {}", LEVEL_VERSION + 1),
+        ))
+        .is_err()
+    );
+}
+
+#[test]
+fn only_component_keys_are_migrated() {
+    let name = "\"shplat::weapon::SelectedWeapon\"";
+    let level = migrated(level(
+        2,
+        &[entity(
+            4294967296,
+            &[
+                ("bevy_ecs::name::Name", name),
+                ("shplat::level::Serialize", "()"),
+                ("shplat::weapon::SelectedWeapon", "()"),
+            ],
+        )],
+    ));
+    let scene = deserialize(&level);
+    assert_eq!(
+        components::<Name>(&scene),
+        [Name::new("shplat::weapon::SelectedWeapon")]
+    );
+    assert_eq!(components::<PrimaryWeapon>(&scene).len(), 1);
+}
+
+/// The translation of each spawn point in the `scene`.
+fn spawn_points(scene: &DynamicScene) -> Vec<Vec3> {
+    scene
+        .entities
+        .iter()
+        .filter(|entity| component::<SpawnPoint>(entity).is_some())
+        .map(|entity| component::<Transform>(entity).unwrap().translation)
+        .collect()
+}
+
+#[test]
+fn spawn_point_is_added_where_the_player_was() {
+    for version in 0..5 {
+        let scene = deserialize(&migrated(fixture(version)));
+        assert_eq!(
+            spawn_points(&scene),
+            [Vec3::new(-40.0, 25.0, 0.0)],
+            "from version {version}"
+        );
+    }
+}
+
+#[test]
+fn spawn_point_is_not_added_twice() {
+    // A version 4 level with a spawn point placed by hand.
+    let level = fixture(5).replace("// version 5", "// version 4");
+    let scene = deserialize(&migrated(level));
+    assert_eq!(spawn_points(&scene), [Vec3::new(-40.0, 25.0, 0.0)]);
+}
+
+fn door(id: u64, door: &str) -> String {
+    entity(
+        id,
+        &[
+            ("shplat::level::Door", door),
+            ("shplat::level::Serialize", "()"),
+        ],
+    )
+}
+
+#[test]
+fn every_door_gains_an_entrance() {
+    let level = migrated(level(
+        5,
+        &[
+            door(4294967296, r#"("shotgun_2")"#),
+            door(4294967297, r#"("shotgun_3")"#),
+            door(4294967298, r#"("shotgun_4")"#),
+        ],
+    ));
+    let doors = components::<Door>(&deserialize(&level));
+    let levels: Vec<_> = doors.iter().map(|door| door.level.as_str()).collect();
+    assert_eq!(levels, ["shotgun_2", "shotgun_3", "shotgun_4"]);
+    assert!(doors.iter().all(|door| door.entrance.is_none()));
+}
+
+#[test]
+fn door_with_an_entrance_is_left_as_is() {
+    let with_entrance = r#"(level: "shotgun_3", entrance: Some("roof"))"#;
+    let level = migrated(level(
+        5,
+        &[
+            door(4294967296, r#"("shotgun_2")"#),
+            door(4294967297, with_entrance),
+        ],
+    ));
+    assert!(level.contains(with_entrance), "{level}");
+    let doors = components::<Door>(&deserialize(&level));
+    assert_eq!(doors.len(), 2);
+    assert_eq!(doors[0].level, "shotgun_2");
+    assert_eq!(doors[0].entrance, None);
+    assert_eq!(doors[1].level, "shotgun_3");
+    assert_eq!(doors[1].entrance.as_deref(), Some("roof"));
+
+    let current = level.clone();
+    assert_eq!(migrate(current), Ok((LEVEL_VERSION, level)));
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "netplay")]
+
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+use shplat::{
+    Seed,
+    damage::Health,
+    level::Level,
+    netplay::{LocalInput, SyncTest},
+    player::Player,
+    weapon::Bullet,
+};
+
+/// Keys held down during a range of frames.
+const INPUTS: &[(KeyCode, std::ops::Range<usize>)] = &[
+    (KeyCode::KeyD, 0..90),
+    (KeyCode::Space, 20..35),
+    (KeyCode::ArrowUp, 40..60),
+    (KeyCode::KeyA, 120..200),
+    (KeyCode::Space, 150..160),
+    (KeyCode::KeyD, 230..300),
+];
+
+/// Frames on which the mouse is clicked to fire.
+const CLICKS: &[usize] = &[45, 100, 180, 250];
+
+const FRAMES: usize = 300;
+
+/// Players ordered by position, with their velocity and health.
+type PlayerState = Vec<(Vec2, Vec2, f32)>;
+
+/// Runs [`INPUTS`] and [`CLICKS`] through a [`SyncTest`] that simulates each
+/// frame again `check_distance` frames later, returns the player and bullet
+/// state.
+fn simulate(check_distance: usize) -> (PlayerState, Vec<Vec2>) {
+    let mut app = App::new();
+    app.insert_resource(Seed(7))
+        .insert_resource(SyncTest { check_distance })
+        .add_plugins(shplat::headless_plugin)
+        .insert_resource(Level("shotgun_1".to_string()));
+
+    let mut starting = 0;
+    while app
+        .world_mut()
+        .query_filtered::<(), With<LocalInput>>()
+        .iter(app.world())
+        .next()
+        .is_none()
+    {
+        starting += 1;
+        assert!(starting < 1_000, "the sync test did not start");
+        app.update();
+    }
+
+    for frame in 0..FRAMES {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        for (key, range) in INPUTS {
+            if range.start == frame {
+                input.press(*key);
+            } else if range.end == frame {
+                input.release(*key);
+            }
+        }
+        let mut mouse = app.world_mut().resource_mut::<ButtonInput<MouseButton>>();
+        if CLICKS.contains(&frame) {
+            mouse.press(MouseButton::Left);
+        } else if CLICKS.contains(&frame.wrapping_sub(1)) {
+            mouse.release(MouseButton::Left);
+        }
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let mut players = world
+        .query_filtered::<(&Transform, &LinearVelocity, &Health), With<Player>>()
+        .iter(world)
+        .map(|(transform, velocity, health)| (transform.translation.xy(), velocity.0, health.0))
+        .collect::<Vec<_>>();
+    players.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.y.total_cmp(&b.0.y)));
+    let mut bullets = world
+        .query_filtered::<&Transform, With<Bullet>>()
+        .iter(world)
+        .map(|transform| transform.translation.xy())
+        .collect::<Vec<_>>();
+    bullets.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    (players, bullets)
+}
+
+#[test]
+fn rollbacks_reach_the_same_state() {
+    let (players, bullets) = simulate(0);
+    assert_eq!(players.len(), 2, "the partner did not join");
+    assert_eq!(simulate(7), (players, bullets));
+}